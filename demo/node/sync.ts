@@ -0,0 +1,59 @@
+import { NodeEngine } from "@surrealdb/node";
+import { RecordId, Surreal } from "surrealdb";
+
+// Opens an in-memory database, keeping hold of its engine to run syncs
+async function open(name: string): Promise<[Surreal, NodeEngine]> {
+    let engine: NodeEngine | undefined;
+
+    const surreal = new Surreal({
+        engines: {
+            mem: (ctx) => {
+                engine = new NodeEngine(ctx);
+                return engine;
+            },
+        },
+    });
+
+    await surreal.connect("mem://");
+    await surreal.use({ namespace: "demo", database: name });
+    await surreal.query("DEFINE TABLE note CHANGEFEED 1h");
+
+    if (!engine) throw new Error("Engine was not created");
+
+    return [surreal, engine];
+}
+
+const [client, clientEngine] = await open("client");
+const [server, serverEngine] = await open("server");
+
+// The transport exchanges delta files directly between the two engines
+let serverVersion = "0";
+
+const transport = {
+    async pull(since: string) {
+        const { data, versionstamp } = await serverEngine.exportChanges({ since });
+        serverVersion = versionstamp ?? serverVersion;
+        return data;
+    },
+    async push(delta: Uint8Array) {
+        await serverEngine.importChanges(delta);
+    },
+};
+
+const options = { tables: ["note"] };
+
+await client.upsert(new RecordId("note", "a")).content({ text: "from client", updated_at: 1 });
+await server.upsert(new RecordId("note", "b")).content({ text: "from server", updated_at: 1 });
+
+console.log("First sync =", await clientEngine.sync("server", transport, options));
+
+// Both sides edit the same record, the later write wins
+await client.upsert(new RecordId("note", "a")).content({ text: "client edit", updated_at: 2 });
+await server.upsert(new RecordId("note", "a")).content({ text: "server edit", updated_at: 3 });
+
+console.log("Second sync =", await clientEngine.sync("server", transport, options));
+console.log("Client =", await client.select(new RecordId("note", "a")));
+console.log("Server =", await server.select(new RecordId("note", "a")));
+
+await client.close();
+await server.close();
@@ -0,0 +1,97 @@
+import { createWasmEngines } from "@surrealdb/wasm";
+import { Surreal, Table } from "surrealdb";
+
+// Usage: bun bench.ts [rows] [row size] [--save <file>] [--compare <file>]
+//
+// Run with `--save` on the previous build to record a baseline, then with
+// `--compare` on the current build to print the change in throughput.
+
+interface BenchResult {
+    rows: number;
+    rowSize: number;
+    elapsed: number;
+    throughput: number;
+}
+
+const args = Bun.argv.slice(2);
+
+function option(name: string): string | undefined {
+    const index = args.indexOf(name);
+    if (index === -1) return undefined;
+    const [, value] = args.splice(index, 2);
+    return value;
+}
+
+const savePath = option("--save");
+const comparePath = option("--compare");
+
+const ROWS = Number(args[0] ?? 10_000);
+const ROW_SIZE = Number(args[1] ?? 1_024);
+
+const surreal = new Surreal({
+    engines: createWasmEngines(),
+});
+
+await surreal.connect("mem://");
+
+await surreal.use({
+    namespace: "bench",
+    database: "bench",
+});
+
+const payload = "x".repeat(ROW_SIZE);
+const table = new Table("events");
+
+// Warm up the engine before measuring
+for (let i = 0; i < 100; i++) {
+    await surreal.insert(table, { payload });
+}
+
+const start = performance.now();
+
+for (let i = 0; i < ROWS; i++) {
+    await surreal.insert(table, { payload });
+}
+
+const elapsed = performance.now() - start;
+
+await surreal.close();
+
+const result: BenchResult = {
+    rows: ROWS,
+    rowSize: ROW_SIZE,
+    elapsed,
+    throughput: (ROWS / elapsed) * 1000,
+};
+
+console.log(`Inserted ${ROWS} rows of ${ROW_SIZE} bytes in ${elapsed.toFixed(0)}ms`);
+console.log(`Throughput: ${result.throughput.toFixed(0)} inserts/s`);
+
+if (savePath) {
+    await Bun.write(savePath, JSON.stringify(result, null, 4));
+    console.log(`Saved the result to ${savePath}`);
+}
+
+if (comparePath) {
+    const baseline: BenchResult = await Bun.file(comparePath).json();
+
+    if (baseline.rows !== ROWS || baseline.rowSize !== ROW_SIZE) {
+        console.warn(
+            `The baseline inserted ${baseline.rows} rows of ${baseline.rowSize} bytes, so the results may not be comparable`,
+        );
+    }
+
+    const change = (result.throughput / baseline.throughput - 1) * 100;
+
+    console.table({
+        before: {
+            "elapsed (ms)": Math.round(baseline.elapsed),
+            "inserts/s": Math.round(baseline.throughput),
+        },
+        after: {
+            "elapsed (ms)": Math.round(result.elapsed),
+            "inserts/s": Math.round(result.throughput),
+        },
+    });
+    console.log(`Throughput change: ${change >= 0 ? "+" : ""}${change.toFixed(1)}%`);
+}
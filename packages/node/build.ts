@@ -16,6 +16,11 @@ const DTS_HEADER = dedent`
 		deny?: boolean | string[];
 	};
 
+	type SessionPin = {
+		namespace: string;
+		database?: string;
+	};
+
 	type ConnectionOptions = {
 		strict?: boolean;
 		query_timeout?: number;
@@ -30,6 +35,166 @@ const DTS_HEADER = dedent`
 				network_targets?: boolean | string[] | CapabilitiesAllowDenyList;
 				experimental?: boolean | string[] | CapabilitiesAllowDenyList;
 			};
+		log_filter?: string;
+		slow_queries?: {
+			threshold: number;
+			capacity?: number;
+			redact_params?: boolean;
+			explain?: boolean;
+			buffer?: number;
+		};
+		audit?: {
+			buffer?: number;
+		};
+		record?: string;
+		pin?: SessionPin;
+		read_only?: boolean;
+		storage?: StorageOptions;
+	};
+
+	type SlowQuery = {
+		query: string;
+		namespace: string | null;
+		database: string | null;
+		params: Record<string, unknown>;
+		duration: number;
+		finished: number;
+		failed: boolean;
+		planner_strategy: "best-effort" | "compute-only" | "all-read-only";
+		explain: unknown | null;
+		dropped: number;
+	};
+
+	type AuditRecord = {
+		method: string;
+		session: string | null;
+		namespace: string | null;
+		database: string | null;
+		access: string | null;
+		user: string | null;
+		transaction: string | null;
+		success: boolean;
+		error: string | null;
+		duration: number;
+		time: number;
+		dropped: number;
+	};
+
+	type ReplayReport = {
+		requests: number;
+		skipped: number;
+		divergences: {
+			index: number;
+			method: string;
+			recorded: unknown;
+			actual: unknown;
+		}[];
+	};
+
+	type StorageOptions = {
+		versioned?: boolean;
+		retention?: string;
+	};
+
+	type UpgradeProgress = {
+		stage: "copying" | "migrating" | "complete";
+		copied: number;
+		from: number;
+		to: number;
+	};
+
+	type UpgradeReport = {
+		from: number;
+		to: number;
+		copied: number;
+	};
+
+	type BulkLoadOptions = {
+		batch_size?: number;
+		delimiter?: string;
+		columns?: Record<string, string>;
+		types?: Record<string, "auto" | "string" | "int" | "float" | "bool" | "json">;
+		null?: string;
+	};
+
+	type DataExportOptions = {
+		tables?: string[];
+		page_size?: number;
+		schema?: boolean;
+		as_of?: string;
+	};
+
+	type RecordVersion = {
+		version: string;
+		deleted: boolean;
+		value: unknown;
+	};
+
+	type ChangesExportOptions = {
+		tables?: string[];
+		since?: string;
+		limit?: number;
+	};
+
+	type SyncOptions = {
+		tables?: string[];
+		conflict?: "last-writer-wins" | "local-wins" | "remote-wins" | "custom";
+		timestamp_field?: string;
+	};
+
+	type EngineInspection = {
+		sessions: {
+			id: string | null;
+			namespace: string | null;
+			database: string | null;
+			access: string | null;
+			user: string | null;
+			live_queries: string[];
+		}[];
+		transactions: {
+			id: string;
+			started: number | null;
+			age: number | null;
+		}[];
+	};
+
+	type StorageUsage = {
+		keys: number;
+		bytes: number;
+	};
+
+	type StatsOptions = {
+		namespace?: string;
+		database?: string;
+		tables?: string[];
+	};
+
+	type DatastoreStats = StorageUsage & {
+		namespaces: Record<string, StorageUsage & {
+			databases: Record<string, StorageUsage & {
+				tables: Record<string, StorageUsage & {
+					records: StorageUsage;
+					indexes: Record<string, StorageUsage>;
+				}>;
+			}>;
+		}>;
+		backend: 
+			| { kind: "memory" }
+			| {
+				kind: "rocksdb";
+				path: string;
+				sst_files: number;
+				sst_bytes: number;
+				memtable_bytes: number;
+				memtable_limit: number | null;
+			}
+			| { kind: "surrealkv"; path: string; segments: number; bytes: number };
+	};
+
+	type ColumnarExportOptions = {
+		table?: string;
+		query?: string;
+		batch_size?: number;
 	};
 	\n
 `;
@@ -0,0 +1,110 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use serde::Deserialize;
+use serde::Serialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_types::Value;
+use uuid::Uuid;
+
+use super::inspect::now;
+
+/// The number of undelivered audit records kept when not specified
+const DEFAULT_BUFFER: u32 = 1024;
+
+#[derive(Deserialize, Clone, Default)]
+pub struct AuditOptions {
+	/// The number of records held until the subscriber receives them
+	pub buffer: Option<u32>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct AuditRecord {
+	pub method: String,
+	/// The session id, or `None` for the default session
+	pub session: Option<String>,
+	pub namespace: Option<String>,
+	pub database: Option<String>,
+	/// The access method the session signed in with
+	pub access: Option<String>,
+	/// The authenticated user or record, or `None` when anonymous
+	pub user: Option<String>,
+	pub transaction: Option<String>,
+	pub success: bool,
+	/// The kind of error returned, such as `NotAllowed`
+	pub error: Option<String>,
+	/// How long the request took, in milliseconds
+	pub duration: f64,
+	/// When the request finished, in milliseconds since the Unix epoch
+	pub time: f64,
+	/// The number of records dropped since the previous record, as the buffer was full
+	pub dropped: u64,
+}
+
+/// Sends a record of each request to a bounded channel.
+///
+/// Records are never waited on, so when the subscriber falls behind and the
+/// buffer is full, records are dropped and counted in the next delivered record.
+pub struct Audit {
+	tx: channel::Sender<AuditRecord>,
+	rx: channel::Receiver<AuditRecord>,
+	dropped: AtomicU64,
+}
+
+impl Audit {
+	pub fn new(options: AuditOptions) -> Self {
+		let (tx, rx) = channel::bounded(options.buffer.unwrap_or(DEFAULT_BUFFER).max(1) as usize);
+		Audit {
+			tx,
+			rx,
+			dropped: AtomicU64::new(0),
+		}
+	}
+
+	/// Records a request, read from the state of its session after it ran
+	pub fn record(
+		&self,
+		method: &str,
+		session_id: Option<Uuid>,
+		session: Option<&Session>,
+		txn: Option<Uuid>,
+		error: Option<String>,
+		duration: f64,
+	) {
+		let dropped = self.dropped.swap(0, Ordering::Relaxed);
+		let record = AuditRecord {
+			method: method.to_string(),
+			session: session_id.map(|id| id.to_string()),
+			namespace: session.and_then(|s| s.ns.clone()),
+			database: session.and_then(|s| s.db.clone()),
+			access: session.and_then(|s| s.ac.clone()),
+			user: session.filter(|s| !s.au.is_anon()).map(|s| s.au.id().to_string()),
+			transaction: txn.map(|id| id.to_string()),
+			success: error.is_none(),
+			error,
+			duration,
+			time: now(),
+			dropped,
+		};
+
+		if self.tx.try_send(record).is_err() {
+			self.dropped.fetch_add(dropped + 1, Ordering::Relaxed);
+		}
+	}
+
+	/// Returns the receiving end of the channel, which is shared by every caller
+	pub fn receiver(&self) -> channel::Receiver<AuditRecord> {
+		self.rx.clone()
+	}
+}
+
+/// Returns the kind of an RPC error, as sent to the SDK in the error envelope
+pub fn error_kind(error: &surrealdb_types::Error) -> String {
+	match Value::from_t(error.clone()) {
+		Value::Object(error) => match error.get("kind") {
+			Some(Value::String(kind)) => kind.clone(),
+			_ => "Internal".to_string(),
+		},
+		_ => "Internal".to_string(),
+	}
+}
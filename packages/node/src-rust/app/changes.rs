@@ -0,0 +1,177 @@
+use serde::Deserialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_types::Array;
+use surrealdb_types::Object;
+use surrealdb_types::Value;
+use surrealdb_types::Variables;
+
+use super::data::query;
+
+/// The version of the delta file layout written by `export`
+const DELTA_VERSION: i64 = 2;
+
+/// Replays every change set of a delta file inside a single transaction
+const APPLY_QUERY: &str = r#"
+BEGIN TRANSACTION;
+FOR $set IN $changes {
+	FOR $change IN $set.changes {
+		IF $change.update != NONE {
+			UPSERT $change.update.id CONTENT $change.update;
+		} ELSE IF $change.delete != NONE {
+			DELETE $change.delete.id;
+		};
+	};
+};
+COMMIT TRANSACTION;
+"#;
+
+#[derive(Deserialize, Default)]
+pub struct ChangesExportOptions {
+	/// The tables to read, defaulting to the change feed of the whole database
+	pub tables: Option<Vec<String>>,
+	/// Only changes after this versionstamp, as a decimal string, are exported
+	pub since: Option<String>,
+	/// The maximum number of change sets exported, across every table
+	pub limit: Option<u32>,
+}
+
+/// A delta file along with the highest versionstamp it contains
+pub struct ChangesDelta {
+	pub data: Vec<u8>,
+	pub versionstamp: Option<u64>,
+}
+
+/// Reads change feeds after a versionstamp, encoding them as a replayable delta file.
+///
+/// The delta is a CBOR map holding the `since` and `versionstamp` bounds and the
+/// change sets in versionstamp order. Versionstamps do not fit in a JavaScript
+/// number, so the bounds are decimal strings. Passing the returned versionstamp
+/// as the next `since` continues the export where this one left off.
+pub async fn export(
+	kvs: &Datastore,
+	session: &Session,
+	options: ChangesExportOptions,
+) -> Result<ChangesDelta, String> {
+	let since = options.since.as_deref().map(parse).transpose()?.unwrap_or(0);
+	let (sets, high) = read(kvs, session, options.tables.as_deref(), since, options.limit).await?;
+
+	Ok(ChangesDelta {
+		data: encode(since, high, sets)?,
+		versionstamp: high,
+	})
+}
+
+/// Applies a delta file written by `export`, returning its highest versionstamp
+pub async fn apply(kvs: &Datastore, session: &Session, data: &[u8]) -> Result<u64, String> {
+	let (sets, high) = decode(data)?;
+	replay(kvs, session, sets).await?;
+	Ok(high.unwrap_or(0))
+}
+
+/// Reads the change sets after a versionstamp, in versionstamp order.
+///
+/// When limited, the change feed of each table is read up to the limit, and the
+/// merged sets are cut at the limit, keeping any later sets which share the
+/// versionstamp of the last one. The returned versionstamp is then the highest
+/// one before which every change was read.
+pub async fn read(
+	kvs: &Datastore,
+	session: &Session,
+	tables: Option<&[String]>,
+	since: u64,
+	limit: Option<u32>,
+) -> Result<(Vec<Value>, Option<u64>), String> {
+	let clause = limit.map(|l| format!(" LIMIT {l}")).unwrap_or_default();
+
+	let targets = match tables {
+		Some(tables) => {
+			tables.iter().map(|t| format!("TABLE `{}`", t.replace('`', "\\`"))).collect()
+		}
+		None => vec!["DATABASE".to_string()],
+	};
+
+	let mut sets = Vec::new();
+	for target in targets {
+		let sql = format!("SHOW CHANGES FOR {target} SINCE {}{clause}", since + 1);
+		if let Value::Array(res) = query(kvs, session, &sql, None).await? {
+			sets.extend(res.into_vec());
+		}
+	}
+
+	sets.sort_by_key(versionstamp);
+
+	if let Some(limit) = limit.map(|l| l as usize).filter(|l| *l > 0) {
+		if let Some(last) = sets.get(limit - 1).map(versionstamp) {
+			let ties = sets[limit..].iter().take_while(|set| versionstamp(set) == last).count();
+			sets.truncate(limit + ties);
+		}
+	}
+
+	let high = sets.iter().filter_map(versionstamp).max();
+
+	Ok((sets, high))
+}
+
+/// Encodes change sets as a delta file
+pub fn encode(since: u64, high: Option<u64>, sets: Vec<Value>) -> Result<Vec<u8>, String> {
+	let mut delta = Object::default();
+	delta.insert("version".to_string(), Value::from_t(DELTA_VERSION));
+	delta.insert("since".to_string(), Value::String(since.to_string()));
+	delta.insert("versionstamp".to_string(), Value::String(high.unwrap_or(since).to_string()));
+	delta.insert("changes".to_string(), Value::Array(Array::from(sets)));
+
+	cbor::encode(Value::Object(delta)).map(Vec::from).map_err(|e| e.to_string())
+}
+
+/// Decodes a delta file into its change sets and highest versionstamp
+pub fn decode(data: &[u8]) -> Result<(Vec<Value>, Option<u64>), String> {
+	let Value::Object(mut delta) = cbor::decode(data).map_err(|e| e.to_string())? else {
+		return Err("Invalid delta file".to_string());
+	};
+
+	match delta.get("version").cloned().map(Value::into_t::<i64>) {
+		Some(Ok(DELTA_VERSION)) => (),
+		_ => return Err("Unsupported delta file version".to_string()),
+	}
+
+	let high = match delta.get("versionstamp") {
+		Some(Value::String(versionstamp)) => Some(parse(versionstamp)?),
+		_ => None,
+	};
+	let sets = match delta.remove("changes") {
+		Some(Value::Array(sets)) => sets.into_vec(),
+		_ => Vec::new(),
+	};
+
+	Ok((sets, high))
+}
+
+/// Parses a versionstamp given as a decimal string
+pub fn parse(versionstamp: &str) -> Result<u64, String> {
+	versionstamp.parse().map_err(|_| format!("Invalid versionstamp: {versionstamp}"))
+}
+
+/// Replays change sets inside a single transaction
+pub async fn replay(kvs: &Datastore, session: &Session, sets: Vec<Value>) -> Result<(), String> {
+	let mut vars = Variables::new();
+	vars.insert("changes".to_string(), Value::Array(Array::from(sets)));
+
+	let res = kvs.execute(APPLY_QUERY, session, Some(vars)).await.map_err(|e| e.to_string())?;
+	for res in res {
+		res.result.map_err(|e| e.to_string())?;
+	}
+
+	Ok(())
+}
+
+/// Returns the versionstamp of a change set
+pub fn versionstamp(set: &Value) -> Option<u64> {
+	match set {
+		Value::Object(set) => {
+			set.get("versionstamp")?.clone().into_t::<i64>().ok().map(|v| v as u64)
+		}
+		_ => None,
+	}
+}
@@ -0,0 +1,45 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use surrealdb_core::kvs::Datastore;
+
+use super::stats::location;
+
+/// The files which every RocksDB datastore holds
+const ROCKSDB_FILES: &[&str] = &["CURRENT", "IDENTITY"];
+
+/// The manifest which every SurrealKV datastore holds
+const SURREALKV_FILES: &[&str] = &["manifest"];
+
+/// Removes the storage directory of a RocksDB or SurrealKV endpoint.
+///
+/// The directory is only removed when it holds the files of a datastore of the
+/// endpoint's backend, which is checked without opening it. The datastore is
+/// then opened and shut down to ensure no other handle holds its lock, failing
+/// when it is still open. In-memory endpoints have nothing to remove.
+pub async fn destroy(endpoint: &str) -> Result<(), String> {
+	let (path, files) = match location(endpoint) {
+		Some(("rocksdb" | "file", path)) => (path, ROCKSDB_FILES),
+		Some((scheme, path)) if scheme.starts_with("surrealkv") => (path, SURREALKV_FILES),
+		_ => return Ok(()),
+	};
+	let dir = Path::new(path);
+	if !dir.exists() {
+		return Ok(());
+	}
+	if !files.iter().all(|file| dir.join(file).exists()) {
+		return Err(format!("{path} does not hold a datastore of {endpoint}"));
+	}
+
+	match Datastore::new(endpoint).await {
+		Ok(kvs) => kvs.shutdown().await.map_err(|e| e.to_string())?,
+		Err(err) => return Err(format!("The datastore is still open: {err}")),
+	}
+
+	match fs::remove_dir_all(dir) {
+		Ok(()) => Ok(()),
+		Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+		Err(err) => Err(format!("Failed to remove {path}: {err}")),
+	}
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use std::sync::RwLock as StdRwLock;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use dashmap::DashMap;
+use napi::tokio::sync::RwLock;
+use serde::Serialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::Transaction;
+use surrealdb_types::HashMap;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct SessionInfo {
+	/// The session id, or `None` for the default session
+	pub id: Option<String>,
+	pub namespace: Option<String>,
+	pub database: Option<String>,
+	/// The access method the session signed in with
+	pub access: Option<String>,
+	/// The authenticated user or record, or `None` when anonymous
+	pub user: Option<String>,
+	pub live_queries: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TransactionInfo {
+	pub id: String,
+	/// When the transaction began, in milliseconds since the Unix epoch
+	pub started: Option<f64>,
+	/// How long the transaction has been open, in milliseconds
+	pub age: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct Inspection {
+	pub sessions: Vec<SessionInfo>,
+	pub transactions: Vec<TransactionInfo>,
+}
+
+/// Returns the current time in milliseconds since the Unix epoch
+pub fn now() -> f64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+}
+
+/// Describes the sessions, live queries and open transactions of a connection.
+///
+/// Live queries are listed under their owning session. Live queries whose
+/// session has been removed are listed under a session with no details.
+pub async fn inspect(
+	sessions: &HashMap<Option<Uuid>, Arc<RwLock<Session>>>,
+	live_queries: &StdRwLock<HashMap<Uuid, Option<Uuid>>>,
+	transactions: &DashMap<Uuid, Arc<Transaction>>,
+	started: &DashMap<Uuid, f64>,
+) -> Inspection {
+	let mut entries = Vec::new();
+	sessions.retain(|id, session| {
+		entries.push((*id, session.clone()));
+		true
+	});
+
+	let mut owners = Vec::new();
+	live_queries.read().unwrap().retain(|lqid, session| {
+		owners.push((*lqid, *session));
+		true
+	});
+
+	let mut infos = Vec::with_capacity(entries.len());
+	for (id, session) in entries {
+		let session = session.read().await;
+		infos.push(SessionInfo {
+			id: id.map(|id| id.to_string()),
+			namespace: session.ns.clone(),
+			database: session.db.clone(),
+			access: session.ac.clone(),
+			user: (!session.au.is_anon()).then(|| session.au.id().to_string()),
+			live_queries: Vec::new(),
+		});
+	}
+
+	for (lqid, owner) in owners {
+		let owner = owner.map(|id| id.to_string());
+		let position = match infos.iter().position(|info| info.id == owner) {
+			Some(position) => position,
+			None => {
+				infos.push(SessionInfo {
+					id: owner,
+					namespace: None,
+					database: None,
+					access: None,
+					user: None,
+					live_queries: Vec::new(),
+				});
+				infos.len() - 1
+			}
+		};
+		infos[position].live_queries.push(lqid.to_string());
+	}
+
+	let now = now();
+	let transactions = transactions
+		.iter()
+		.map(|tx| {
+			let started = started.get(tx.key()).map(|s| *s);
+			TransactionInfo {
+				id: tx.key().to_string(),
+				started,
+				age: started.map(|s| now - s),
+			}
+		})
+		.collect();
+
+	Inspection {
+		sessions: infos,
+		transactions,
+	}
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The upper bounds of the latency histogram buckets, in seconds
+pub const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Counters and latency histograms for the operations handled by an engine
+#[derive(Default)]
+pub struct Metrics {
+	methods: Mutex<BTreeMap<String, MethodMetrics>>,
+	notifications: AtomicU64,
+}
+
+#[derive(Default, Clone)]
+pub struct MethodMetrics {
+	pub calls: u64,
+	pub errors: u64,
+	/// The total time spent, in seconds
+	pub duration: f64,
+	/// The number of calls in each bucket, without the calls of smaller buckets
+	buckets: [u64; BUCKETS.len()],
+}
+
+impl MethodMetrics {
+	/// Returns the cumulative count of calls at or below each bucket bound
+	pub fn cumulative(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+		BUCKETS.iter().zip(self.buckets.iter()).scan(0, |total, (le, count)| {
+			*total += count;
+			Some((*le, *total))
+		})
+	}
+}
+
+/// The current number of sessions, live queries and open transactions
+pub struct Gauges {
+	pub sessions: u64,
+	pub live_queries: u64,
+	pub transactions: u64,
+}
+
+pub struct Snapshot {
+	pub methods: BTreeMap<String, MethodMetrics>,
+	pub notifications: u64,
+	pub gauges: Gauges,
+}
+
+impl Metrics {
+	/// Records a call to an RPC method or engine operation
+	pub fn observe(&self, method: &str, elapsed: Duration, ok: bool) {
+		let seconds = elapsed.as_secs_f64();
+		let mut methods = self.methods.lock().unwrap();
+		let metrics = methods.entry(method.to_string()).or_default();
+
+		metrics.calls += 1;
+		metrics.duration += seconds;
+		if !ok {
+			metrics.errors += 1;
+		}
+		// Calls slower than every bucket are only counted in the +Inf bucket
+		if let Some(bucket) = BUCKETS.iter().position(|le| seconds <= *le) {
+			metrics.buckets[bucket] += 1;
+		}
+	}
+
+	/// Records a live query notification sent to the client
+	pub fn notification(&self) {
+		self.notifications.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn snapshot(&self, gauges: Gauges) -> Snapshot {
+		Snapshot {
+			methods: self.methods.lock().unwrap().clone(),
+			notifications: self.notifications.load(Ordering::Relaxed),
+			gauges,
+		}
+	}
+}
+
+impl Snapshot {
+	/// Renders the metrics in the Prometheus text exposition format
+	pub fn prometheus(&self) -> String {
+		let mut out = String::new();
+
+		let _ = writeln!(out, "# HELP surrealdb_requests_total The number of requests handled.");
+		let _ = writeln!(out, "# TYPE surrealdb_requests_total counter");
+		for (method, metrics) in &self.methods {
+			let _ =
+				writeln!(out, "surrealdb_requests_total{{method=\"{method}\"}} {}", metrics.calls);
+		}
+
+		let _ =
+			writeln!(out, "# HELP surrealdb_request_errors_total The number of failed requests.");
+		let _ = writeln!(out, "# TYPE surrealdb_request_errors_total counter");
+		for (method, metrics) in &self.methods {
+			let _ = writeln!(
+				out,
+				"surrealdb_request_errors_total{{method=\"{method}\"}} {}",
+				metrics.errors
+			);
+		}
+
+		let name = "surrealdb_request_duration_seconds";
+		let _ = writeln!(out, "# HELP {name} The time taken to handle requests.");
+		let _ = writeln!(out, "# TYPE {name} histogram");
+		for (method, metrics) in &self.methods {
+			for (le, count) in metrics.cumulative() {
+				let _ = writeln!(out, "{name}_bucket{{method=\"{method}\",le=\"{le}\"}} {count}");
+			}
+			let _ =
+				writeln!(out, "{name}_bucket{{method=\"{method}\",le=\"+Inf\"}} {}", metrics.calls);
+			let _ = writeln!(out, "{name}_sum{{method=\"{method}\"}} {}", metrics.duration);
+			let _ = writeln!(out, "{name}_count{{method=\"{method}\"}} {}", metrics.calls);
+		}
+
+		let _ = writeln!(
+			out,
+			"# HELP surrealdb_notifications_total The number of live query notifications sent."
+		);
+		let _ = writeln!(out, "# TYPE surrealdb_notifications_total counter");
+		let _ = writeln!(out, "surrealdb_notifications_total {}", self.notifications);
+
+		let gauges = [
+			("surrealdb_sessions", "The number of active sessions.", self.gauges.sessions),
+			(
+				"surrealdb_live_queries",
+				"The number of active live queries.",
+				self.gauges.live_queries,
+			),
+			(
+				"surrealdb_transactions",
+				"The number of open transactions.",
+				self.gauges.transactions,
+			),
+		];
+		for (name, help, value) in gauges {
+			let _ = writeln!(out, "# HELP {name} {help}");
+			let _ = writeln!(out, "# TYPE {name} gauge");
+			let _ = writeln!(out, "{name} {value}");
+		}
+
+		out
+	}
+}
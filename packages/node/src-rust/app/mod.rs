@@ -1,18 +1,67 @@
+mod audit;
+mod bulk;
+mod changes;
+mod columnar;
+mod data;
+mod destroy;
+mod format;
+mod inspect;
+pub mod intercept;
+mod logs;
+mod metrics;
 mod options;
+mod pin;
+mod readonly;
+mod recording;
+mod slowlog;
+mod stats;
+mod storage;
+mod stream;
+mod sync;
+mod upgrade;
+mod versions;
 
+use std::fs::OpenOptions;
 use std::sync::Arc;
 use std::sync::RwLock;
 use std::time::Duration;
+use std::time::Instant;
 
 use crate::err::err_map;
+use audit::Audit;
+use audit::AuditRecord;
+use bulk::BulkLoadOptions;
+use bulk::BulkLoader;
+use changes::ChangesExportOptions;
+use columnar::ColumnarExportOptions;
+use columnar::ColumnarFormat;
 use dashmap::DashMap;
+use data::DataChunk;
+use data::DataExport;
+use data::DataExportOptions;
+use format::Format;
+use futures::future::join_all;
+use intercept::Interceptor;
+use intercept::Interceptors;
+use intercept::JsInterceptor;
+use metrics::Gauges;
+use metrics::Metrics;
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::ThreadsafeFunction;
+use napi::threadsafe_function::ThreadsafeFunctionCallMode;
+use napi::tokio::sync::Mutex as TokioMutex;
 use napi::tokio::sync::RwLock as TokioRwLock;
 use napi_derive::napi;
 
 use options::Options;
+use pin::Pin;
+use recording::FileWriter;
+use recording::Recorder;
 use serde_json::from_value;
 use serde_json::Value as JsValue;
+use slowlog::SlowLog;
+use slowlog::SlowQuery;
+use stats::StatsOptions;
 use surrealdb_core::dbs::Session;
 use surrealdb_core::kvs::export::Config;
 use surrealdb_core::kvs::Datastore;
@@ -27,10 +76,11 @@ use surrealdb_core::rpc::RpcProtocol;
 use surrealdb_types::Array;
 use surrealdb_types::HashMap;
 use surrealdb_types::Value;
+use sync::SyncOptions;
 use uuid::Uuid;
 
 #[napi]
-pub struct SurrealNodeEngine(TokioRwLock<Option<SurrealNodeConnection>>);
+pub struct SurrealNodeEngine(TokioRwLock<Option<Arc<SurrealNodeConnection>>>);
 
 #[napi]
 pub struct NotificationReceiver {
@@ -48,54 +98,327 @@ impl NotificationReceiver {
 	}
 }
 
+#[napi]
+pub struct ChunkReceiver {
+	receiver: channel::Receiver<std::result::Result<Uint8Array, String>>,
+}
+
+#[napi]
+impl ChunkReceiver {
+	#[napi]
+	pub async fn recv(&self) -> std::result::Result<Option<Uint8Array>, Error> {
+		match self.receiver.recv().await {
+			Ok(Ok(data)) => Ok(Some(data)),
+			Ok(Err(err)) => Err(err_map(err)),
+			Err(_) => Ok(None), // Stream finished
+		}
+	}
+}
+
+#[napi]
+pub struct AuditReceiver {
+	receiver: channel::Receiver<AuditRecord>,
+}
+
+#[napi]
+impl AuditReceiver {
+	#[napi(ts_return_type = "Promise<AuditRecord | null>")]
+	pub async fn recv(&self) -> std::result::Result<Option<JsValue>, Error> {
+		match self.receiver.recv().await {
+			Ok(record) => Ok(Some(serde_json::to_value(record)?)),
+			Err(_) => Ok(None), // Channel closed
+		}
+	}
+}
+
+#[napi]
+pub struct SlowQueryReceiver {
+	receiver: channel::Receiver<SlowQuery>,
+}
+
+#[napi]
+impl SlowQueryReceiver {
+	#[napi(ts_return_type = "Promise<SlowQuery | null>")]
+	pub async fn recv(&self) -> std::result::Result<Option<JsValue>, Error> {
+		match self.receiver.recv().await {
+			Ok(record) => Ok(Some(serde_json::to_value(record)?)),
+			Err(_) => Ok(None), // Channel closed
+		}
+	}
+}
+
+#[napi(object)]
+pub struct BulkLoadProgress {
+	pub rows: i64,
+	pub loaded: i64,
+	pub failed: i64,
+}
+
+#[napi(object)]
+pub struct BulkLoadFailure {
+	pub row: i64,
+	pub error: String,
+}
+
+#[napi(object)]
+pub struct BulkLoadReport {
+	pub rows: i64,
+	pub loaded: i64,
+	pub failures: Vec<BulkLoadFailure>,
+}
+
+#[napi]
+pub struct BulkLoad {
+	kvs: Arc<Datastore>,
+	session: Arc<TokioRwLock<Session>>,
+	loader: TokioMutex<BulkLoader>,
+}
+
+#[napi]
+impl BulkLoad {
+	/// Writes a chunk of input, loading every complete batch of records
+	#[napi]
+	pub async fn write(&self, chunk: Uint8Array) -> std::result::Result<BulkLoadProgress, Error> {
+		let mut loader = self.loader.lock().await;
+		let session = self.session.read().await;
+		loader.write(&self.kvs, &session, &chunk).await.map_err(err_map)?;
+
+		Ok(BulkLoadProgress {
+			rows: loader.rows as i64,
+			loaded: loader.loaded as i64,
+			failed: loader.failures.len() as i64,
+		})
+	}
+
+	/// Loads any remaining records, returning the rows which failed
+	#[napi]
+	pub async fn finish(&self) -> std::result::Result<BulkLoadReport, Error> {
+		let mut loader = self.loader.lock().await;
+		let session = self.session.read().await;
+		loader.finish(&self.kvs, &session).await.map_err(err_map)?;
+
+		Ok(BulkLoadReport {
+			rows: loader.rows as i64,
+			loaded: loader.loaded as i64,
+			failures: std::mem::take(&mut loader.failures)
+				.into_iter()
+				.map(|f| BulkLoadFailure {
+					row: f.row as i64,
+					error: f.error,
+				})
+				.collect(),
+		})
+	}
+}
+
+#[napi(object)]
+pub struct DataExportChunk {
+	/// The exported table, or `null` for the schema sidecar
+	pub table: Option<String>,
+	pub data: Uint8Array,
+}
+
+#[napi(object)]
+pub struct ChangesExport {
+	pub data: Uint8Array,
+	/// The highest exported versionstamp as a decimal string, or `null` when
+	/// there were no changes
+	pub versionstamp: Option<String>,
+}
+
+#[napi(object)]
+pub struct SyncReport {
+	/// The remote changes applied locally
+	pub pulled: i64,
+	/// The local changes sent to the peer
+	pub pushed: i64,
+	/// The records changed on both sides since the last sync
+	pub conflicts: i64,
+}
+
+#[napi(object)]
+pub struct LogRecord {
+	pub level: String,
+	pub target: String,
+	pub message: String,
+	pub fields: std::collections::HashMap<String, String>,
+	/// The names of the enclosing spans, from the outermost
+	pub spans: Vec<String>,
+}
+
+#[napi(object)]
+pub struct HistogramBucket {
+	/// The upper bound of the bucket, in seconds
+	pub le: f64,
+	/// The number of calls at or below the upper bound
+	pub count: i64,
+}
+
+#[napi(object)]
+pub struct MethodMetrics {
+	pub method: String,
+	pub calls: i64,
+	pub errors: i64,
+	/// The total time spent, in seconds
+	pub duration: f64,
+	pub buckets: Vec<HistogramBucket>,
+}
+
+#[napi(object)]
+pub struct EngineMetrics {
+	pub methods: Vec<MethodMetrics>,
+	pub notifications: i64,
+	pub sessions: i64,
+	pub live_queries: i64,
+	pub transactions: i64,
+	/// The metrics in the Prometheus text exposition format
+	pub prometheus: String,
+}
+
+#[napi]
+pub struct DataExportReceiver {
+	receiver: channel::Receiver<std::result::Result<DataChunk, String>>,
+}
+
+#[napi]
+impl DataExportReceiver {
+	#[napi]
+	pub async fn recv(&self) -> std::result::Result<Option<DataExportChunk>, Error> {
+		match self.receiver.recv().await {
+			Ok(Ok(chunk)) => Ok(Some(DataExportChunk {
+				table: chunk.table,
+				data: chunk.data.into(),
+			})),
+			Ok(Err(err)) => Err(err_map(err)),
+			Err(_) => Ok(None), // Export finished
+		}
+	}
+}
+
 #[napi]
 impl SurrealNodeEngine {
 	#[napi]
-	pub async fn execute(&self, data: Uint8Array) -> std::result::Result<Uint8Array, Error> {
+	pub async fn execute(
+		&self,
+		data: Uint8Array,
+		#[napi(ts_arg_type = "'cbor' | 'json'")] format: Option<String>,
+	) -> std::result::Result<Uint8Array, Error> {
+		let format = Format::parse(format.as_deref())?;
 		let lock = self.0.read().await;
 		let engine = lock.as_ref().unwrap();
-		let obj = cbor::decode(data.to_vec().as_slice())
-			.map_err(err_map)?
-			.into_object()
-			.map_err(err_map)?;
-		let req = Request::from_object(obj).map_err(err_map)?;
-		let res = RpcProtocol::execute(
-			engine,
-			req.txn.map(Into::into),
-			req.session_id.map(Into::into),
-			req.method,
-			req.params,
-		)
-		.await;
+		// Decode directly from the borrowed JavaScript buffer
+		let req = format.request(&data)?;
+		let out = engine.handle(format, req).await?;
+		// Hand the encoded buffer to JavaScript without copying
+		Ok(out.into())
+	}
 
-		match res {
-			Ok(result) => {
-				let value = Value::from_t(result);
-				let out = cbor::encode(value).map_err(err_map)?;
-				Ok(out.as_slice().into())
+	/// Executes many requests in a single call, returning one response per request.
+	///
+	/// Requests run in order unless `concurrent` is set. A malformed request is
+	/// answered with an error envelope in its own slot, and the others still run.
+	#[napi]
+	pub async fn execute_batch(
+		&self,
+		data: Vec<Uint8Array>,
+		concurrent: Option<bool>,
+		#[napi(ts_arg_type = "'cbor' | 'json'")] format: Option<String>,
+	) -> std::result::Result<Vec<Uint8Array>, Error> {
+		let format = Format::parse(format.as_deref())?;
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let reqs = data
+			.iter()
+			.map(|data| {
+				format.request(data).map_err(|e| surrealdb_core::rpc::invalid_params(&e.reason))
+			})
+			.collect::<Vec<_>>();
+		let run = |req: TxResult<Request>| async move {
+			match req {
+				Ok(req) => engine.handle(format, req).await,
+				Err(err) => format.encode(error_envelope(err)),
 			}
-			Err(rpc_err) => {
-				let mut envelope = surrealdb_types::Object::default();
-				envelope.insert("error".to_string(), Value::from_t(rpc_err));
-				let out = cbor::encode(Value::Object(envelope)).map_err(err_map)?;
-				Ok(out.as_slice().into())
+		};
+
+		let out = if concurrent.unwrap_or(false) {
+			join_all(reqs.into_iter().map(run))
+				.await
+				.into_iter()
+				.collect::<std::result::Result<Vec<_>, _>>()?
+		} else {
+			let mut out = Vec::with_capacity(reqs.len());
+			for req in reqs {
+				out.push(run(req).await?);
 			}
-		}
+			out
+		};
+
+		Ok(out.into_iter().map(Into::into).collect())
+	}
+
+	/// Executes a request, streaming its result back as a sequence of chunks.
+	///
+	/// Query results are sent per statement, with array results split into batches
+	/// of `batch_size` records. Independent statements of a query execute one at a
+	/// time as the receiver reads them, and chunks are encoded as the receiver makes
+	/// room, so a slow reader holds back execution rather than buffering the whole
+	/// response. An error envelope ends the stream.
+	#[napi]
+	pub async fn execute_stream(
+		&self,
+		data: Uint8Array,
+		batch_size: Option<u32>,
+		#[napi(ts_arg_type = "'cbor' | 'json'")] format: Option<String>,
+	) -> std::result::Result<ChunkReceiver, Error> {
+		let format = Format::parse(format.as_deref())?;
+		let batch_size = batch_size.map_or(stream::DEFAULT_BATCH_SIZE, |b| b as usize);
+		let req = format.request(&data)?;
+		let is_query = req.method.to_str() == "query";
+		let engine = self.0.read().await.clone().unwrap();
+		let (tx, rx) = channel::bounded(stream::STREAM_BUFFER);
+
+		// Spawn a task to execute and encode chunks as the reader makes room
+		napi::tokio::spawn(async move {
+			for (offset, req) in stream::requests(req).into_iter().enumerate() {
+				let response = engine.interceptors.run(req, |req| engine.record(req)).await;
+				let failed = response.is_err();
+				for frame in stream::frames(response, is_query, offset, batch_size) {
+					let data = format.encode(frame).map(Into::into).map_err(|e| e.to_string());
+					let unencoded = data.is_err();
+					if tx.send(data).await.is_err() || unencoded {
+						return; // Receiver dropped or encoding failed
+					}
+				}
+				if failed {
+					break;
+				}
+			}
+		});
+
+		Ok(ChunkReceiver {
+			receiver: rx,
+		})
 	}
 
 	#[napi]
-	pub async fn notifications(&self) -> std::result::Result<NotificationReceiver, Error> {
-		let stream = {
+	pub async fn notifications(
+		&self,
+		#[napi(ts_arg_type = "'cbor' | 'json'")] format: Option<String>,
+	) -> std::result::Result<NotificationReceiver, Error> {
+		let format = Format::parse(format.as_deref())?;
+		let (stream, metrics) = {
 			let lock = self.0.read().await;
 			let engine = lock.as_ref().unwrap();
 
-			engine
+			let stream = engine
 				.kvs
 				.notifications()
 				.ok_or_else(|| {
 					Error::new(napi::Status::GenericFailure, "Notifications not enabled")
 				})
-				.map_err(err_map)?
+				.map_err(err_map)?;
+
+			(stream, engine.metrics.clone())
 		};
 
 		let (tx, rx) = channel::unbounded();
@@ -107,11 +430,12 @@ impl SurrealNodeEngine {
 			while let Ok(notification) = notification_stream.recv().await {
 				let message = Value::from_t(notification);
 
-				if let Ok(out) = cbor::encode(message) {
-					let data = out.as_slice().into();
+				if let Ok(out) = format.encode(message) {
+					let data = out.into();
 					if tx.send(data).await.is_err() {
 						break; // Receiver dropped
 					}
+					metrics.notification();
 				}
 			}
 		});
@@ -126,15 +450,33 @@ impl SurrealNodeEngine {
 		endpoint: String,
 		#[napi(ts_arg_type = "ConnectionOptions")] opts: Option<JsValue>,
 	) -> std::result::Result<SurrealNodeEngine, Error> {
-		let endpoint = match &endpoint {
-			s if s.starts_with("mem:") => "memory",
-			s => s,
-		};
-
 		let opts: Option<Options> = from_value::<Option<Options>>(JsValue::from(opts))?;
+		let storage = opts.as_ref().and_then(|o| o.storage.as_ref());
+		let endpoint = storage::endpoint(&endpoint, storage).map_err(err_map)?;
 		let defaults = opts.as_ref().and_then(|o| o.defaults.clone()).unwrap_or_default();
 
-		let kvs = Datastore::new(endpoint).await.map_err(err_map)?.with_notifications();
+		if let Some(filter) = opts.as_ref().and_then(|o| o.log_filter.as_deref()) {
+			logs::set_filter(filter).map_err(err_map)?;
+		}
+
+		let slow_log = opts.as_ref().and_then(|o| {
+			let planner = o.capabilities.as_ref().map(|c| c.planner_strategy()).unwrap_or_default();
+			o.slow_queries.clone().map(|s| Arc::new(SlowLog::new(s, planner.as_str())))
+		});
+		let audit = opts.as_ref().and_then(|o| o.audit.clone()).map(Audit::new);
+		let pin = opts.as_ref().and_then(|o| o.pin.clone());
+		let read_only = opts.as_ref().and_then(|o| o.read_only).unwrap_or(false);
+		if read_only {
+			readonly::check_backend(&endpoint).map_err(err_map)?;
+		}
+		let recorder = match opts.as_ref().and_then(|o| o.record.as_deref()) {
+			Some(path) => Some(Recorder::new(FileWriter::spawn(
+				OpenOptions::new().create(true).append(true).open(path).map_err(err_map)?,
+			))),
+			None => None,
+		};
+
+		let kvs = Datastore::new(&endpoint).await.map_err(err_map)?.with_notifications();
 		let kvs = match opts {
 			None => kvs,
 			Some(opts) => kvs
@@ -147,15 +489,21 @@ impl SurrealNodeEngine {
 				.with_query_timeout(opts.query_timeout.map(|qt| Duration::from_secs(qt as u64))),
 		};
 
-		let (_, is_new) = kvs.check_version().await.map_err(err_map)?;
+		// A read-only engine neither upgrades the storage version nor writes the defaults
+		if !read_only {
+			let (_, is_new) = kvs.check_version().await.map_err(err_map)?;
 
-		if is_new {
-			if let Some(defaults) = defaults.get_defaults() {
-				kvs.initialise_defaults(&defaults.0, &defaults.1).await.map_err(err_map)?;
+			if is_new {
+				if let Some(defaults) = defaults.get_defaults() {
+					kvs.initialise_defaults(&defaults.0, &defaults.1).await.map_err(err_map)?;
+				}
 			}
 		}
 
-		let session = Session::default().with_rt(true);
+		let mut session = Session::default().with_rt(true);
+		if let Some(pin) = &pin {
+			pin.select(&mut session);
+		}
 		#[allow(unused_mut)]
 		let mut sessions = HashMap::new();
 		sessions.insert(None, Arc::new(TokioRwLock::new(session)));
@@ -164,10 +512,41 @@ impl SurrealNodeEngine {
 			kvs: Arc::new(kvs),
 			live_queries: Arc::new(RwLock::new(HashMap::new())),
 			transactions: DashMap::new(),
+			transaction_started: DashMap::new(),
 			sessions,
+			metrics: Default::default(),
+			slow_log,
+			audit,
+			recorder,
+			interceptors: Default::default(),
+			pin,
+			pins: DashMap::new(),
+			read_only,
+			endpoint,
 		};
 
-		Ok(SurrealNodeEngine(TokioRwLock::new(Some(connection))))
+		Ok(SurrealNodeEngine(TokioRwLock::new(Some(Arc::new(connection)))))
+	}
+
+	/// Starts loading NDJSON, CSV or CBOR sequence records into a table
+	#[napi]
+	pub async fn bulk_load(
+		&self,
+		table: String,
+		#[napi(ts_arg_type = "'ndjson' | 'csv' | 'cbor'")] format: String,
+		#[napi(ts_arg_type = "BulkLoadOptions")] opts: Option<JsValue>,
+	) -> std::result::Result<BulkLoad, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		engine.check_writable("load records")?;
+		let opts = from_value::<Option<BulkLoadOptions>>(JsValue::from(opts))?;
+		let loader = BulkLoader::new(table, &format, opts.unwrap_or_default()).map_err(err_map)?;
+
+		Ok(BulkLoad {
+			kvs: engine.kvs.clone(),
+			session: engine.default_session(),
+			loader: TokioMutex::new(loader),
+		})
 	}
 
 	#[napi]
@@ -178,28 +557,32 @@ impl SurrealNodeEngine {
 		let session_arc = engine.default_session();
 		let session_guard = session_arc.read().await;
 
-		match config {
-			Some(config) => {
-				let in_config = cbor::decode(config.to_vec().as_slice()).map_err(err_map)?;
-				let config = in_config.into_t::<Config>().map_err(err_map)?;
-				engine
-					.kvs
-					.export_with_config(&*session_guard, tx, config)
-					.await
-					.map_err(err_map)?
-					.await
-					.map_err(err_map)?;
-			}
-			None => {
-				engine
+		let start = Instant::now();
+		let res = async {
+			match config {
+				Some(config) => {
+					let in_config = cbor::decode(&config).map_err(err_map)?;
+					let config = in_config.into_t::<Config>().map_err(err_map)?;
+					engine
+						.kvs
+						.export_with_config(&*session_guard, tx, config)
+						.await
+						.map_err(err_map)?
+						.await
+						.map_err(err_map)
+				}
+				None => engine
 					.kvs
 					.export(&*session_guard, tx)
 					.await
 					.map_err(err_map)?
 					.await
-					.map_err(err_map)?;
+					.map_err(err_map),
 			}
-		};
+		}
+		.await;
+		engine.metrics.observe("export", start.elapsed(), res.is_ok());
+		res?;
 
 		let mut buffer = Vec::new();
 		while let Ok(item) = rx.try_recv() {
@@ -211,17 +594,541 @@ impl SurrealNodeEngine {
 		Ok(result)
 	}
 
+	/// Exports table records as NDJSON, JSON arrays or CBOR sequences.
+	///
+	/// Tables are read page by page as the receiver reads chunks. When `schema`
+	/// is set, the first chunk holds the table definitions as SurrealQL.
+	#[napi]
+	pub async fn export_data(
+		&self,
+		#[napi(ts_arg_type = "'ndjson' | 'json' | 'cbor'")] format: String,
+		#[napi(ts_arg_type = "DataExportOptions")] opts: Option<JsValue>,
+	) -> std::result::Result<DataExportReceiver, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let opts = from_value::<Option<DataExportOptions>>(JsValue::from(opts))?;
+		let mut export = DataExport::new(&format, opts.unwrap_or_default()).map_err(err_map)?;
+		let kvs = engine.kvs.clone();
+		let session = engine.default_session();
+		let (tx, rx) = channel::bounded(stream::STREAM_BUFFER);
+
+		// Spawn a task to read pages as the receiver makes room
+		napi::tokio::spawn(async move {
+			let session = session.read().await;
+
+			loop {
+				match export.next(&kvs, &session).await {
+					Ok(Some(chunk)) => {
+						if tx.send(Ok(chunk)).await.is_err() {
+							break; // Receiver dropped
+						}
+					}
+					Ok(None) => break,
+					Err(err) => {
+						let _ = tx.send(Err(err)).await;
+						break;
+					}
+				}
+			}
+		});
+
+		Ok(DataExportReceiver {
+			receiver: rx,
+		})
+	}
+
+	/// Exports a table or query result as an Arrow IPC stream or a Parquet file
+	#[napi]
+	pub async fn export_columnar(
+		&self,
+		#[napi(ts_arg_type = "'arrow' | 'parquet'")] format: String,
+		#[napi(ts_arg_type = "ColumnarExportOptions")] opts: JsValue,
+	) -> std::result::Result<Uint8Array, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let format = ColumnarFormat::parse(&format).map_err(err_map)?;
+		let opts = from_value::<ColumnarExportOptions>(opts)?;
+		// A query may contain statements which write, and runs outside of a read transaction
+		if let Some(query) = &opts.query {
+			engine.check_writable("export the result of a query")?;
+			if let Some(pin) = engine.pin(None) {
+				pin.check_query(query).map_err(err_map)?;
+			}
+		}
+		let session_arc = engine.default_session();
+		let session_guard = session_arc.read().await;
+		let out =
+			columnar::export(&engine.kvs, &session_guard, format, opts).await.map_err(err_map)?;
+
+		Ok(out.into())
+	}
+
+	/// Exports the change feeds after a versionstamp as a replayable delta file
+	#[napi]
+	pub async fn export_changes(
+		&self,
+		#[napi(ts_arg_type = "ChangesExportOptions")] opts: Option<JsValue>,
+	) -> std::result::Result<ChangesExport, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let opts = from_value::<Option<ChangesExportOptions>>(JsValue::from(opts))?;
+		let session_arc = engine.default_session();
+		let session_guard = session_arc.read().await;
+		let delta = changes::export(&engine.kvs, &session_guard, opts.unwrap_or_default())
+			.await
+			.map_err(err_map)?;
+
+		Ok(ChangesExport {
+			data: delta.data.into(),
+			versionstamp: delta.versionstamp.map(|v| v.to_string()),
+		})
+	}
+
+	/// Applies a delta file in a single transaction, returning its highest versionstamp
+	#[napi]
+	pub async fn import_changes(&self, data: Uint8Array) -> std::result::Result<String, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		engine.check_writable("apply changes")?;
+		let session_arc = engine.default_session();
+		let session_guard = session_arc.read().await;
+		let versionstamp =
+			changes::apply(&engine.kvs, &session_guard, &data).await.map_err(err_map)?;
+
+		Ok(versionstamp.to_string())
+	}
+
+	/// Returns the request counters, latency histograms and gauges of the engine,
+	/// along with the same metrics in the Prometheus text exposition format.
+	#[napi]
+	pub async fn metrics(&self) -> std::result::Result<EngineMetrics, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+
+		let mut sessions = 0;
+		engine.sessions.retain(|_, _| {
+			sessions += 1;
+			true
+		});
+		let mut live_queries = 0;
+		engine.live_queries.read().unwrap().retain(|_, _| {
+			live_queries += 1;
+			true
+		});
+
+		let snapshot = engine.metrics.snapshot(Gauges {
+			sessions,
+			live_queries,
+			transactions: engine.transactions.len() as u64,
+		});
+
+		Ok(EngineMetrics {
+			methods: snapshot
+				.methods
+				.iter()
+				.map(|(method, metrics)| MethodMetrics {
+					method: method.clone(),
+					calls: metrics.calls as i64,
+					errors: metrics.errors as i64,
+					duration: metrics.duration,
+					buckets: metrics
+						.cumulative()
+						.map(|(le, count)| HistogramBucket {
+							le,
+							count: count as i64,
+						})
+						.collect(),
+				})
+				.collect(),
+			notifications: snapshot.notifications as i64,
+			sessions: snapshot.gauges.sessions as i64,
+			live_queries: snapshot.gauges.live_queries as i64,
+			transactions: snapshot.gauges.transactions as i64,
+			prometheus: snapshot.prometheus(),
+		})
+	}
+
+	/// Returns the queries which exceeded the slow query threshold, from the oldest
+	#[napi(ts_return_type = "Promise<SlowQuery[]>")]
+	pub async fn slow_queries(&self) -> std::result::Result<JsValue, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let records = engine.slow_log.as_ref().map(|log| log.records()).unwrap_or_default();
+
+		Ok(serde_json::to_value(records)?)
+	}
+
+	/// Returns a receiver of every slow query recorded from now on
+	#[napi]
+	pub async fn slow_query_receiver(&self) -> std::result::Result<SlowQueryReceiver, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let log = engine
+			.slow_log
+			.as_ref()
+			.ok_or_else(|| Error::from_reason("The slow query log is not enabled"))?;
+
+		Ok(SlowQueryReceiver {
+			receiver: log.subscribe(),
+		})
+	}
+
+	/// Returns the receiver of the audit records, which is shared by every caller
+	#[napi]
+	pub async fn audit_receiver(&self) -> std::result::Result<AuditReceiver, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let audit =
+			engine.audit.as_ref().ok_or_else(|| Error::from_reason("Auditing is not enabled"))?;
+
+		Ok(AuditReceiver {
+			receiver: audit.receiver(),
+		})
+	}
+
+	/// Describes the active sessions with their live queries, and the open transactions
+	#[napi(ts_return_type = "Promise<EngineInspection>")]
+	pub async fn inspect(&self) -> std::result::Result<JsValue, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let inspection = inspect::inspect(
+			&engine.sessions,
+			&engine.live_queries,
+			&engine.transactions,
+			&engine.transaction_started,
+		)
+		.await;
+
+		Ok(serde_json::to_value(inspection)?)
+	}
+
+	/// Kills a live query, returning whether it was found
+	#[napi]
+	pub async fn kill_live_query(&self, id: String) -> std::result::Result<bool, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let id = Uuid::parse_str(&id).map_err(err_map)?;
+
+		if engine.live_queries.write().unwrap().remove(&id).is_none() {
+			return Ok(false);
+		}
+		engine.kvs.delete_queries(vec![id]).await.map_err(err_map)?;
+
+		Ok(true)
+	}
+
+	/// Cancels an open transaction, returning whether it was found
+	#[napi]
+	pub async fn cancel_transaction(&self, id: String) -> std::result::Result<bool, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let id = Uuid::parse_str(&id).map_err(err_map)?;
+
+		let Some((_, tx)) = engine.transactions.remove(&id) else {
+			return Ok(false);
+		};
+		engine.transaction_started.remove(&id);
+		tx.cancel().await.map_err(err_map)?;
+
+		Ok(true)
+	}
+
+	/// Measures the key counts and stored sizes of every namespace, database,
+	/// table and index within the pin of the engine, or only those selected by
+	/// the options, along with details of the storage backend.
+	///
+	/// Every key of each measured table is read, so measuring a whole datastore
+	/// is best kept to debugging, while single tables can be measured with `tables`.
+	#[napi(ts_return_type = "Promise<DatastoreStats>")]
+	pub async fn stats(
+		&self,
+		#[napi(ts_arg_type = "StatsOptions")] opts: Option<JsValue>,
+	) -> std::result::Result<JsValue, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let opts = from_value::<Option<StatsOptions>>(JsValue::from(opts))?.unwrap_or_default();
+		let pin = engine.pin(None);
+		let mut stats = stats::collect(&engine.kvs, pin.as_ref(), &opts).await.map_err(err_map)?;
+		stats.backend = Some(stats::backend(&engine.endpoint));
+
+		Ok(serde_json::to_value(stats)?)
+	}
+
+	/// Lists every version of a record kept by a versioned datastore, such as a
+	/// SurrealKV store opened with `versioned` storage, from the oldest
+	#[napi(ts_return_type = "Promise<RecordVersion[]>")]
+	pub async fn record_versions(&self, record: String) -> std::result::Result<JsValue, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let session_arc = engine.default_session();
+		let session_guard = session_arc.read().await;
+		let versions =
+			versions::list(&engine.kvs, &session_guard, &record).await.map_err(err_map)?;
+
+		Ok(serde_json::to_value(versions)?)
+	}
+
+	/// Syncs the change feeds with a peer through the supplied transport.
+	///
+	/// `pull` receives the remote versionstamp of the last sync, as a decimal
+	/// string, and resolves to a delta file of the remote changes since then,
+	/// while `push` receives a delta file of the local changes. With the `custom`
+	/// strategy, `resolve` receives each conflict as CBOR and resolves to the
+	/// merged record, or `null` to delete it.
+	#[napi]
+	pub async fn sync(
+		&self,
+		peer: String,
+		#[napi(ts_arg_type = "(since: string) => Promise<Uint8Array>")] pull: ThreadsafeFunction<
+			String,
+			Promise<Uint8Array>,
+			String,
+			Status,
+			false,
+		>,
+		#[napi(ts_arg_type = "(delta: Uint8Array) => Promise<void>")] push: ThreadsafeFunction<
+			Uint8Array,
+			Promise<()>,
+			Uint8Array,
+			Status,
+			false,
+		>,
+		#[napi(ts_arg_type = "SyncOptions")] opts: Option<JsValue>,
+		#[napi(ts_arg_type = "(conflict: Uint8Array) => Promise<Uint8Array | null>")]
+		resolve: Option<
+			ThreadsafeFunction<Uint8Array, Promise<Option<Uint8Array>>, Uint8Array, Status, false>,
+		>,
+	) -> std::result::Result<SyncReport, Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		engine.check_writable("sync with a peer")?;
+		let opts = from_value::<Option<SyncOptions>>(JsValue::from(opts))?;
+		let session_arc = engine.default_session();
+		let session_guard = session_arc.read().await;
+
+		let pull = |since: u64| async move {
+			let promise = pull.call_async(since.to_string()).await.map_err(|e| e.to_string())?;
+			let data = promise.await.map_err(|e| e.to_string())?;
+			Ok(data.to_vec())
+		};
+
+		let push = |delta: Vec<u8>| async move {
+			let promise = push.call_async(delta.into()).await.map_err(|e| e.to_string())?;
+			promise.await.map_err(|e| e.to_string())
+		};
+
+		let resolve = |conflict: Value| {
+			let resolve = resolve.as_ref();
+			async move {
+				let Some(resolve) = resolve else {
+					return Err("The custom conflict strategy requires a resolver".to_string());
+				};
+				let conflict = cbor::encode(conflict).map_err(|e| e.to_string())?;
+				let promise = resolve
+					.call_async(Vec::from(conflict).into())
+					.await
+					.map_err(|e| e.to_string())?;
+				match promise.await.map_err(|e| e.to_string())? {
+					Some(record) => cbor::decode(&record).map(Some).map_err(|e| e.to_string()),
+					None => Ok(None),
+				}
+			}
+		};
+
+		let report = sync::sync(
+			&engine.kvs,
+			&session_guard,
+			&peer,
+			opts.unwrap_or_default(),
+			pull,
+			push,
+			resolve,
+		)
+		.await
+		.map_err(err_map)?;
+
+		Ok(SyncReport {
+			pulled: report.pulled as i64,
+			pushed: report.pushed as i64,
+			conflicts: report.conflicts as i64,
+		})
+	}
+
 	#[napi]
 	pub async fn import(&self, input: String) -> std::result::Result<(), Error> {
 		let lock = self.0.read().await;
 		let engine = lock.as_ref().unwrap();
+		engine.check_writable("import data")?;
+		if let Some(pin) = engine.pin(None) {
+			pin.check_query(&input).map_err(err_map)?;
+		}
 		let session_arc = engine.default_session();
 		let session_guard = session_arc.read().await;
-		engine.kvs.import(&input, &*session_guard).await.map_err(err_map)?;
+		let start = Instant::now();
+		let res = engine.kvs.import(&input, &*session_guard).await;
+		engine.metrics.observe("import", start.elapsed(), res.is_ok());
+		res.map_err(err_map)?;
+
+		Ok(())
+	}
+
+	/// Adds an interceptor at the end of the chain run around every request.
+	///
+	/// `before` receives the CBOR encoded request and resolves to `{ request }` to
+	/// replace it, `{ response }` to respond without executing it, or `null` to
+	/// continue. `after` receives `{ request, response }` and resolves to
+	/// `{ response }` to replace the response, or `null` to keep it.
+	#[napi]
+	pub async fn add_interceptor(
+		&self,
+		#[napi(ts_arg_type = "((request: Uint8Array) => Promise<Uint8Array | null>) | null")]
+		before: Option<
+			ThreadsafeFunction<Uint8Array, Promise<Option<Uint8Array>>, Uint8Array, Status, false>,
+		>,
+		#[napi(ts_arg_type = "((exchange: Uint8Array) => Promise<Uint8Array | null>) | null")]
+		after: Option<
+			ThreadsafeFunction<Uint8Array, Promise<Option<Uint8Array>>, Uint8Array, Status, false>,
+		>,
+	) -> std::result::Result<(), Error> {
+		self.intercept(Arc::new(JsInterceptor {
+			before,
+			after,
+		}))
+		.await;
+
+		Ok(())
+	}
+
+	/// Restricts a session, or the default session when `session` is `null`, to a
+	/// namespace and optionally a database, within the pin of the engine.
+	///
+	/// Requests, `USE` statements and imports selecting anything outside the pin
+	/// fail with a permission error. A pin cannot be removed once set.
+	#[napi]
+	pub async fn pin_session(
+		&self,
+		session: Option<String>,
+		#[napi(ts_arg_type = "SessionPin")] pin: JsValue,
+	) -> std::result::Result<(), Error> {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		let session_id = session.map(|id| Uuid::parse_str(&id)).transpose().map_err(err_map)?;
+		let pin = from_value::<Pin>(pin)?;
+
+		if let Some(current) = engine.pin(session_id) {
+			if !current.contains(&pin) {
+				return Err(err_map(pin::denied(format!(
+					"The session is pinned to namespace {}",
+					current.namespace
+				))));
+			}
+		}
+
+		if let Some(session) = engine.sessions.get(&session_id).map(|s| s.clone()) {
+			pin.select(&mut *session.write().await);
+		}
+		engine.pins.insert(session_id, pin);
 
 		Ok(())
 	}
 
+	/// Sends the tracing records of every engine in the process to a callback,
+	/// or stops sending them when `handler` is `null`. Fails when the process
+	/// already has a global tracing subscriber of its own.
+	#[napi]
+	pub fn set_log_handler(
+		#[napi(ts_arg_type = "((record: LogRecord) => void) | null")] handler: Option<
+			ThreadsafeFunction<LogRecord, (), LogRecord, Status, false, true>,
+		>,
+	) -> std::result::Result<(), Error> {
+		logs::set_handler(handler.map(|handler| {
+			Box::new(move |record: logs::LogRecord| {
+				let record = LogRecord {
+					level: record.level,
+					target: record.target,
+					message: record.message,
+					fields: record.fields,
+					spans: record.spans,
+				};
+				handler.call(record, ThreadsafeFunctionCallMode::NonBlocking);
+			}) as _
+		}))
+		.map_err(err_map)
+	}
+
+	/// Replaces the tracing filter with directives such as `surrealdb_core::dbs=debug`
+	#[napi]
+	pub fn set_log_filter(directives: String) -> std::result::Result<(), Error> {
+		logs::set_filter(&directives).map_err(err_map)
+	}
+
+	/// Re-executes a log written with the `record` option against a fresh engine,
+	/// reporting the requests whose result differs from the recorded one.
+	///
+	/// The engine is opened at `endpoint`, defaulting to an in-memory datastore.
+	#[napi(ts_return_type = "Promise<ReplayReport>")]
+	pub async fn replay(
+		log: Uint8Array,
+		endpoint: Option<String>,
+		#[napi(ts_arg_type = "ConnectionOptions")] opts: Option<JsValue>,
+	) -> std::result::Result<JsValue, Error> {
+		let engine = Self::connect(endpoint.unwrap_or("mem://".to_string()), opts).await?;
+		let lock = engine.0.read().await;
+		let connection = lock.as_ref().unwrap();
+		let report =
+			recording::replay(&log, |req| connection.envelope(req)).await.map_err(err_map)?;
+
+		Ok(serde_json::to_value(report)?)
+	}
+
+	/// Returns whether the datastore at an endpoint was written with an older
+	/// storage format, and must be upgraded before it can be connected to
+	#[napi]
+	pub async fn needs_upgrade(endpoint: String) -> std::result::Result<bool, Error> {
+		let kvs = Datastore::new(&endpoint).await.map_err(err_map)?;
+		upgrade::needs_upgrade(&kvs).await.map_err(err_map)
+	}
+
+	/// Migrates the storage format of the datastore at an endpoint to the latest
+	/// version, in place or into an empty datastore at `target`, leaving the
+	/// original untouched. The datastore must not be open in another engine.
+	#[napi(ts_return_type = "Promise<UpgradeReport>")]
+	pub async fn upgrade(
+		endpoint: String,
+		target: Option<String>,
+		#[napi(ts_arg_type = "(progress: UpgradeProgress) => void")] progress: Option<
+			ThreadsafeFunction<JsValue, (), JsValue, Status, false, true>,
+		>,
+	) -> std::result::Result<JsValue, Error> {
+		if target.as_ref() == Some(&endpoint) {
+			return Err(err_map("The target of an upgrade must differ from the datastore"));
+		}
+
+		let source = Datastore::new(&endpoint).await.map_err(err_map)?;
+		let target = match target {
+			Some(target) => Some(Datastore::new(&target).await.map_err(err_map)?),
+			None => None,
+		};
+		let report = upgrade::upgrade(&source, target.as_ref(), |stage| {
+			if let Some(progress) = &progress {
+				let stage = serde_json::to_value(stage).map_err(|e| e.to_string())?;
+				progress.call(stage, ThreadsafeFunctionCallMode::NonBlocking);
+			}
+			Ok(())
+		})
+		.await
+		.map_err(err_map)?;
+
+		Ok(serde_json::to_value(report)?)
+	}
+
+	/// Removes every file of the datastore at an endpoint, failing when the path
+	/// does not hold a datastore or the datastore is still open
+	#[napi]
+	pub async fn destroy(endpoint: String) -> std::result::Result<(), Error> {
+		destroy::destroy(&endpoint).await.map_err(err_map)
+	}
+
 	#[napi]
 	pub fn version() -> std::result::Result<String, Error> {
 		Ok(env!("SURREALDB_VERSION").into())
@@ -233,17 +1140,203 @@ impl SurrealNodeEngine {
 	}
 }
 
+impl SurrealNodeEngine {
+	/// Adds an interceptor, implemented in Rust, at the end of the chain run
+	/// around every request
+	pub async fn intercept(&self, interceptor: Arc<dyn Interceptor>) {
+		let lock = self.0.read().await;
+		let engine = lock.as_ref().unwrap();
+		engine.interceptors.push(interceptor);
+	}
+}
+
 struct SurrealNodeConnection {
 	pub kvs: Arc<Datastore>,
 	pub live_queries: Arc<RwLock<HashMap<Uuid, Option<Uuid>>>>,
 	pub transactions: DashMap<Uuid, Arc<Transaction>>,
+	/// When each open transaction began, in milliseconds since the Unix epoch
+	pub transaction_started: DashMap<Uuid, f64>,
 	pub sessions: HashMap<Option<Uuid>, Arc<napi::tokio::sync::RwLock<Session>>>,
+	/// The endpoint the datastore was opened with
+	pub endpoint: String,
+	pub metrics: Arc<Metrics>,
+	/// The slow query log, when enabled in the connection options
+	pub slow_log: Option<Arc<SlowLog>>,
+	/// The audit channel, when enabled in the connection options
+	pub audit: Option<Audit>,
+	/// The log of requests and their results, when enabled in the connection options
+	pub recorder: Option<Recorder<FileWriter>>,
+	/// The interceptors run around every request
+	pub interceptors: Interceptors,
+	/// The namespace and database every session is restricted to, when set
+	pub pin: Option<Pin>,
+	/// The restrictions of individual sessions, within the pin of the engine
+	pub pins: DashMap<Option<Uuid>, Pin>,
+	/// Whether every write is rejected
+	pub read_only: bool,
 }
 
 impl SurrealNodeConnection {
 	fn default_session(&self) -> Arc<napi::tokio::sync::RwLock<Session>> {
 		self.sessions.get(&None).unwrap().clone()
 	}
+
+	/// Returns the pin restricting a session, if any
+	fn pin(&self, session_id: Option<Uuid>) -> Option<Pin> {
+		self.pins.get(&session_id).map(|pin| pin.clone()).or_else(|| self.pin.clone())
+	}
+
+	/// Rejects an operation which writes when the engine is read-only
+	fn check_writable(&self, action: &str) -> std::result::Result<(), Error> {
+		match self.read_only {
+			true => Err(err_map(readonly::denied(action))),
+			false => Ok(()),
+		}
+	}
+
+	/// Executes a request without writing, rejecting the methods which write and
+	/// running queries outside of a transaction in a read transaction, which is
+	/// cancelled once they finish
+	async fn execute_read_only(
+		&self,
+		txn: Option<Uuid>,
+		session_id: Option<Uuid>,
+		req: Request,
+	) -> TxResult<DbResult> {
+		let method = req.method.to_str();
+		if readonly::is_write(method) {
+			return Err(readonly::denied(&format!("run the {method} method")));
+		}
+		if txn.is_some() || !readonly::is_query(method) {
+			return self.execute_pinned(txn, session_id, req).await;
+		}
+
+		let tx = self
+			.kvs
+			.transaction(TransactionType::Read, LockType::Optimistic)
+			.await
+			.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
+		let tx = Arc::new(tx);
+		let id = Uuid::now_v7();
+		self.transactions.insert(id, tx.clone());
+		let res = self.execute_pinned(Some(id), session_id, req).await;
+		self.transactions.remove(&id);
+		let _ = tx.cancel().await;
+		res
+	}
+
+	/// Executes a request, keeping its session within its pin
+	async fn execute_pinned(
+		&self,
+		txn: Option<Uuid>,
+		session_id: Option<Uuid>,
+		req: Request,
+	) -> TxResult<DbResult> {
+		let Some(pin) = self.pin(session_id) else {
+			return RpcProtocol::execute(self, txn, session_id, req.method, req.params).await;
+		};
+
+		pin.check(req.method.to_str(), &req.params)?;
+		let session = self.sessions.get(&session_id).map(|s| s.clone());
+		if let Some(session) = &session {
+			pin.select(&mut *session.write().await);
+		}
+
+		let res = RpcProtocol::execute(self, txn, session_id, req.method, req.params).await;
+
+		// Requests selecting anything outside the pin are rejected above, but a reset
+		// clears the selection and an authentication token may carry its own, so
+		// select the pin again
+		let session = session.or_else(|| self.sessions.get(&session_id).map(|s| s.clone()));
+		if let Some(session) = session {
+			pin.select(&mut *session.write().await);
+		}
+
+		res
+	}
+
+	/// Executes a decoded request against this connection
+	async fn run(&self, req: Request) -> TxResult<DbResult> {
+		let method = req.method.to_str().to_string();
+		let session_id: Option<Uuid> = req.session_id.map(Into::into);
+		let txn: Option<Uuid> = req.txn.map(Into::into);
+		let captured = self.slow_log.as_ref().and_then(|log| log.capture(&req));
+		let start = Instant::now();
+		let res = match self.read_only {
+			true => self.execute_read_only(txn, session_id, req).await,
+			false => self.execute_pinned(txn, session_id, req).await,
+		};
+		let elapsed = start.elapsed();
+		self.metrics.observe(&method, elapsed, res.is_ok());
+
+		if let Some(audit) = &self.audit {
+			let error = res.as_ref().err().map(audit::error_kind);
+			let session = self.sessions.get(&session_id).map(|s| s.clone());
+			let session = match &session {
+				Some(session) => Some(session.read().await),
+				None => None,
+			};
+			let duration = elapsed.as_secs_f64() * 1000.0;
+			audit.record(&method, session_id, session.as_deref(), txn, error, duration);
+		}
+
+		if let (Some(log), Some(captured)) = (&self.slow_log, captured) {
+			if log.is_slow(elapsed) {
+				// Record it in the background, as capturing the plan runs another query
+				let log = log.clone();
+				let kvs = self.kvs.clone();
+				let session = self.sessions.get(&session_id).map(|s| s.clone());
+				let failed = res.is_err();
+				napi::tokio::spawn(async move {
+					if let Some(session) = session {
+						let session = session.read().await.clone();
+						log.record(&kvs, &session, captured, elapsed, failed).await;
+					}
+				});
+			}
+		}
+
+		res
+	}
+
+	/// Executes a decoded request, resolving to its result or to its error envelope
+	async fn respond(&self, req: Request) -> std::result::Result<Value, Value> {
+		self.run(req).await.map(Value::from_t).map_err(error_envelope)
+	}
+
+	/// Executes a decoded request, returning either its result or an error envelope
+	async fn envelope(&self, req: Request) -> Value {
+		self.respond(req).await.unwrap_or_else(|envelope| envelope)
+	}
+
+	/// Executes a decoded request, recording it along with its result when enabled
+	async fn record(&self, req: Request) -> std::result::Result<Value, Value> {
+		let entry = self.recorder.as_ref().map(|_| recording::entry(&req));
+		let response = self.respond(req).await;
+
+		if let (Some(recorder), Some(entry)) = (&self.recorder, entry) {
+			let (Ok(value) | Err(value)) = &response;
+			if let Err(err) = recorder.record(entry, value) {
+				tracing::warn!("Failed to record a request: {err}");
+			}
+		}
+
+		response
+	}
+
+	/// Executes a decoded request through the interceptors, encoding either its
+	/// result or an error envelope
+	async fn handle(&self, format: Format, req: Request) -> std::result::Result<Vec<u8>, Error> {
+		let response = self.interceptors.run(req, |req| self.record(req)).await;
+		format.encode(response.unwrap_or_else(|envelope| envelope))
+	}
+}
+
+/// Wraps an RPC error in the envelope returned to the SDK
+fn error_envelope(rpc_err: TxError) -> Value {
+	let mut envelope = surrealdb_types::Object::default();
+	envelope.insert("error".to_string(), Value::from_t(rpc_err));
+	Value::Object(envelope)
 }
 
 type TxError = surrealdb_types::Error;
@@ -330,16 +1423,21 @@ impl RpcProtocol for SurrealNodeConnection {
 
 	/// Begin a new transaction
 	async fn begin(&self, _txn: Option<Uuid>, _session_id: Option<Uuid>) -> TxResult<DbResult> {
-		// Create a new transaction
+		// Create a new transaction, which only reads on a read-only engine
+		let kind = match self.read_only {
+			true => TransactionType::Read,
+			false => TransactionType::Write,
+		};
 		let tx = self
 			.kvs()
-			.transaction(TransactionType::Write, LockType::Optimistic)
+			.transaction(kind, LockType::Optimistic)
 			.await
 			.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
 		// Generate a unique transaction ID
 		let id = Uuid::now_v7();
 		// Store the transaction in the map
 		self.transactions.insert(id, Arc::new(tx));
+		self.transaction_started.insert(id, inspect::now());
 		// Return the transaction ID to the client
 		Ok(DbResult::Other(Value::Uuid(surrealdb_types::Uuid::from(id))))
 	}
@@ -359,6 +1457,7 @@ impl RpcProtocol for SurrealNodeConnection {
 		let Some((_, tx)) = self.transactions.remove(&txn_id) else {
 			return Err(surrealdb_core::rpc::invalid_params("Transaction not found"));
 		};
+		self.transaction_started.remove(&txn_id);
 		tx.commit().await.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
 		Ok(DbResult::Other(Value::None))
 	}
@@ -378,6 +1477,7 @@ impl RpcProtocol for SurrealNodeConnection {
 		let Some((_, tx)) = self.transactions.remove(&txn_id) else {
 			return Err(surrealdb_core::rpc::invalid_params("Transaction not found"));
 		};
+		self.transaction_started.remove(&txn_id);
 		tx.cancel().await.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
 
 		// Return success
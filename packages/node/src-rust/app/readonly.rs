@@ -0,0 +1,52 @@
+/// The RPC methods which write to the datastore
+const WRITE_METHODS: &[&str] = &[
+	"create",
+	"insert",
+	"insert_relation",
+	"update",
+	"upsert",
+	"merge",
+	"patch",
+	"delete",
+	"relate",
+	"live",
+	"kill",
+	"signup",
+];
+
+/// The RPC methods which run arbitrary statements, and so run in a read
+/// transaction on a read-only engine
+const QUERY_METHODS: &[&str] = &["query", "run", "graphql"];
+
+/// The backends which can be opened without locking or writing to the store, so
+/// that another engine may keep using it
+const READ_ONLY_BACKENDS: &[&str] = &["memory", "indxdb"];
+
+pub fn is_write(method: &str) -> bool {
+	WRITE_METHODS.contains(&method)
+}
+
+pub fn is_query(method: &str) -> bool {
+	QUERY_METHODS.contains(&method)
+}
+
+/// Rejects a read-only engine on a backend which locks the store when opened,
+/// such as RocksDB or SurrealKV, as the datastore only opens them read-write
+pub fn check_backend(endpoint: &str) -> Result<(), String> {
+	let scheme = endpoint.split([':', '?']).next().unwrap_or_default();
+	if READ_ONLY_BACKENDS.contains(&scheme) {
+		return Ok(());
+	}
+
+	Err(format!(
+		"The {scheme} backend cannot be opened read-only, as it is locked and may be written to when opened"
+	))
+}
+
+/// The error returned when a read-only engine is asked to write
+pub fn denied(action: &str) -> surrealdb_types::Error {
+	surrealdb_types::Error::not_allowed(
+		format!("The engine is read-only and cannot {action}"),
+		None,
+	)
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::future::Future;
+use std::io::BufWriter;
+use std::io::Write;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+
+use serde::Serialize;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_core::rpc::Request;
+use surrealdb_types::Array;
+use surrealdb_types::Object;
+use surrealdb_types::Value;
+use uuid::Uuid;
+
+use super::format::to_json;
+
+/// The size of the length prefix written before each entry
+const PREFIX_LEN: usize = 4;
+
+/// The methods whose parameters and results hold credentials or tokens
+const REDACTED_METHODS: [&str; 3] = ["signin", "signup", "authenticate"];
+
+/// The fields of `signin` and `signup` parameters kept in the log, as they name
+/// who signs in rather than prove it
+const IDENTIFYING_FIELDS: [&str; 10] =
+	["ns", "db", "ac", "user", "NS", "DB", "AC", "namespace", "database", "access"];
+
+/// The value recorded in place of a credential or token
+const REDACTED: &str = "[REDACTED]";
+
+/// Appends an entry for each request and its result envelope to a log.
+///
+/// Each entry is a CBOR map holding the `method`, `params`, `session` and `txn`
+/// of the request along with its `result` envelope, prefixed with its length as
+/// a big-endian `u32`. Entries are only ever appended, so a log can be read while
+/// it is still being written, up to the last complete entry.
+///
+/// The credentials passed to `signin`, `signup` and `authenticate` and the tokens
+/// they return are never recorded. Their entries are marked as `redacted`, and
+/// skipped when the log is replayed.
+pub struct Recorder<W> {
+	sink: Mutex<W>,
+}
+
+impl<W: Write> Recorder<W> {
+	pub fn new(sink: W) -> Self {
+		Recorder {
+			sink: Mutex::new(sink),
+		}
+	}
+
+	/// Appends a request captured with `entry` along with its result envelope
+	pub fn record(&self, mut entry: Object, result: &Value) -> Result<(), String> {
+		let result = match entry.get("redacted") {
+			Some(Value::Bool(true)) => redact_result(result),
+			_ => result.clone(),
+		};
+		entry.insert("result".to_string(), result);
+		let data = cbor::encode(Value::Object(entry)).map(Vec::from).map_err(|e| e.to_string())?;
+		let len = u32::try_from(data.len()).map_err(|_| "The entry is too large to record")?;
+
+		// The entry is written at once, so concurrent requests never interleave
+		let mut buf = Vec::with_capacity(PREFIX_LEN + data.len());
+		buf.extend_from_slice(&len.to_be_bytes());
+		buf.extend_from_slice(&data);
+		self.sink.lock().unwrap().write_all(&buf).map_err(|e| e.to_string())
+	}
+}
+
+/// Writes a log to a file from a dedicated thread, so requests never wait on
+/// disk I/O.
+///
+/// Entries are buffered, and flushed whenever the thread has written every
+/// pending entry. Dropping the writer waits for the remaining entries to be
+/// written, so the log is complete once the engine is freed.
+pub struct FileWriter {
+	sender: Option<mpsc::Sender<Vec<u8>>>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl FileWriter {
+	pub fn spawn(file: File) -> Self {
+		let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+		let thread = std::thread::spawn(move || {
+			let mut file = BufWriter::new(file);
+			while let Ok(data) = receiver.recv() {
+				let mut result = file.write_all(&data);
+				while let Ok(data) = receiver.try_recv() {
+					result = result.and_then(|_| file.write_all(&data));
+				}
+				if let Err(err) = result.and_then(|_| file.flush()) {
+					tracing::warn!("Failed to write the request log: {err}");
+				}
+			}
+		});
+
+		FileWriter {
+			sender: Some(sender),
+			thread: Some(thread),
+		}
+	}
+}
+
+impl Write for FileWriter {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		let sender = self.sender.as_ref().expect("The writer is open until dropped");
+		sender.send(buf.to_vec()).map_err(|_| std::io::ErrorKind::BrokenPipe)?;
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(())
+	}
+}
+
+impl Drop for FileWriter {
+	fn drop(&mut self) {
+		// Closing the channel ends the thread once every entry is written
+		self.sender.take();
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+/// Captures the fields of a request before it is executed
+pub fn entry(req: &Request) -> Object {
+	let uuid = |id: Option<Uuid>| match id {
+		Some(id) => Value::Uuid(surrealdb_types::Uuid::from(id)),
+		None => Value::None,
+	};
+
+	let method = req.method.to_str();
+	let redacted = REDACTED_METHODS.contains(&method);
+	let params = match redacted {
+		true => Array::from(req.params.iter().map(redact_param).collect::<Vec<_>>()),
+		false => req.params.clone(),
+	};
+
+	let mut entry = Object::default();
+	entry.insert("method".to_string(), Value::String(method.to_string()));
+	entry.insert("params".to_string(), Value::Array(params));
+	entry.insert("session".to_string(), uuid(req.session_id.map(Into::into)));
+	entry.insert("txn".to_string(), uuid(req.txn.map(Into::into)));
+	if redacted {
+		entry.insert("redacted".to_string(), Value::Bool(true));
+	}
+	entry
+}
+
+/// Keeps the fields of a credentials object naming who signs in, redacting the
+/// others, or redacts a token entirely
+fn redact_param(param: &Value) -> Value {
+	match param {
+		Value::Object(fields) => {
+			let mut redacted = fields.clone();
+			for key in fields.keys().filter(|k| !IDENTIFYING_FIELDS.contains(&k.as_str())) {
+				redacted.insert(key.clone(), Value::String(REDACTED.to_string()));
+			}
+			Value::Object(redacted)
+		}
+		_ => Value::String(REDACTED.to_string()),
+	}
+}
+
+/// Redacts the token returned by a successful request, keeping error envelopes
+fn redact_result(envelope: &Value) -> Value {
+	match envelope {
+		Value::Object(envelope) if envelope.get("error").is_some() => {
+			Value::Object(envelope.clone())
+		}
+		_ => Value::String(REDACTED.to_string()),
+	}
+}
+
+#[derive(Serialize)]
+pub struct Divergence {
+	/// The position of the entry in the log
+	pub index: usize,
+	pub method: String,
+	pub recorded: serde_json::Value,
+	pub actual: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct ReplayReport {
+	/// The number of requests replayed
+	pub requests: usize,
+	/// The number of redacted requests, which could not be replayed
+	pub skipped: usize,
+	pub divergences: Vec<Divergence>,
+}
+
+/// Re-executes every request of a log, comparing each result with the recorded one.
+///
+/// Transaction and live query ids are generated anew, so ids returned by the
+/// recorded requests are mapped to the replayed ones, both when comparing the
+/// results and when passing them to later requests. The execution time of each
+/// query statement is ignored. Other nondeterministic values, such as random ids
+/// or the current time, are reported as divergences.
+pub async fn replay<F, Fut>(log: &[u8], mut run: F) -> Result<ReplayReport, String>
+where
+	F: FnMut(Request) -> Fut,
+	Fut: Future<Output = Value>,
+{
+	let mut ids = HashMap::new();
+	let mut report = ReplayReport {
+		requests: 0,
+		skipped: 0,
+		divergences: Vec::new(),
+	};
+
+	for (index, mut entry) in decode(log)?.into_iter().enumerate() {
+		let recorded = entry.remove("result").unwrap_or(Value::None);
+		let method = match entry.get("method") {
+			Some(Value::String(method)) => method.clone(),
+			_ => return Err(format!("The entry at {index} has no method")),
+		};
+		if let Some(Value::Bool(true)) = entry.remove("redacted") {
+			report.skipped += 1;
+			continue;
+		}
+
+		if let Some(txn) = entry.remove("txn") {
+			entry.insert("txn".to_string(), remap(txn, &ids));
+		}
+		if let Some(Value::Array(params)) = entry.remove("params") {
+			let params = params.into_vec().into_iter().map(|p| remap(p, &ids)).collect::<Vec<_>>();
+			entry.insert("params".to_string(), Value::Array(Array::from(params)));
+		}
+
+		let req =
+			Request::from_object(entry).map_err(|e| format!("Invalid entry at {index}: {e}"))?;
+		let actual = run(req).await;
+		report.requests += 1;
+
+		// Requests returning a new id are matched by their position in the log
+		let returned = (returned_id(&method, &recorded), returned_id(&method, &actual));
+		if let (Some(from), Some(to)) = returned {
+			ids.insert(from, to);
+			continue;
+		}
+
+		let (recorded, actual) = (normalize(recorded), normalize(actual));
+		if recorded != actual {
+			report.divergences.push(Divergence {
+				index,
+				method,
+				recorded: to_json(recorded),
+				actual: to_json(actual),
+			});
+		}
+	}
+
+	Ok(report)
+}
+
+/// Decodes the complete entries of a log
+fn decode(log: &[u8]) -> Result<Vec<Object>, String> {
+	let mut entries = Vec::new();
+	let mut rest = log;
+
+	while rest.len() >= PREFIX_LEN {
+		let (prefix, tail) = rest.split_at(PREFIX_LEN);
+		let len = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+		if tail.len() < len {
+			break; // The last entry was not completely written
+		}
+
+		let (data, tail) = tail.split_at(len);
+		match cbor::decode(data).map_err(|e| e.to_string())? {
+			Value::Object(entry) => entries.push(entry),
+			_ => return Err(format!("Invalid entry at {}", entries.len())),
+		}
+		rest = tail;
+	}
+
+	Ok(entries)
+}
+
+/// Replaces an id returned by a recorded request with the replayed one
+fn remap(value: Value, ids: &HashMap<Uuid, Uuid>) -> Value {
+	match value {
+		Value::Uuid(id) => match ids.get(&id.into_inner()) {
+			Some(to) => Value::Uuid(surrealdb_types::Uuid::from(*to)),
+			None => Value::Uuid(id),
+		},
+		value => value,
+	}
+}
+
+/// Returns the id generated by a `live`, `begin` or `attach` request
+fn returned_id(method: &str, envelope: &Value) -> Option<Uuid> {
+	if !matches!(method, "live" | "begin" | "attach") {
+		return None;
+	}
+
+	match envelope {
+		Value::Uuid(id) => Some((*id).into_inner()),
+		Value::Object(envelope) => match envelope.get("result") {
+			Some(Value::Uuid(id)) => Some((*id).into_inner()),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Removes the execution time from the statement results of a query
+fn normalize(envelope: Value) -> Value {
+	match envelope {
+		Value::Array(results) => {
+			let results = results
+				.into_vec()
+				.into_iter()
+				.map(|result| match result {
+					Value::Object(mut result) if result.get("status").is_some() => {
+						result.remove("time");
+						Value::Object(result)
+					}
+					result => result,
+				})
+				.collect::<Vec<_>>();
+			Value::Array(Array::from(results))
+		}
+		Value::Object(mut envelope) => {
+			if let Some(result) = envelope.remove("result") {
+				envelope.insert("result".to_string(), normalize(result));
+			}
+			Value::Object(envelope)
+		}
+		envelope => envelope,
+	}
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::rpc::Request;
+use surrealdb_types::Value;
+use surrealdb_types::Variables;
+
+use super::data::query;
+use super::format::to_json;
+use super::inspect::now;
+
+/// The number of slow queries kept when not specified
+const DEFAULT_CAPACITY: u32 = 100;
+
+/// The number of undelivered slow queries held for each subscriber when not specified
+const DEFAULT_BUFFER: u32 = 1024;
+
+/// The value recorded in place of each parameter when parameters are redacted
+const REDACTED: &str = "[redacted]";
+
+#[derive(Deserialize, Clone)]
+pub struct SlowQueryOptions {
+	/// The duration above which a query is recorded, in milliseconds
+	pub threshold: u32,
+	/// The number of slow queries kept in memory
+	pub capacity: Option<u32>,
+	/// Whether to record parameter names without their values
+	pub redact_params: Option<bool>,
+	/// Whether to capture the plan of slow `SELECT` statements
+	pub explain: Option<bool>,
+	/// The number of slow queries held for each subscriber until it receives them
+	pub buffer: Option<u32>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SlowQuery {
+	pub query: String,
+	pub namespace: Option<String>,
+	pub database: Option<String>,
+	pub params: BTreeMap<String, serde_json::Value>,
+	/// How long the query took, in milliseconds
+	pub duration: f64,
+	/// When the query finished, in milliseconds since the Unix epoch
+	pub finished: f64,
+	/// Whether the query returned an error
+	pub failed: bool,
+	pub planner_strategy: String,
+	/// The output of `EXPLAIN`, when the query is a single `SELECT` statement
+	pub explain: Option<serde_json::Value>,
+	/// The number of slow queries a subscriber missed since the previous one, as
+	/// its buffer was full
+	pub dropped: u64,
+}
+
+/// The text and parameters of a query, captured before it runs
+pub struct Captured {
+	query: String,
+	vars: Option<Value>,
+}
+
+/// A bounded channel to a subscriber, with the number of slow queries it missed
+struct Subscriber {
+	tx: channel::Sender<SlowQuery>,
+	dropped: u64,
+}
+
+/// Records the queries exceeding a duration threshold in a ring buffer, and
+/// sends them to any subscribed receivers.
+///
+/// Subscribers are never waited on, so when one falls behind and its buffer is
+/// full, slow queries are dropped and counted in the next one it receives.
+pub struct SlowLog {
+	options: SlowQueryOptions,
+	planner_strategy: String,
+	records: Mutex<VecDeque<SlowQuery>>,
+	subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl SlowLog {
+	pub fn new(options: SlowQueryOptions, planner_strategy: &str) -> Self {
+		let capacity = options.capacity.unwrap_or(DEFAULT_CAPACITY) as usize;
+		SlowLog {
+			options,
+			planner_strategy: planner_strategy.to_string(),
+			records: Mutex::new(VecDeque::with_capacity(capacity)),
+			subscribers: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Captures the text and parameters of a `query` request before it is executed
+	pub fn capture(&self, req: &Request) -> Option<Captured> {
+		if req.method.to_str() != "query" {
+			return None;
+		}
+
+		let mut params = req.params.clone().into_vec().into_iter();
+		let Some(Value::String(query)) = params.next() else {
+			return None;
+		};
+
+		Some(Captured {
+			query,
+			vars: params.next(),
+		})
+	}
+
+	pub fn is_slow(&self, elapsed: Duration) -> bool {
+		elapsed.as_secs_f64() * 1000.0 >= self.options.threshold as f64
+	}
+
+	/// Records a slow query, running `EXPLAIN` for it in the session it ran in
+	pub async fn record(
+		&self,
+		kvs: &Datastore,
+		session: &Session,
+		captured: Captured,
+		elapsed: Duration,
+		failed: bool,
+	) {
+		let redact = self.options.redact_params.unwrap_or(false);
+		let mut params = BTreeMap::new();
+		let mut vars = Variables::new();
+		if let Some(Value::Object(object)) = captured.vars {
+			for (name, value) in object.iter() {
+				let recorded = match redact {
+					true => serde_json::Value::String(REDACTED.to_string()),
+					false => to_json(value.clone()),
+				};
+				params.insert(name.clone(), recorded);
+				vars.insert(name.clone(), value.clone());
+			}
+		}
+
+		let explain = match self.options.explain.unwrap_or(false) {
+			true => explain(kvs, session, &captured.query, vars).await,
+			false => None,
+		};
+
+		let record = SlowQuery {
+			query: captured.query,
+			namespace: session.ns.clone(),
+			database: session.db.clone(),
+			params,
+			duration: elapsed.as_secs_f64() * 1000.0,
+			finished: now(),
+			failed,
+			planner_strategy: self.planner_strategy.clone(),
+			explain,
+			dropped: 0,
+		};
+
+		self.subscribers.lock().unwrap().retain_mut(|subscriber| {
+			let record = SlowQuery {
+				dropped: subscriber.dropped,
+				..record.clone()
+			};
+			match subscriber.tx.try_send(record) {
+				Ok(()) => subscriber.dropped = 0,
+				Err(channel::TrySendError::Full(_)) => subscriber.dropped += 1,
+				Err(channel::TrySendError::Closed(_)) => return false,
+			}
+			true
+		});
+
+		let capacity = self.options.capacity.unwrap_or(DEFAULT_CAPACITY) as usize;
+		let mut records = self.records.lock().unwrap();
+		while records.len() >= capacity.max(1) {
+			records.pop_front();
+		}
+		records.push_back(record);
+	}
+
+	/// Returns the recorded slow queries, from the oldest
+	pub fn records(&self) -> Vec<SlowQuery> {
+		self.records.lock().unwrap().iter().cloned().collect()
+	}
+
+	/// Returns a receiver of every slow query recorded from now on
+	pub fn subscribe(&self) -> channel::Receiver<SlowQuery> {
+		let buffer = self.options.buffer.unwrap_or(DEFAULT_BUFFER).max(1);
+		let (tx, rx) = channel::bounded(buffer as usize);
+		self.subscribers.lock().unwrap().push(Subscriber {
+			tx,
+			dropped: 0,
+		});
+		rx
+	}
+}
+
+/// Runs `EXPLAIN` for a query made of a single `SELECT` statement.
+///
+/// Other statements may write, so they are never run a second time.
+async fn explain(
+	kvs: &Datastore,
+	session: &Session,
+	sql: &str,
+	vars: Variables,
+) -> Option<serde_json::Value> {
+	let sql = sql.trim().trim_end_matches(';').trim_end();
+	let select = sql.get(..7).is_some_and(|s| s.eq_ignore_ascii_case("SELECT "));
+	if !select || sql.contains(';') || sql.to_ascii_uppercase().contains(" EXPLAIN") {
+		return None;
+	}
+
+	query(kvs, session, &format!("{sql} EXPLAIN"), Some(vars)).await.ok().map(to_json)
+}
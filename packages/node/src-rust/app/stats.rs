@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use surrealdb_core::catalog::DatabaseId;
+use surrealdb_core::catalog::NamespaceId;
+use surrealdb_core::key::table;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::kvs::KVKey;
+use surrealdb_core::kvs::LockType;
+use surrealdb_core::kvs::Transaction;
+use surrealdb_core::kvs::TransactionType;
+
+use super::pin::Pin;
+
+/// The number of keys read at a time while measuring a table
+const BATCH_SIZE: u32 = 1000;
+
+/// The byte following the table prefix in the keys of its records
+const RECORD_MARKER: u8 = b'*';
+
+/// The byte following the table prefix in the keys of its index entries
+const INDEX_MARKER: u8 = b'+';
+
+/// The number of keys and the bytes of their keys and values
+#[derive(Serialize, Default, Clone, Copy)]
+pub struct Usage {
+	pub keys: u64,
+	pub bytes: u64,
+}
+
+impl Usage {
+	fn add(&mut self, other: Usage) {
+		self.keys += other.keys;
+		self.bytes += other.bytes;
+	}
+}
+
+#[derive(Serialize, Default)]
+pub struct TableStats {
+	#[serde(flatten)]
+	pub usage: Usage,
+	pub records: Usage,
+	pub indexes: BTreeMap<String, Usage>,
+}
+
+#[derive(Serialize, Default)]
+pub struct DatabaseStats {
+	#[serde(flatten)]
+	pub usage: Usage,
+	pub tables: BTreeMap<String, TableStats>,
+}
+
+#[derive(Serialize, Default)]
+pub struct NamespaceStats {
+	#[serde(flatten)]
+	pub usage: Usage,
+	pub databases: BTreeMap<String, DatabaseStats>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct StatsOptions {
+	/// Only measure the databases of this namespace
+	pub namespace: Option<String>,
+	/// Only measure the databases with this name
+	pub database: Option<String>,
+	/// Only measure the tables with these names
+	pub tables: Option<Vec<String>>,
+}
+
+impl StatsOptions {
+	fn allows(&self, ns: &str, db: Option<&str>, tb: Option<&str>) -> bool {
+		self.namespace.as_deref().is_none_or(|n| n == ns)
+			&& db.is_none_or(|db| self.database.as_deref().is_none_or(|d| d == db))
+			&& tb.is_none_or(|tb| self.tables.as_ref().is_none_or(|t| t.iter().any(|t| t == tb)))
+	}
+}
+
+#[derive(Serialize, Default)]
+pub struct DatastoreStats {
+	#[serde(flatten)]
+	pub usage: Usage,
+	pub namespaces: BTreeMap<String, NamespaceStats>,
+	pub backend: Option<BackendStats>,
+}
+
+/// Measures every table of every database in the datastore within a pin, or
+/// only those selected by the options.
+///
+/// Every key of each measured table is read, so the time taken grows with the
+/// size of the measured tables, which the options can narrow down. Each table
+/// is read in a read transaction of its own, so that no snapshot is held for
+/// the whole measurement. Sizes are the lengths of the stored keys and values,
+/// before any compression by the storage backend. A table also counts the keys
+/// of its definitions and change feed, which belong to neither its records nor
+/// its indexes.
+pub async fn collect(
+	kvs: &Datastore,
+	pin: Option<&Pin>,
+	options: &StatsOptions,
+) -> Result<DatastoreStats, String> {
+	measure(kvs, pin, options).await.map_err(|e| e.to_string())
+}
+
+async fn measure(
+	kvs: &Datastore,
+	pin: Option<&Pin>,
+	options: &StatsOptions,
+) -> anyhow::Result<DatastoreStats> {
+	let allows = |ns: &str, db: Option<&str>| {
+		pin.is_none_or(|pin| pin.allows(Some(ns), db)) && options.allows(ns, db, None)
+	};
+	let mut stats = DatastoreStats::default();
+
+	let tx = read(kvs).await?;
+	let namespaces = tx.all_ns().await;
+	let _ = tx.cancel().await;
+
+	for ns in namespaces?.iter() {
+		if !allows(&ns.name, None) {
+			continue;
+		}
+		let mut ns_stats = NamespaceStats::default();
+
+		let tx = read(kvs).await?;
+		let databases = tx.all_db(ns.namespace_id).await;
+		let _ = tx.cancel().await;
+
+		for db in databases?.iter() {
+			if !allows(&ns.name, Some(&db.name)) {
+				continue;
+			}
+			let mut db_stats = DatabaseStats::default();
+
+			let tx = read(kvs).await?;
+			let tables = tx.all_tb(ns.namespace_id, db.database_id, None).await;
+			let _ = tx.cancel().await;
+
+			for tb in tables?.iter() {
+				if !options.allows(&ns.name, Some(&db.name), Some(&tb.name)) {
+					continue;
+				}
+				let tx = read(kvs).await?;
+				let tb_stats = measure_table(&tx, ns.namespace_id, db.database_id, &tb.name).await;
+				let _ = tx.cancel().await;
+				let tb_stats = tb_stats?;
+				db_stats.usage.add(tb_stats.usage);
+				db_stats.tables.insert(tb.name.clone(), tb_stats);
+			}
+
+			ns_stats.usage.add(db_stats.usage);
+			ns_stats.databases.insert(db.name.clone(), db_stats);
+		}
+
+		stats.usage.add(ns_stats.usage);
+		stats.namespaces.insert(ns.name.clone(), ns_stats);
+	}
+
+	Ok(stats)
+}
+
+/// Opens a read transaction
+async fn read(kvs: &Datastore) -> anyhow::Result<Transaction> {
+	Ok(kvs.transaction(TransactionType::Read, LockType::Optimistic).await?)
+}
+
+/// Scans the key range of a table, sorting its keys into records and indexes
+async fn measure_table(
+	tx: &Transaction,
+	ns: NamespaceId,
+	db: DatabaseId,
+	tb: &str,
+) -> anyhow::Result<TableStats> {
+	let mut stats = TableStats::default();
+	for ix in tx.all_tb_indexes(ns, db, tb).await?.iter() {
+		stats.indexes.insert(ix.name.clone(), Usage::default());
+	}
+
+	let prefix = table::all::new(ns, db, tb).encode_key()?;
+	let mut end = prefix.clone();
+	end.push(0xff);
+	let mut beg = prefix.clone();
+
+	loop {
+		let batch = tx.scan(beg.clone()..end.clone(), BATCH_SIZE, None).await?;
+
+		for (key, value) in &batch {
+			let usage = Usage {
+				keys: 1,
+				bytes: (key.len() + value.len()) as u64,
+			};
+			stats.usage.add(usage);
+
+			let rest = &key[prefix.len()..];
+			match rest.split_first() {
+				Some((&RECORD_MARKER, _)) => stats.records.add(usage),
+				Some((&INDEX_MARKER, rest)) => {
+					// The index name is stored as a null terminated string
+					let name = rest.split(|b| *b == 0).next().unwrap_or_default();
+					let name = String::from_utf8_lossy(name).into_owned();
+					stats.indexes.entry(name).or_default().add(usage);
+				}
+				_ => (),
+			}
+		}
+
+		// Continue from just after the last key read
+		match batch.last() {
+			Some((key, _)) if batch.len() == BATCH_SIZE as usize => {
+				beg = key.clone();
+				beg.push(0x00);
+			}
+			_ => break,
+		}
+	}
+
+	Ok(stats)
+}
+
+/// Details of the storage backend, read from the files of the store on disk
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BackendStats {
+	Memory,
+	Rocksdb {
+		path: String,
+		sst_files: u64,
+		sst_bytes: u64,
+		/// The bytes written to the memtables and not yet flushed to SST files,
+		/// which the live write-ahead logs hold until the memtables are flushed
+		memtable_bytes: u64,
+		/// The memtable size at which RocksDB flushes to SST files, from the
+		/// latest options file of the store
+		memtable_limit: Option<u64>,
+	},
+	Surrealkv {
+		path: String,
+		segments: u64,
+		bytes: u64,
+	},
+}
+
+/// Splits an endpoint into its scheme and the path of its storage directory
+pub fn location(endpoint: &str) -> Option<(&str, &str)> {
+	let (scheme, path) = endpoint.split_once(':')?;
+	let path = path.trim_start_matches("//");
+	let path = path.split_once('?').map_or(path, |(path, _)| path);
+	Some((scheme, path))
+}
+
+/// Inspects the storage backend of an endpoint
+pub fn backend(endpoint: &str) -> BackendStats {
+	let Some((scheme, path)) = location(endpoint) else {
+		return BackendStats::Memory;
+	};
+
+	let mut files = Vec::new();
+	disk_files(Path::new(path), &mut files);
+	let total = |filter: &dyn Fn(&Path) -> bool| {
+		files
+			.iter()
+			.filter(|(file, _)| filter(file))
+			.fold((0, 0), |(n, b), (_, len)| (n + 1, b + len))
+	};
+	let extension = |file: &Path, ext: &str| file.extension().is_some_and(|e| e == ext);
+
+	match scheme {
+		"rocksdb" => {
+			let (sst_files, sst_bytes) = total(&|file| extension(file, "sst"));
+			// Archived logs hold writes which are already flushed
+			let (_, memtable_bytes) =
+				total(&|file| extension(file, "log") && file.parent() == Some(Path::new(path)));
+			BackendStats::Rocksdb {
+				path: path.to_string(),
+				sst_files,
+				sst_bytes,
+				memtable_bytes,
+				memtable_limit: memtable_limit(&files),
+			}
+		}
+		scheme if scheme.starts_with("surrealkv") => {
+			// Segment files are named after their sequence number
+			let (segments, _) = total(&|file| {
+				file.file_stem().and_then(|stem| stem.to_str()).is_some_and(|stem| {
+					!stem.is_empty() && stem.bytes().all(|b| b.is_ascii_digit())
+				})
+			});
+			let (_, bytes) = total(&|_| true);
+			BackendStats::Surrealkv {
+				path: path.to_string(),
+				segments,
+				bytes,
+			}
+		}
+		_ => BackendStats::Memory,
+	}
+}
+
+/// Reads the size and number of the write buffers of the default column family
+/// from the latest RocksDB options file, which are named `OPTIONS-<number>`
+fn memtable_limit(files: &[(PathBuf, u64)]) -> Option<u64> {
+	let (_, file) = files
+		.iter()
+		.filter_map(|(file, _)| {
+			let name = file.file_name()?.to_str()?;
+			let number = name.strip_prefix("OPTIONS-")?.parse::<u64>().ok()?;
+			Some((number, file))
+		})
+		.max_by_key(|(number, _)| *number)?;
+	let options = fs::read_to_string(file).ok()?;
+
+	let mut section = "";
+	let mut size = None;
+	let mut count = None;
+	for line in options.lines().map(str::trim) {
+		if line.starts_with('[') {
+			section = line;
+			continue;
+		}
+		if section != "[CFOptions \"default\"]" {
+			continue;
+		}
+		match line.split_once('=') {
+			Some(("write_buffer_size", value)) => size = value.parse::<u64>().ok(),
+			Some(("max_write_buffer_number", value)) => count = value.parse::<u64>().ok(),
+			_ => (),
+		}
+	}
+
+	Some(size? * count.unwrap_or(1))
+}
+
+/// Lists the files below a directory along with their sizes
+fn disk_files(dir: &Path, files: &mut Vec<(PathBuf, u64)>) {
+	let Ok(entries) = fs::read_dir(dir) else {
+		return;
+	};
+
+	for entry in entries.flatten() {
+		let path = entry.path();
+		match entry.metadata() {
+			Ok(meta) if meta.is_dir() => disk_files(&path, files),
+			Ok(meta) => files.push((path, meta.len())),
+			Err(_) => (),
+		}
+	}
+}
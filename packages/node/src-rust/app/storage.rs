@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+/// The storage options along with the backends accepting each of them.
+///
+/// Only the options the datastore reads from the endpoint are listed, so that
+/// tuning which would be silently ignored, such as syncing or caching, is
+/// rejected as an unknown option instead.
+const KEYS: &[(&str, &[&str])] =
+	&[("versioned", &["memory", "surrealkv"]), ("retention", &["memory", "surrealkv"])];
+
+/// The units accepted in a duration, such as `30d` or `1h30m`
+const DURATION_UNITS: &[&str] = &["ns", "us", "µs", "ms", "s", "m", "h", "d", "w", "y"];
+
+/// Options for the storage backend, equivalent to the query parameters of the endpoint
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct StorageOptions {
+	/// Whether to keep previous versions of every record
+	pub versioned: Option<bool>,
+	/// How long previous versions are kept, such as `30d`
+	pub retention: Option<String>,
+}
+
+impl StorageOptions {
+	fn params(&self) -> Vec<(&'static str, String)> {
+		let mut params = Vec::new();
+		if let Some(versioned) = self.versioned {
+			params.push(("versioned", versioned.to_string()));
+		}
+		if let Some(retention) = &self.retention {
+			params.push(("retention", retention.clone()));
+		}
+		params
+	}
+}
+
+/// Builds the endpoint passed to the datastore from the endpoint of a connection
+/// and its storage options.
+///
+/// The query parameters of the endpoint and the storage options are merged, and
+/// every key is checked against the backend of the endpoint, so that an unknown
+/// key, an invalid value or a key the backend does not support is rejected
+/// instead of being ignored.
+pub fn endpoint(endpoint: &str, options: Option<&StorageOptions>) -> Result<String, String> {
+	let (base, query) = match endpoint.split_once('?') {
+		Some((base, query)) => (base, Some(query)),
+		None => (endpoint, None),
+	};
+	let (base, scheme) = match base.split_once(':') {
+		Some(("mem" | "memory", _)) => ("memory", "memory"),
+		Some((scheme, _)) => (base, scheme),
+		None => (base, base),
+	};
+	let backend = match scheme {
+		"file" => "rocksdb",
+		"surrealkv+versioned" => "surrealkv",
+		scheme => scheme,
+	};
+
+	let mut params = BTreeMap::new();
+	for pair in query.into_iter().flat_map(|q| q.split('&')).filter(|p| !p.is_empty()) {
+		let (key, value) = pair.split_once('=').unwrap_or((pair, "true"));
+		if params.insert(key.to_string(), value.to_string()).is_some() {
+			return Err(format!("The storage option {key} is set more than once in the endpoint"));
+		}
+	}
+	for (key, value) in options.map(StorageOptions::params).unwrap_or_default() {
+		match params.get(key) {
+			Some(existing) if *existing != value => {
+				return Err(format!(
+					"The storage option {key} is set to {existing} in the endpoint and {value} in the storage options"
+				));
+			}
+			_ => params.insert(key.to_string(), value),
+		};
+	}
+
+	for (key, value) in &params {
+		let Some((_, backends)) = KEYS.iter().find(|(name, _)| name == key) else {
+			let keys = KEYS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ");
+			return Err(format!("Unknown storage option {key}, expected one of {keys}"));
+		};
+		if !backends.contains(&backend) {
+			return Err(format!(
+				"The storage option {key} is not supported by the {backend} backend"
+			));
+		}
+		check(key, value)?;
+	}
+
+	let versioned = scheme == "surrealkv+versioned"
+		|| params.get("versioned").is_some_and(|versioned| versioned == "true");
+	if params.contains_key("retention") && !versioned {
+		return Err("The storage option retention requires versioned storage".to_string());
+	}
+
+	if params.is_empty() {
+		return Ok(base.to_string());
+	}
+	let query = params.iter().map(|(key, value)| format!("{key}={value}")).collect::<Vec<_>>();
+	Ok(format!("{base}?{}", query.join("&")))
+}
+
+/// Rejects an invalid value for a storage option
+fn check(key: &str, value: &str) -> Result<(), String> {
+	let valid = match key {
+		"versioned" => matches!(value, "true" | "false"),
+		"retention" => is_duration(value),
+		_ => true,
+	};
+
+	match valid {
+		true => Ok(()),
+		false => Err(format!("Invalid value {value} for the storage option {key}")),
+	}
+}
+
+/// Returns whether a value is a duration made of numbers followed by units, such as `1h30m`
+fn is_duration(value: &str) -> bool {
+	let mut rest = value;
+	while !rest.is_empty() {
+		let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+		if digits == 0 {
+			return false;
+		}
+		rest = &rest[digits..];
+		let Some(unit) = DURATION_UNITS
+			.iter()
+			.filter(|unit| rest.starts_with(**unit))
+			.max_by_key(|unit| unit.len())
+		else {
+			return false;
+		};
+		rest = &rest[unit.len()..];
+	}
+	!value.is_empty()
+}
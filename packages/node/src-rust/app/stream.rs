@@ -0,0 +1,177 @@
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use surrealdb_core::rpc::Request;
+use surrealdb_core::sql::Expr;
+use surrealdb_core::sql::TopLevelExpr;
+use surrealdb_types::Array;
+use surrealdb_types::Object;
+use surrealdb_types::ToSql;
+use surrealdb_types::Value;
+
+use super::recording::entry;
+
+/// The number of records sent in each batched chunk when not specified
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// The number of encoded frames buffered ahead of the reader
+pub const STREAM_BUFFER: usize = 16;
+
+/// Splits a streamed request into the requests run one after another.
+///
+/// A query of several statements runs a statement at a time, so that the result
+/// of one statement is sent before the next executes, unless its statements
+/// depend on each other through a transaction, a `LET` parameter, a `USE` or an
+/// `OPTION` statement. The result of a single statement is still produced in full
+/// before it is sent.
+pub fn requests(req: Request) -> Vec<Request> {
+	if req.method.to_str() != "query" {
+		return vec![req];
+	}
+
+	let mut params = req.params.clone().into_vec().into_iter();
+	let Some(statements) = params.next().and_then(|sql| match sql {
+		Value::String(sql) => statements(&sql),
+		_ => None,
+	}) else {
+		return vec![req];
+	};
+
+	let vars = params.next();
+	let split = statements
+		.into_iter()
+		.map(|sql| {
+			let mut entry = entry(&req);
+			let params =
+				std::iter::once(Value::String(sql)).chain(vars.clone()).collect::<Vec<_>>();
+			entry.insert("params".to_string(), Value::Array(Array::from(params)));
+			Request::from_object(entry)
+		})
+		.collect::<Result<Vec<_>, _>>();
+
+	split.unwrap_or_else(|_| vec![req])
+}
+
+/// Parses a query into independent statements, or `None` when it runs as a whole
+fn statements(sql: &str) -> Option<Vec<String>> {
+	let ast = surrealdb_core::syn::parse(sql).ok()?;
+	if ast.expressions.len() < 2 {
+		return None;
+	}
+
+	ast.expressions
+		.iter()
+		.map(|expr| match expr {
+			TopLevelExpr::Expr(Expr::Let(_)) => None,
+			TopLevelExpr::Expr(expr) => Some(expr.to_sql()),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Splits the response to a streamed request into the frames sent for it.
+///
+/// Query results produce frames per statement, numbered from `offset`, with array
+/// results split into batches of `batch_size` records, mirroring the SDK
+/// `QueryChunk` shape. Any other result is sent as a single frame, and an error
+/// envelope as it is. Frames are built as they are read, moving the records of
+/// the response into each batch.
+pub fn frames(
+	response: Result<Value, Value>,
+	is_query: bool,
+	offset: usize,
+	batch_size: usize,
+) -> Frames {
+	let mut frames = Frames {
+		batch_size: batch_size.max(1),
+		query: offset,
+		ready: None,
+		statements: Vec::new().into_iter(),
+		batches: None,
+	};
+
+	match response {
+		Err(envelope) => frames.ready = Some(envelope),
+		Ok(Value::Array(statements)) if is_query => {
+			frames.statements = statements.into_vec().into_iter();
+		}
+		Ok(value) => frames.ready = Some(frame(offset, 0, "single", Object::default(), value)),
+	}
+
+	frames
+}
+
+pub struct Frames {
+	batch_size: usize,
+	/// The index of the statement being sent
+	query: usize,
+	/// A frame sent as it is, before any statement
+	ready: Option<Value>,
+	statements: IntoIter<Value>,
+	/// The remaining records of the statement being sent in batches
+	batches: Option<Batches>,
+}
+
+struct Batches {
+	meta: Object,
+	records: Peekable<IntoIter<Value>>,
+	batch: usize,
+}
+
+impl Iterator for Frames {
+	type Item = Value;
+
+	fn next(&mut self) -> Option<Value> {
+		if let Some(frame) = self.ready.take() {
+			return Some(frame);
+		}
+
+		if let Some(batches) = &mut self.batches {
+			let records: Vec<Value> = batches.records.by_ref().take(self.batch_size).collect();
+			let records = Value::Array(Array::from(records));
+			let batch = batches.batch;
+			batches.batch += 1;
+
+			if batches.records.peek().is_some() {
+				return Some(frame(self.query, batch, "batched", batches.meta.clone(), records));
+			}
+
+			let meta = self.batches.take().map(|batches| batches.meta).unwrap_or_default();
+			self.query += 1;
+			return Some(frame(self.query - 1, batch, "batched-final", meta, records));
+		}
+
+		let query = self.query;
+		let mut meta = match self.statements.next()? {
+			Value::Object(meta) => meta,
+			statement => {
+				self.query += 1;
+				return Some(frame(query, 0, "single", Object::default(), statement));
+			}
+		};
+
+		let is_ok = matches!(meta.get("status"), Some(Value::String(status)) if status == "OK");
+		match meta.remove("result") {
+			Some(Value::Array(records)) if is_ok => {
+				self.batches = Some(Batches {
+					meta,
+					records: records.into_vec().into_iter().peekable(),
+					batch: 0,
+				});
+				self.next()
+			}
+			result => {
+				self.query += 1;
+				Some(frame(query, 0, "single", meta, result.unwrap_or(Value::None)))
+			}
+		}
+	}
+}
+
+fn frame(query: usize, batch: usize, kind: &str, mut meta: Object, result: Value) -> Value {
+	meta.insert("query".to_string(), Value::from_t(query as i64));
+	meta.insert("batch".to_string(), Value::from_t(batch as i64));
+	meta.insert("kind".to_string(), Value::String(kind.to_string()));
+	meta.insert("result".to_string(), result);
+	Value::Object(meta)
+}
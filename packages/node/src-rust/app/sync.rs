@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::future::Future;
+
+use serde::Deserialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_types::Array;
+use surrealdb_types::Object;
+use surrealdb_types::Value;
+use surrealdb_types::Variables;
+
+use super::changes;
+use super::data::query;
+
+/// The namespace holding the sync checkpoints, apart from the synced databases
+const CHECKPOINT_NAMESPACE: &str = "__sync";
+
+/// The database holding the sync checkpoints
+const CHECKPOINT_DATABASE: &str = "__sync";
+
+/// The table holding the sync checkpoint of each peer of each database
+const CHECKPOINT_TABLE: &str = "checkpoint";
+
+/// Reads the checkpoint of a peer
+const LOAD_CHECKPOINT_QUERY: &str = "SELECT * FROM ONLY type::record($table, [$ns, $db, $peer])";
+
+/// Writes the checkpoint of a peer
+const SAVE_CHECKPOINT_QUERY: &str = "UPSERT type::record($table, [$ns, $db, $peer]) \
+	CONTENT { ns: $ns, db: $db, peer: $peer, pushed: $pushed, pulled: $pulled, applied: $applied }";
+
+/// The record field compared by the last writer wins strategy when not specified
+const DEFAULT_TIMESTAMP_FIELD: &str = "updated_at";
+
+#[derive(Deserialize, Default)]
+pub struct SyncOptions {
+	/// The tables to sync, defaulting to the change feed of the whole database
+	pub tables: Option<Vec<String>>,
+	/// How a record changed on both sides since the last sync is resolved
+	pub conflict: Option<ConflictStrategy>,
+	/// The record field compared by the last writer wins strategy
+	pub timestamp_field: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictStrategy {
+	#[default]
+	LastWriterWins,
+	LocalWins,
+	RemoteWins,
+	Custom,
+}
+
+/// The number of changes exchanged with a peer during a sync
+pub struct SyncResult {
+	pub pulled: u64,
+	pub pushed: u64,
+	pub conflicts: u64,
+}
+
+struct Checkpoint {
+	pushed: u64,
+	pulled: u64,
+	/// The versionstamps after `pushed` of the local changes applied from the peer
+	applied: BTreeSet<u64>,
+}
+
+/// Exchanges changes with a peer through the supplied transport.
+///
+/// Local changes since the last push are collapsed into the latest change for
+/// each record, and checked against the remote changes returned by `pull`. The
+/// remote changes which win are applied locally in a single transaction, and the
+/// local changes which win are sent with `push`. The `resolve` callback is only
+/// called by the custom strategy, and returns the merged record or `None` to
+/// delete it. Checkpoints are stored per peer, so that each sync only exchanges
+/// the changes made since the previous one. They are kept in the `__sync`
+/// namespace, so they are neither synced nor exported with the database.
+///
+/// Applying the remote changes writes local changes of its own, which are found
+/// by the records they hold and skipped by the next sync, while local writes made
+/// during the sync are pushed by the next one.
+pub async fn sync<Pull, PullFut, Push, PushFut, Resolve, ResolveFut>(
+	kvs: &Datastore,
+	session: &Session,
+	peer: &str,
+	options: SyncOptions,
+	pull: Pull,
+	push: Push,
+	resolve: Resolve,
+) -> Result<SyncResult, String>
+where
+	Pull: FnOnce(u64) -> PullFut,
+	PullFut: Future<Output = Result<Vec<u8>, String>>,
+	Push: FnOnce(Vec<u8>) -> PushFut,
+	PushFut: Future<Output = Result<(), String>>,
+	Resolve: Fn(Value) -> ResolveFut,
+	ResolveFut: Future<Output = Result<Option<Value>, String>>,
+{
+	let tables = options.tables.as_deref();
+	let strategy = options.conflict.unwrap_or_default();
+	let field = options.timestamp_field.unwrap_or_else(|| DEFAULT_TIMESTAMP_FIELD.to_string());
+	let checkpoint = load_checkpoint(kvs, session, peer).await?;
+
+	// Collect the local changes which have not been pushed yet
+	let (local_sets, local_high) =
+		changes::read(kvs, session, tables, checkpoint.pushed, None).await?;
+	let mut pending = latest_changes(local_sets, &checkpoint.applied);
+
+	// Fetch the remote changes made since the last pull
+	let (remote_sets, remote_high) = changes::decode(&pull(checkpoint.pulled).await?)?;
+	let mut incoming = Vec::new();
+	let mut conflicts = 0;
+
+	for (key, remote) in latest_changes(remote_sets, &BTreeSet::new()) {
+		let Some(local) = pending.get(&key) else {
+			incoming.push(remote);
+			continue;
+		};
+
+		conflicts += 1;
+
+		match strategy {
+			ConflictStrategy::LocalWins => (),
+			ConflictStrategy::LastWriterWins if newer(local, &remote, &field) => (),
+			ConflictStrategy::RemoteWins | ConflictStrategy::LastWriterWins => {
+				pending.remove(&key);
+				incoming.push(remote);
+			}
+			ConflictStrategy::Custom => {
+				let id = change_id(&remote).ok_or("A conflicting change has no record id")?;
+
+				let mut conflict = Object::default();
+				conflict.insert("id".to_string(), id.clone());
+				conflict.insert("local".to_string(), local.clone());
+				conflict.insert("remote".to_string(), remote);
+
+				// The merged change is applied locally and pushed to the peer
+				let merged = match resolve(Value::Object(conflict)).await? {
+					Some(Value::Object(mut record)) => {
+						record.insert("id".to_string(), id);
+						change("update", record)
+					}
+					_ => {
+						let mut record = Object::default();
+						record.insert("id".to_string(), id);
+						change("delete", record)
+					}
+				};
+
+				pending.insert(key, merged.clone());
+				incoming.push(merged);
+			}
+		}
+	}
+
+	let pulled = incoming.len() as u64;
+	let pushed = pending.len() as u64;
+	let mut next = Checkpoint {
+		pushed: local_high.unwrap_or(checkpoint.pushed),
+		pulled: remote_high.unwrap_or(checkpoint.pulled),
+		applied: checkpoint.applied,
+	};
+
+	if !incoming.is_empty() {
+		let records = incoming.iter().filter_map(change_key).collect::<BTreeSet<_>>();
+		changes::replay(kvs, session, vec![change_set(incoming)]).await?;
+
+		// Find the local changes written by applying the remote ones
+		let (sets, _) = changes::read(kvs, session, tables, next.pushed, None).await?;
+		next.applied.extend(applied(sets, &records));
+	}
+	next.applied.retain(|versionstamp| *versionstamp > next.pushed);
+
+	if !pending.is_empty() {
+		let sets = vec![change_set(pending.into_values().collect())];
+		push(changes::encode(checkpoint.pushed, local_high, sets)?).await?;
+	}
+
+	save_checkpoint(kvs, session, peer, &next).await?;
+
+	Ok(SyncResult {
+		pulled,
+		pushed,
+		conflicts,
+	})
+}
+
+/// Collapses change sets into the latest change for each record, keyed by its encoded id.
+///
+/// Sets with a `skipped` versionstamp are left out.
+fn latest_changes(sets: Vec<Value>, skipped: &BTreeSet<u64>) -> BTreeMap<Vec<u8>, Value> {
+	let mut latest = BTreeMap::new();
+
+	for set in sets {
+		if changes::versionstamp(&set).is_some_and(|v| skipped.contains(&v)) {
+			continue;
+		}
+		let Value::Object(mut set) = set else {
+			continue;
+		};
+		let Some(Value::Array(changes)) = set.remove("changes") else {
+			continue;
+		};
+
+		for change in changes.into_vec() {
+			if let Some(key) = change_key(&change) {
+				latest.insert(key, change);
+			}
+		}
+	}
+
+	latest
+}
+
+/// Returns the versionstamps of the change sets which only hold changes to `records`
+fn applied(sets: Vec<Value>, records: &BTreeSet<Vec<u8>>) -> BTreeSet<u64> {
+	let mut written: BTreeMap<u64, bool> = BTreeMap::new();
+
+	for set in sets {
+		let Some(versionstamp) = changes::versionstamp(&set) else {
+			continue;
+		};
+		let Value::Object(mut set) = set else {
+			continue;
+		};
+		let Some(Value::Array(changes)) = set.remove("changes") else {
+			continue;
+		};
+
+		// The changes of a versionstamp may be split across the feeds of several tables
+		let only = written.entry(versionstamp).or_insert(true);
+		for change in changes.into_vec() {
+			*only &= change_key(&change).is_some_and(|key| records.contains(&key));
+		}
+	}
+
+	written.into_iter().filter(|(_, only)| *only).map(|(versionstamp, _)| versionstamp).collect()
+}
+
+/// Returns the encoded record id of a change
+fn change_key(change: &Value) -> Option<Vec<u8>> {
+	cbor::encode(change_id(change)?).ok().map(Vec::from)
+}
+
+/// Returns the record id of an update or delete change
+fn change_id(change: &Value) -> Option<Value> {
+	let Value::Object(change) = change else {
+		return None;
+	};
+
+	match change.get("update").or_else(|| change.get("delete")) {
+		Some(Value::Object(record)) => record.get("id").cloned(),
+		_ => None,
+	}
+}
+
+/// Whether the local change was written after the remote one
+fn newer(local: &Value, remote: &Value, field: &str) -> bool {
+	let stamp = |change: &Value| match change {
+		Value::Object(change) => match change.get("update") {
+			Some(Value::Object(record)) => record.get(field).cloned(),
+			_ => None,
+		},
+		_ => None,
+	};
+
+	match (stamp(local), stamp(remote)) {
+		(Some(l), Some(r)) if l != r => l > r,
+		// Break ties on the encoded changes, so both peers pick the same winner
+		_ => {
+			let encode = |v: &Value| cbor::encode(v.clone()).map(Vec::from).unwrap_or_default();
+			encode(local) > encode(remote)
+		}
+	}
+}
+
+fn change(kind: &str, record: Object) -> Value {
+	let mut change = Object::default();
+	change.insert(kind.to_string(), Value::Object(record));
+	Value::Object(change)
+}
+
+fn change_set(changes: Vec<Value>) -> Value {
+	let mut set = Object::default();
+	set.insert("changes".to_string(), Value::Array(Array::from(changes)));
+	Value::Object(set)
+}
+
+/// Loads the checkpoint of a peer
+async fn load_checkpoint(
+	kvs: &Datastore,
+	session: &Session,
+	peer: &str,
+) -> Result<Checkpoint, String> {
+	let (session, vars) = checkpoint_target(session, peer)?;
+
+	let record = query(kvs, &session, LOAD_CHECKPOINT_QUERY, Some(vars)).await?;
+	let record = match record {
+		Value::Object(record) => record,
+		_ => Object::default(),
+	};
+
+	let read = |name: &str| {
+		record.get(name).and_then(|v| v.clone().into_t::<i64>().ok()).unwrap_or(0) as u64
+	};
+	let applied = match record.get("applied") {
+		Some(Value::Array(applied)) => applied
+			.iter()
+			.filter_map(|v| v.clone().into_t::<i64>().ok())
+			.map(|v| v as u64)
+			.collect(),
+		_ => BTreeSet::new(),
+	};
+
+	Ok(Checkpoint {
+		pushed: read("pushed"),
+		pulled: read("pulled"),
+		applied,
+	})
+}
+
+async fn save_checkpoint(
+	kvs: &Datastore,
+	session: &Session,
+	peer: &str,
+	checkpoint: &Checkpoint,
+) -> Result<(), String> {
+	let (session, mut vars) = checkpoint_target(session, peer)?;
+	vars.insert("pushed".to_string(), Value::from_t(checkpoint.pushed as i64));
+	vars.insert("pulled".to_string(), Value::from_t(checkpoint.pulled as i64));
+	let applied = checkpoint.applied.iter().map(|v| Value::from_t(*v as i64)).collect::<Vec<_>>();
+	vars.insert("applied".to_string(), Value::Array(Array::from(applied)));
+
+	query(kvs, &session, SAVE_CHECKPOINT_QUERY, Some(vars)).await?;
+
+	Ok(())
+}
+
+/// Returns the session and variables addressing the checkpoint of a peer for the
+/// database of a session
+fn checkpoint_target(session: &Session, peer: &str) -> Result<(Session, Variables), String> {
+	let (Some(ns), Some(db)) = (session.ns.as_deref(), session.db.as_deref()) else {
+		return Err("Select a namespace and database to sync".to_string());
+	};
+
+	let mut vars = Variables::new();
+	vars.insert("table".to_string(), Value::String(CHECKPOINT_TABLE.to_string()));
+	vars.insert("ns".to_string(), Value::String(ns.to_string()));
+	vars.insert("db".to_string(), Value::String(db.to_string()));
+	vars.insert("peer".to_string(), Value::String(peer.to_string()));
+
+	let session = Session::owner().with_ns(CHECKPOINT_NAMESPACE).with_db(CHECKPOINT_DATABASE);
+
+	Ok((session, vars))
+}
@@ -0,0 +1,82 @@
+use serde::Serialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::key::record;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::kvs::KVKey;
+use surrealdb_core::kvs::LockType;
+use surrealdb_core::kvs::TransactionType;
+use surrealdb_types::Value;
+use surrealdb_types::Variables;
+
+use super::data::query;
+use super::format::to_json;
+
+/// The largest number of versions listed for a record
+const MAX_VERSIONS: u32 = 10_000;
+
+/// Reads a record as it was at a version, given in nanoseconds since the Unix epoch
+const VERSION_QUERY: &str =
+	"SELECT * FROM ONLY type::record($record) VERSION time::from::nanos($version)";
+
+#[derive(Serialize)]
+pub struct RecordVersion {
+	/// When the version was written, in nanoseconds since the Unix epoch, as a
+	/// string since it exceeds the integers JavaScript numbers hold exactly
+	pub version: String,
+	/// Whether the record was deleted in this version
+	pub deleted: bool,
+	/// The record as it was in this version, or `null` once deleted
+	pub value: serde_json::Value,
+}
+
+/// Lists every version of a record kept by a versioned datastore, from the oldest.
+///
+/// The versions are read from the record key in the session database, and the
+/// record is then read as of each of them, so the values have the same shape as
+/// the result of a `SELECT ... VERSION` query.
+pub async fn list(
+	kvs: &Datastore,
+	session: &Session,
+	record_id: &str,
+) -> Result<Vec<RecordVersion>, String> {
+	let (Some(ns), Some(db)) = (session.ns.as_deref(), session.db.as_deref()) else {
+		return Err("Select a namespace and database to list the versions of a record".to_string());
+	};
+	let id = surrealdb_core::syn::record_id(record_id).map_err(|e| e.to_string())?;
+
+	let tx = kvs
+		.transaction(TransactionType::Read, LockType::Optimistic)
+		.await
+		.map_err(|e| e.to_string())?;
+	let versions = async {
+		let def = tx.expect_db_by_name(ns, db).await?;
+		let key =
+			record::new(def.namespace_id, def.database_id, &id.table, &id.key).encode_key()?;
+		let mut end = key.clone();
+		end.push(0x00);
+		tx.scan_all_versions(key..end, MAX_VERSIONS).await
+	}
+	.await;
+	let _ = tx.cancel().await;
+	let versions = versions.map_err(|e| e.to_string())?;
+
+	let mut records = Vec::with_capacity(versions.len());
+	for (_, _, version, deleted) in versions {
+		let value = match deleted {
+			true => serde_json::Value::Null,
+			false => {
+				let mut vars = Variables::new();
+				vars.insert("record".to_string(), Value::String(record_id.to_string()));
+				vars.insert("version".to_string(), Value::from_t(version as i64));
+				to_json(query(kvs, session, VERSION_QUERY, Some(vars)).await?)
+			}
+		};
+		records.push(RecordVersion {
+			version: version.to_string(),
+			deleted,
+			value,
+		});
+	}
+
+	Ok(records)
+}
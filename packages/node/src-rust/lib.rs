@@ -2,3 +2,6 @@
 
 mod app;
 mod err;
+
+pub use app::intercept::{Before, Interceptor};
+pub use app::SurrealNodeEngine;
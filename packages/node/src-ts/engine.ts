@@ -17,11 +17,99 @@ import {
     UnexpectedConnectionError,
     type Uuid,
 } from "surrealdb";
-import { type ConnectionOptions, type NotificationReceiver, SurrealNodeEngine } from "../napi";
+import {
+    type AuditRecord,
+    type BulkLoadOptions,
+    type BulkLoadProgress,
+    type BulkLoadReport,
+    type ChangesExport,
+    type ChangesExportOptions,
+    type ConnectionOptions,
+    type DatastoreStats,
+    type EngineInspection,
+    type EngineMetrics,
+    type LogRecord,
+    type NotificationReceiver,
+    type RecordVersion,
+    type ReplayReport,
+    type SessionPin,
+    type SlowQuery,
+    type StatsOptions,
+    SurrealNodeEngine,
+    type SyncOptions,
+    type SyncReport,
+    type UpgradeProgress,
+    type UpgradeReport,
+} from "../napi";
 import { wrapSqonError } from "./wrap-sqon-error";
 
 type LiveChannels = Record<string, [LiveMessage]>;
 
+export type BulkLoadFormat = "ndjson" | "csv" | "cbor";
+
+export interface BulkLoadStreamOptions extends BulkLoadOptions {
+    onProgress?: (progress: BulkLoadProgress) => void;
+}
+
+export interface NodeEngineOptions extends ConnectionOptions {
+    /** Receives the tracing records of the embedded engines which pass `log_filter` */
+    onLog?: (record: LogRecord) => void;
+    /** Receives each query exceeding the `slow_queries` threshold */
+    onSlowQuery?: (query: SlowQuery) => void;
+    /** Receives a record of every request, when `audit` is enabled */
+    onAudit?: (record: AuditRecord) => void;
+    /** Run around every request, in order */
+    interceptors?: EngineInterceptor[];
+}
+
+export interface InterceptedRequest {
+    method: string;
+    params: unknown[];
+    /** The session the request runs in, or `undefined` for the default session */
+    session?: Uuid;
+    txn?: Uuid;
+}
+
+export type InterceptorAction = { request: InterceptedRequest } | { response: unknown };
+
+/**
+ * Runs around every request executed by the embedded engine. The response is
+ * either the result of the request or an object holding its `error`.
+ */
+export interface EngineInterceptor {
+    /**
+     * Runs before the request executes, in the order the interceptors are listed.
+     * Return `{ request }` to replace the request, or `{ response }` to respond
+     * without running the remaining interceptors or the request itself.
+     */
+    before?(
+        request: InterceptedRequest,
+    ): InterceptorAction | undefined | Promise<InterceptorAction | undefined>;
+    /**
+     * Runs after the request, in the reverse order, for every interceptor whose
+     * `before` ran. Return `{ response }` to replace the response.
+     */
+    after?(
+        request: InterceptedRequest,
+        response: unknown,
+    ): { response: unknown } | undefined | Promise<{ response: unknown } | undefined>;
+}
+
+export interface SyncConflict {
+    id: RecordId;
+    local: Record<string, unknown>;
+    remote: Record<string, unknown>;
+}
+
+export interface SyncTransport {
+    /** Resolves to a delta file of the peer changes after the given versionstamp */
+    pull(since: string): Promise<Uint8Array>;
+    /** Sends a delta file of the local changes to the peer */
+    push(delta: Uint8Array): Promise<void>;
+    /** Merges a conflicting record, or returns `null` to delete it */
+    resolve?(conflict: SyncConflict): Promise<Record<string, unknown> | null>;
+}
+
 interface LivePayload {
     id: Uuid;
     action: LiveAction;
@@ -29,6 +117,9 @@ interface LivePayload {
     record: RecordId;
 }
 
+/** The engines which are currently open, closed when their datastore is destroyed */
+const openEngines = new Set<NodeEngine>();
+
 /**
  * The engine implementation responsible for communicating with an embedded
  * WebAssembly build of SurrealDB.
@@ -41,10 +132,75 @@ export class NodeEngine extends RpcEngine implements SurrealEngine {
     #active = false;
     #abort: AbortController | undefined;
     #options: ConnectionOptions | undefined;
+    #onLog: ((record: LogRecord) => void) | undefined;
+    #onSlowQuery: ((query: SlowQuery) => void) | undefined;
+    #onAudit: ((record: AuditRecord) => void) | undefined;
+    #interceptors: EngineInterceptor[];
 
-    constructor(context: DriverContext, options?: ConnectionOptions) {
+    constructor(context: DriverContext, options?: NodeEngineOptions) {
         super(context);
-        this.#options = options;
+        const { onLog, onSlowQuery, onAudit, interceptors, ...rest } = options ?? {};
+        this.#options = options ? rest : undefined;
+        this.#onLog = onLog;
+        this.#onSlowQuery = onSlowQuery;
+        this.#onAudit = onAudit;
+        this.#interceptors = interceptors ?? [];
+    }
+
+    /**
+     * Re-executes a log written with the `record` option against a fresh engine,
+     * reporting the requests whose result differs from the recorded one
+     *
+     * @param log The contents of the log
+     * @param endpoint The endpoint of the fresh engine, defaulting to `mem://`
+     * @param options The connection options of the fresh engine
+     */
+    static replay(
+        log: Uint8Array,
+        endpoint?: string,
+        options?: ConnectionOptions,
+    ): Promise<ReplayReport> {
+        return SurrealNodeEngine.replay(log, endpoint, options);
+    }
+
+    /**
+     * Returns whether the datastore at an endpoint was written with an older
+     * storage format, and must be upgraded before it can be connected to
+     *
+     * @param endpoint The endpoint of the datastore, such as `rocksdb://data`
+     */
+    static needsUpgrade(endpoint: string): Promise<boolean> {
+        return SurrealNodeEngine.needsUpgrade(endpoint);
+    }
+
+    /**
+     * Migrates the storage format of a datastore to the latest version. The
+     * datastore must not be open in any engine while it is upgraded.
+     *
+     * @param endpoint The endpoint of the datastore, such as `rocksdb://data`
+     * @param options.target An empty datastore to upgrade a copy into, leaving the original untouched
+     * @param options.onProgress Receives each stage of the upgrade
+     */
+    static upgrade(
+        endpoint: string,
+        options?: { target?: string; onProgress?: (progress: UpgradeProgress) => void },
+    ): Promise<UpgradeReport> {
+        return SurrealNodeEngine.upgrade(endpoint, options?.target, options?.onProgress);
+    }
+
+    /**
+     * Closes every engine connected to a datastore and removes all of its
+     * persisted files. Fails when the path does not hold a datastore, or when
+     * the datastore is still open elsewhere.
+     *
+     * @param endpoint The endpoint of the datastore, such as `rocksdb://data`
+     */
+    static async destroy(endpoint: string): Promise<void> {
+        const url = new URL(endpoint).toString();
+        const engines = [...openEngines].filter((engine) => engine._state?.url.toString() === url);
+        await Promise.all(engines.map((engine) => engine.close()));
+
+        return SurrealNodeEngine.destroy(endpoint);
     }
 
     features = new Set([
@@ -60,10 +216,12 @@ export class NodeEngine extends RpcEngine implements SurrealEngine {
         this.#abort = new AbortController();
         this.#active = true;
         this._state = state;
+        openEngines.add(this);
         this.#initialize(state, this.#abort.signal);
     }
 
     async close(): Promise<void> {
+        openEngines.delete(this);
         this._state = undefined;
         this.#abort?.abort();
         this.#abort = undefined;
@@ -172,14 +330,271 @@ export class NodeEngine extends RpcEngine implements SurrealEngine {
         return new Response(sql);
     }
 
+    /**
+     * Load NDJSON, CSV, or CBOR sequence records into a table, writing them in
+     * batched transactions directly through the embedded datastore.
+     *
+     * @param table The table to load records into
+     * @param format The format of the input data
+     * @param data The input data, either as a single buffer or a stream of chunks
+     * @param options Batching, CSV mapping, and progress reporting options
+     * @returns A report of the loaded rows and any rows which failed
+     */
+    async bulkLoad(
+        table: string,
+        format: BulkLoadFormat,
+        data: Uint8Array | ReadableStream<Uint8Array>,
+        options?: BulkLoadStreamOptions,
+    ): Promise<BulkLoadReport> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        const { onProgress, ...opts } = options ?? {};
+        const loader = await this.#engine.bulkLoad(table, format, opts);
+
+        if (data instanceof ReadableStream) {
+            const reader = data.getReader();
+
+            while (true) {
+                const { done, value } = await reader.read();
+                if (done) break;
+                onProgress?.(await loader.write(value));
+            }
+        } else {
+            onProgress?.(await loader.write(data));
+        }
+
+        return loader.finish();
+    }
+
+    /**
+     * Replaces the log filter of the embedded engines with directives such as
+     * `surrealdb_core::dbs=debug`. The filter is shared by every engine in the process.
+     *
+     * @param directives The filter directives, in the `RUST_LOG` format
+     */
+    setLogFilter(directives: string): void {
+        SurrealNodeEngine.setLogFilter(directives);
+    }
+
+    /**
+     * Sets the function receiving the tracing records of the embedded engines,
+     * or removes it when `null`. The handler is shared by every engine in the process,
+     * and cannot be set when the process has a global tracing subscriber of its own.
+     */
+    setLogHandler(handler: ((record: LogRecord) => void) | null): void {
+        SurrealNodeEngine.setLogHandler(handler);
+        this.#onLog = handler ?? undefined;
+    }
+
+    /**
+     * Returns the request counters, latency histograms and gauges of the embedded
+     * engine. The `prometheus` field holds the same metrics in the Prometheus text
+     * exposition format, ready to be served from an existing metrics endpoint.
+     */
+    async metrics(): Promise<EngineMetrics> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.metrics();
+    }
+
+    /**
+     * Restricts a session to a namespace, and optionally a database. Requests,
+     * `USE` statements and imports selecting anything outside the pin fail with
+     * a permission error. A pin cannot be removed once set, and must be within
+     * the `pin` connection option when one is set.
+     *
+     * @param session The session to restrict, or `undefined` for the default session
+     * @param pin The namespace and database the session is restricted to
+     */
+    async pinSession(session: Uuid | undefined, pin: SessionPin): Promise<void> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.pinSession(session?.toString(), pin);
+    }
+
+    /**
+     * Returns the queries which exceeded the `slow_queries` threshold, from the oldest.
+     * Only the most recent `capacity` queries are kept.
+     */
+    async slowQueries(): Promise<SlowQuery[]> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.slowQueries();
+    }
+
+    /**
+     * Describes the active sessions of the embedded engine with their live queries,
+     * along with every open transaction and how long it has been open.
+     */
+    async inspect(): Promise<EngineInspection> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.inspect();
+    }
+
+    /**
+     * Kills a live query, whichever session started it
+     *
+     * @param id The id of the live query
+     * @returns Whether the live query was found
+     */
+    async killLiveQuery(id: string): Promise<boolean> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.killLiveQuery(id);
+    }
+
+    /**
+     * Cancels an open transaction, discarding its changes
+     *
+     * @param id The id of the transaction
+     * @returns Whether the transaction was found
+     */
+    async cancelTransaction(id: string): Promise<boolean> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.cancelTransaction(id);
+    }
+
+    /**
+     * Lists every version of a record kept by a versioned datastore, from the oldest
+     *
+     * @param record The record id, such as `person:tobie`
+     * @returns Each version, in nanoseconds since the Unix epoch, with the record as it was then
+     */
+    async recordVersions(record: string): Promise<RecordVersion[]> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.recordVersions(record);
+    }
+
+    /**
+     * Measures the embedded datastore, returning key counts and stored sizes per
+     * namespace, database, table and index, along with storage backend details.
+     * Every key of each measured table is read, so measuring the whole datastore
+     * is best kept to debugging.
+     *
+     * @param options The namespace, database and tables to measure, defaulting to all of them
+     */
+    async stats(options?: StatsOptions): Promise<DatastoreStats> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.stats(options);
+    }
+
+    /**
+     * Exports the change feeds after a versionstamp as a replayable delta file
+     *
+     * @param options The tables to read and the versionstamp to start after, as a decimal string
+     * @returns The delta file and the highest versionstamp it contains, as a decimal string
+     */
+    async exportChanges(options?: ChangesExportOptions): Promise<ChangesExport> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.exportChanges(options);
+    }
+
+    /**
+     * Applies a delta file written by `exportChanges` in a single transaction
+     *
+     * @param data The delta file to apply
+     * @returns The highest versionstamp in the delta file, as a decimal string
+     */
+    async importChanges(data: Uint8Array): Promise<string> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.importChanges(data);
+    }
+
+    /**
+     * Syncs the change feeds of the embedded datastore with a peer. Local changes
+     * are pushed and remote changes applied through the supplied transport, using
+     * a checkpoint stored for each peer so only new changes are exchanged.
+     *
+     * @param peer A stable name identifying the peer
+     * @param transport The callbacks used to exchange delta files with the peer
+     * @param options The tables to sync and the conflict resolution strategy
+     * @returns The number of changes pulled, pushed, and in conflict
+     */
+    async sync(
+        peer: string,
+        transport: SyncTransport,
+        options?: SyncOptions,
+    ): Promise<SyncReport> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        const { cbor } = this._context.codecs;
+        const { resolve } = transport;
+
+        return this.#engine.sync(
+            peer,
+            (since) => transport.pull(since),
+            (delta) => transport.push(delta),
+            options,
+            resolve &&
+                (async (conflict) => {
+                    const record = await resolve(cbor.decode<SyncConflict>(conflict));
+                    return record === null ? null : cbor.encode(record);
+                }),
+        );
+    }
+
     async #initialize(state: ConnectionState, signal: AbortSignal) {
         try {
+            if (this.#onLog) {
+                SurrealNodeEngine.setLogHandler(this.#onLog);
+            }
+
             this.#engine = await SurrealNodeEngine.connect(state.url.toString(), this.#options);
 
             if (signal.aborted) {
                 return;
             }
 
+            const { cbor } = this._context.codecs;
+            for (const { before, after } of this.#interceptors) {
+                await this.#engine.addInterceptor(
+                    before &&
+                        (async (request) => {
+                            const action = await before(cbor.decode<InterceptedRequest>(request));
+                            return action === undefined ? null : cbor.encode(action);
+                        }),
+                    after &&
+                        (async (exchange) => {
+                            const { request, response } = cbor.decode<{
+                                request: InterceptedRequest;
+                                response: unknown;
+                            }>(exchange);
+                            const action = await after(request, response);
+                            return action === undefined ? null : cbor.encode(action);
+                        }),
+                );
+            }
+
             this.#notificationReceiver = await this.#engine.notifications();
 
             (async () => {
@@ -205,6 +620,40 @@ export class NodeEngine extends RpcEngine implements SurrealEngine {
                 }
             })();
 
+            const onSlowQuery = this.#onSlowQuery;
+            if (onSlowQuery) {
+                const receiver = await this.#engine.slowQueryReceiver();
+
+                (async () => {
+                    while (!signal.aborted) {
+                        const query = await receiver.recv();
+
+                        if (query === null || signal.aborted) {
+                            break; // Channel closed
+                        }
+
+                        onSlowQuery(query);
+                    }
+                })();
+            }
+
+            const onAudit = this.#onAudit;
+            if (onAudit) {
+                const receiver = await this.#engine.auditReceiver();
+
+                (async () => {
+                    while (!signal.aborted) {
+                        const record = await receiver.recv();
+
+                        if (record === null || signal.aborted) {
+                            break; // Channel closed
+                        }
+
+                        onAudit(record);
+                    }
+                })();
+            }
+
             this.#publisher.publish("connected");
         } catch (err) {
             this.#publisher.publish("error", new UnexpectedConnectionError(err));
@@ -1,6 +1,5 @@
 import type { Engines } from "surrealdb";
-import type { ConnectionOptions } from "../napi";
-import { NodeEngine } from "./engine";
+import { NodeEngine, type NodeEngineOptions } from "./engine";
 
 /**
  * Configure the `mem`, `rocksdb`, `surrealkv`, and `surrealkv+versioned` Nodejs engines for the JavaScript SDK.
@@ -21,7 +20,7 @@ import { NodeEngine } from "./engine";
  * });
  * ```
  */
-export const createNodeEngines = (options?: ConnectionOptions): Engines => ({
+export const createNodeEngines = (options?: NodeEngineOptions): Engines => ({
     mem: (ctx) => new NodeEngine(ctx, options),
     rocksdb: (ctx) => new NodeEngine(ctx, options),
     surrealkv: (ctx) => new NodeEngine(ctx, options),
@@ -1,6 +1,7 @@
 import { afterAll, afterEach, beforeAll, beforeEach } from "bun:test";
 import { escapeIdent } from "surrealdb";
 import { resetIncrementalID } from "../../sdk/src/internal/get-incremental-id";
+import { cleanupEmbedded } from "./integration/__helpers__/embedded";
 import { SURREAL_BACKEND, SURREAL_DB, SURREAL_NS } from "./integration/__helpers__/env";
 import {
     connections,
@@ -40,4 +41,6 @@ afterEach(async () => {
     for (const connection of connections) {
         await connection.close();
     }
+
+    await cleanupEmbedded();
 });
@@ -0,0 +1,177 @@
+import { mkdtemp, rm } from "node:fs/promises";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { CborCodec, type DriverContext, Surreal, Uuid } from "surrealdb";
+import type { NodeEngine, NodeEngineOptions } from "../../../../node/dist/surrealdb-node";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { SURREAL_BACKEND, SURREAL_DB, SURREAL_NS } from "./env";
+import { connections } from "./surreal";
+
+type EngineOptions = Parameters<typeof SurrealNodeEngine.connect>[1];
+
+/** Whether the embedded Node.js engine is under test, which the engine API tests require */
+export const IS_NODE_ENGINE: boolean = SURREAL_BACKEND === "node";
+
+/** Whether the embedded WebAssembly engine is under test */
+export const IS_WASM_ENGINE: boolean = SURREAL_BACKEND === "wasm";
+
+export const cbor: CborCodec = new CborCodec({});
+
+/** The raw engines opened by the current test, freed after each test */
+export const engines: SurrealNodeEngine[] = [];
+
+/** The temporary directories created by the current test, removed after each test */
+export const directories: string[] = [];
+
+/**
+ * Connect a raw embedded engine, with the test namespace and database selected
+ * on its default session.
+ */
+export async function connectEngine(
+    endpoint = "mem://",
+    options?: EngineOptions,
+): Promise<SurrealNodeEngine> {
+    const { SurrealNodeEngine } = await import("../../../../node/napi");
+    const engine = await SurrealNodeEngine.connect(endpoint, options);
+    engines.push(engine);
+
+    await call(engine, "use", [SURREAL_NS, SURREAL_DB]);
+
+    return engine;
+}
+
+/**
+ * Execute a single request against a raw engine, throwing the error of an
+ * error envelope.
+ */
+export async function call<T = unknown>(
+    engine: SurrealNodeEngine,
+    method: string,
+    params?: unknown[],
+    extra?: Record<string, unknown>,
+): Promise<T> {
+    const response = cbor.decode<T | { error: { message: string } }>(
+        await engine.execute(cbor.encode({ id: 1, method, params, ...extra })),
+    );
+
+    if (response && typeof response === "object" && "error" in response) {
+        throw new Error(response.error.message);
+    }
+
+    return response as T;
+}
+
+/**
+ * Attach a new session to a raw engine.
+ */
+export async function attachSession(engine: SurrealNodeEngine): Promise<Uuid> {
+    const session = Uuid.v4();
+    await call(engine, "attach", undefined, { session });
+    return session;
+}
+
+/**
+ * Create a connection through the Node.js engine, returning the engine
+ * instance along with the connection.
+ */
+export async function createEmbeddedSurreal(
+    options?: NodeEngineOptions,
+    endpoint = "mem://",
+): Promise<{ surreal: Surreal; engine: NodeEngine }> {
+    const { NodeEngine } = await import("../../../../node/dist/surrealdb-node");
+    let engine: NodeEngine | undefined;
+
+    const factory = (ctx: DriverContext) => {
+        engine = new NodeEngine(ctx, options);
+        return engine;
+    };
+
+    const surreal = new Surreal({
+        engines: {
+            mem: factory,
+            rocksdb: factory,
+            surrealkv: factory,
+            "surrealkv+versioned": factory,
+        },
+    });
+
+    connections.push(surreal);
+
+    await surreal.connect(endpoint, {
+        namespace: SURREAL_NS,
+        database: SURREAL_DB,
+    });
+
+    return { surreal, engine: engine as NodeEngine };
+}
+
+/**
+ * Create an empty temporary directory for an on-disk datastore.
+ */
+export async function temporaryDirectory(): Promise<string> {
+    const directory = await mkdtemp(join(tmpdir(), "surrealdb-"));
+    directories.push(directory);
+    return directory;
+}
+
+/**
+ * Free the raw engines and remove the temporary directories of the current test.
+ */
+export async function cleanupEmbedded(): Promise<void> {
+    for (const engine of engines.splice(0)) {
+        await engine.free();
+    }
+
+    for (const directory of directories.splice(0)) {
+        await rm(directory, { recursive: true, force: true });
+    }
+}
+
+/** An exclusive Web Locks implementation, as Bun does not provide one */
+class Locks {
+    #queues = new Map<string, Promise<void>>();
+
+    request<T>(
+        name: string,
+        options: { signal?: AbortSignal },
+        callback: () => Promise<T>,
+    ): Promise<T> {
+        const previous = this.#queues.get(name) ?? Promise.resolve();
+        const { promise: released, resolve: release } = Promise.withResolvers<void>();
+        this.#queues.set(name, previous.then(() => released));
+
+        return previous.then(async () => {
+            try {
+                options.signal?.throwIfAborted();
+                return await callback();
+            } finally {
+                release();
+            }
+        });
+    }
+}
+
+/**
+ * Provide the Web Locks API which the leader option of the WebAssembly engine
+ * requires, when the runtime does not.
+ */
+export function provideWebLocks(): void {
+    if (!("locks" in navigator)) {
+        Object.defineProperty(navigator, "locks", { value: new Locks() });
+    }
+}
+
+/**
+ * Open a tab of the WebAssembly memory engine, coordinated with the other tabs
+ * by the leader option over a `BroadcastChannel`.
+ */
+export async function openLeaderTab(): Promise<Surreal> {
+    const { createWasmEngines } = await import("../../../../wasm/dist/surrealdb-wasm");
+    const { indxdb } = createWasmEngines({ leader: true });
+    const surreal = new Surreal({ engines: { mem: indxdb } });
+    connections.push(surreal);
+
+    await surreal.connect("mem://");
+
+    return surreal;
+}
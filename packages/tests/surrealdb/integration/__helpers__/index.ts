@@ -1,4 +1,5 @@
 export * from "./database";
+export * from "./embedded";
 export * from "./env";
 export * from "./helpers";
 export * from "./surreal";
@@ -0,0 +1,68 @@
+import { describe, expect, test } from "bun:test";
+import {
+    attachSession,
+    call,
+    connectEngine,
+    IS_NODE_ENGINE,
+    SURREAL_DB,
+    SURREAL_NS,
+} from "../__helpers__";
+
+describe.if(IS_NODE_ENGINE)("request audit", async () => {
+    test("record of each request", async () => {
+        const engine = await connectEngine("mem://", { audit: {} });
+        const receiver = await engine.auditReceiver();
+        const session = await attachSession(engine);
+        await call(engine, "query", ["RETURN 1"]);
+        await expect(call(engine, "query", ["SELEC"])).rejects.toThrow();
+
+        const records = [];
+        for (let i = 0; i < 4; i++) {
+            records.push(await receiver.recv());
+        }
+
+        expect(records).toEqual([
+            expect.objectContaining({ method: "use", session: null, success: true }),
+            expect.objectContaining({ method: "attach", session: session.toString() }),
+            expect.objectContaining({
+                method: "query",
+                namespace: SURREAL_NS,
+                database: SURREAL_DB,
+                transaction: null,
+                success: true,
+                error: null,
+                dropped: 0,
+            }),
+            expect.objectContaining({ method: "query", success: false, error: expect.any(String) }),
+        ]);
+    });
+
+    test("records within a transaction", async () => {
+        const engine = await connectEngine("mem://", { audit: {} });
+        const receiver = await engine.auditReceiver();
+        await receiver.recv();
+        const txn = await call(engine, "begin");
+        await call(engine, "create", ["person:1"], { txn });
+
+        await receiver.recv();
+        expect(await receiver.recv()).toMatchObject({
+            method: "create",
+            transaction: String(txn),
+        });
+    });
+
+    test("a full buffer drops and counts records", async () => {
+        const engine = await connectEngine("mem://", { audit: { buffer: 2 } });
+        const receiver = await engine.auditReceiver();
+
+        // The buffer already holds the `use` request of the connection
+        for (const value of [1, 2, 3]) {
+            await call(engine, "query", [`RETURN ${value}`]);
+        }
+        expect(await receiver.recv()).toMatchObject({ method: "use", dropped: 0 });
+        expect(await receiver.recv()).toMatchObject({ method: "query", dropped: 0 });
+
+        await call(engine, "query", ["RETURN 4"]);
+        expect(await receiver.recv()).toMatchObject({ method: "query", dropped: 2 });
+    });
+});
@@ -0,0 +1,93 @@
+import { describe, expect, test } from "bun:test";
+import type { Uuid } from "surrealdb";
+import { attachSession, call, cbor, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+function request(method: string, params?: unknown[], extra?: Record<string, unknown>) {
+    return cbor.encode({ id: 1, method, params, ...extra });
+}
+
+describe.if(IS_NODE_ENGINE)("execute batch", async () => {
+    test("responses in request order", async () => {
+        const engine = await connectEngine();
+
+        const responses = await engine.executeBatch(
+            Array.from({ length: 10 }, (_, i) => request("query", [`RETURN ${i}`])),
+        );
+
+        const results = responses.map((response) => cbor.decode<{ result: number }[]>(response));
+        expect(results.map(([statement]) => statement.result)).toEqual(
+            Array.from({ length: 10 }, (_, i) => i),
+        );
+    });
+
+    test("concurrent requests", async () => {
+        const engine = await connectEngine();
+
+        const responses = await engine.executeBatch(
+            Array.from({ length: 10 }, (_, i) => request("create", [`item:${i}`, { i }])),
+            true,
+        );
+
+        expect(responses).toHaveLength(10);
+        const items = await call<unknown[]>(engine, "select", ["item"]);
+        expect(items).toHaveLength(10);
+    });
+
+    test("malformed request fails its own slot", async () => {
+        const engine = await connectEngine();
+
+        const responses = await engine.executeBatch([
+            request("create", ["item:1", { value: 1 }]),
+            new Uint8Array([0xff, 0x00]),
+            cbor.encode(42),
+            request("create", ["item:2", { value: 2 }]),
+        ]);
+
+        const decoded = responses.map((response) => cbor.decode<Record<string, unknown>>(response));
+        expect(decoded[0]).toMatchObject({ value: 1 });
+        expect(decoded[1]).toHaveProperty("error");
+        expect(decoded[2]).toHaveProperty("error");
+        expect(decoded[3]).toMatchObject({ value: 2 });
+    });
+
+    test("failing request does not stop the batch", async () => {
+        const engine = await connectEngine();
+
+        const responses = await engine.executeBatch([
+            request("query", ["THROW 'failed'"]),
+            request("create", ["item:1", { value: 1 }]),
+        ]);
+
+        expect(cbor.decode<Record<string, unknown>>(responses[1])).toMatchObject({ value: 1 });
+    });
+
+    test("requests keep their transaction", async () => {
+        const engine = await connectEngine();
+        const txn = await call<Uuid>(engine, "begin");
+
+        await engine.executeBatch([
+            request("create", ["item:1", { value: 1 }], { txn }),
+            request("create", ["item:2", { value: 2 }], { txn }),
+        ]);
+
+        expect(await call(engine, "select", ["item"])).toHaveLength(0);
+
+        await call(engine, "commit", [txn]);
+
+        expect(await call(engine, "select", ["item"])).toHaveLength(2);
+    });
+
+    test("requests keep their session", async () => {
+        const engine = await connectEngine();
+        const session = await attachSession(engine);
+
+        const responses = await engine.executeBatch([
+            request("use", ["other", "other"], { session }),
+            request("query", ["RETURN session::ns()"], { session }),
+            request("query", ["RETURN session::ns()"]),
+        ]);
+
+        expect(cbor.decode<{ result: string }[]>(responses[1])[0].result).toBe("other");
+        expect(cbor.decode<{ result: string }[]>(responses[2])[0].result).toBe("test");
+    });
+});
@@ -0,0 +1,69 @@
+import { beforeAll, describe, expect, test } from "bun:test";
+import { type LiveMessage, RecordId, Surreal, Table } from "surrealdb";
+import {
+    connections,
+    IS_WASM_ENGINE,
+    openLeaderTab,
+    provideWebLocks,
+    SURREAL_DB,
+    SURREAL_NS,
+} from "../__helpers__";
+
+/** IndexedDB is only shared between the engines of a browser */
+const HAS_INDEXED_DB = "indexedDB" in globalThis;
+
+async function openTab(endpoint: string): Promise<Surreal> {
+    const { createWasmEngines } = await import("../../../../wasm/dist/surrealdb-wasm");
+    const surreal = new Surreal({ engines: createWasmEngines() });
+    connections.push(surreal);
+
+    await surreal.connect(endpoint);
+    await surreal.use({ namespace: SURREAL_NS, database: SURREAL_DB });
+
+    return surreal;
+}
+
+/** Creates a record in one tab, resolving to the notification received by the other */
+async function notify(listener: Surreal, writer: Surreal) {
+    const subscription = await listener.live(new Table("person"));
+    const { promise, resolve } = Promise.withResolvers<LiveMessage>();
+
+    subscription.subscribe(resolve);
+    await writer.create(new RecordId("person", 1)).content({ name: "Tobie" });
+
+    expect(await promise).toMatchObject({
+        action: "CREATE",
+        queryId: subscription.id,
+        value: { name: "Tobie" },
+    });
+}
+
+describe.if(IS_WASM_ENGINE)("live query broadcast", async () => {
+    beforeAll(provideWebLocks);
+
+    test("writes in the leader notify the live queries of a follower", async () => {
+        const leader = await openLeaderTab();
+        const follower = await openLeaderTab();
+        for (const tab of [leader, follower]) {
+            await tab.use({ namespace: SURREAL_NS, database: SURREAL_DB });
+        }
+
+        await notify(follower, leader);
+    });
+
+    test("writes in a follower notify the live queries of the leader", async () => {
+        const leader = await openLeaderTab();
+        const follower = await openLeaderTab();
+        for (const tab of [leader, follower]) {
+            await tab.use({ namespace: SURREAL_NS, database: SURREAL_DB });
+        }
+
+        await notify(leader, follower);
+    });
+
+    test.if(HAS_INDEXED_DB)("writes in another engine notify live queries", async () => {
+        const endpoint = `indxdb://broadcast-${crypto.randomUUID()}`;
+
+        await notify(await openTab(endpoint), await openTab(endpoint));
+    });
+});
@@ -0,0 +1,48 @@
+import { describe, expect, test } from "bun:test";
+import { RecordId, Table } from "surrealdb";
+import { createSurreal, SURREAL_BACKEND } from "../__helpers__";
+
+const isEmbedded = SURREAL_BACKEND !== "remote";
+
+describe.if(isEmbedded)("execute buffers", async () => {
+    test("large request and response", async () => {
+        const surreal = await createSurreal();
+        const payload = "x".repeat(4 * 1024 * 1024);
+
+        await surreal.create(new RecordId("blob", 1)).content({ payload });
+        const record = await surreal.select(new RecordId("blob", 1));
+
+        expect(record?.payload).toBe(payload);
+    });
+
+    test("responses stay intact across concurrent requests", async () => {
+        const surreal = await createSurreal();
+        const table = new Table("item");
+
+        const created = await Promise.all(
+            Array.from({ length: 100 }, (_, i) =>
+                surreal.create(new RecordId("item", i)).content({ value: "y".repeat(i * 100) }),
+            ),
+        );
+
+        expect(created.map((record) => record?.value)).toEqual(
+            Array.from({ length: 100 }, (_, i) => "y".repeat(i * 100)),
+        );
+
+        const selected = await surreal.select(table);
+        expect(selected).toHaveLength(100);
+    });
+
+    test("bytes outlive the response buffer", async () => {
+        const surreal = await createSurreal();
+        const bytes = new Uint8Array(1024).map((_, i) => i % 256);
+
+        await surreal.create(new RecordId("file", 1)).content({ bytes });
+        const record = await surreal.select(new RecordId("file", 1));
+
+        // Later responses may reuse the memory the first response was read from
+        await surreal.create(new RecordId("file", 2)).content({ bytes: new Uint8Array(1024) });
+
+        expect(record?.bytes).toEqual(bytes);
+    });
+});
@@ -0,0 +1,115 @@
+import { describe, expect, test } from "bun:test";
+import { Table } from "surrealdb";
+import { cbor, createEmbeddedSurreal, IS_NODE_ENGINE } from "../__helpers__";
+
+const encoder = new TextEncoder();
+
+function chunked(data: Uint8Array, size: number): ReadableStream<Uint8Array> {
+    return new ReadableStream({
+        start(controller) {
+            for (let i = 0; i < data.length; i += size) {
+                controller.enqueue(data.slice(i, i + size));
+            }
+            controller.close();
+        },
+    });
+}
+
+describe.if(IS_NODE_ENGINE)("bulk load", async () => {
+    test("ndjson records in batches", async () => {
+        const { surreal, engine } = await createEmbeddedSurreal();
+        const lines = Array.from({ length: 25 }, (_, i) => JSON.stringify({ value: i }));
+        const data = chunked(encoder.encode(lines.join("\n")), 7);
+        const progress: number[] = [];
+
+        const report = await engine.bulkLoad("item", "ndjson", data, {
+            batch_size: 10,
+            onProgress: ({ loaded }) => progress.push(loaded),
+        });
+
+        expect(report).toMatchObject({ rows: 25, loaded: 25, failures: [] });
+        expect(progress.at(-1)).toBeLessThan(25);
+        expect(await surreal.select(new Table("item"))).toHaveLength(25);
+    });
+
+    test("csv mapping and coercion", async () => {
+        const { surreal, engine } = await createEmbeddedSurreal();
+        const csv = 'Name,age,score,note\nTobie,30,nan,"line\nbreak"\nJaime,x,1.5,\nshort,1\n';
+
+        const report = await engine.bulkLoad("person", "csv", chunked(encoder.encode(csv), 5), {
+            columns: { Name: "name" },
+            types: { age: "int" },
+        });
+
+        expect(report.rows).toBe(3);
+        expect(report.loaded).toBe(1);
+        expect(report.failures.map(({ row }) => row)).toEqual([2, 3]);
+        expect(report.failures[1].error).toContain("Expected 4 fields, found 2");
+
+        const [person] = await surreal.select<Record<string, unknown>>(new Table("person"));
+        expect(person).toMatchObject({ name: "Tobie", age: 30, score: "nan", note: "line\nbreak" });
+    });
+
+    test("csv empty fields and rows", async () => {
+        const { surreal, engine } = await createEmbeddedSurreal();
+        const csv = "id,name,note\n1,,NULL\n\n2,Jaime,\n";
+
+        const report = await engine.bulkLoad("person", "csv", encoder.encode(csv), {
+            types: { id: "int" },
+            null: "NULL",
+        });
+
+        expect(report.rows).toBe(3);
+        expect(report.loaded).toBe(2);
+        expect(report.failures).toEqual([{ row: 2, error: "Empty row" }]);
+
+        const [people] = await surreal
+            .query("SELECT name, note FROM person ORDER BY name")
+            .collect<[Record<string, unknown>[]]>();
+        expect(people).toEqual([
+            { name: "", note: null },
+            { name: "Jaime", note: "" },
+        ]);
+    });
+
+    test("cbor sequence with a malformed item", async () => {
+        const { surreal, engine } = await createEmbeddedSurreal();
+        const data = new Uint8Array([
+            ...cbor.encode({ value: 1 }),
+            0x1c,
+            ...cbor.encode({ value: 2 }),
+            ...cbor.encode({ value: 3 }),
+        ]);
+
+        const report = await engine.bulkLoad("item", "cbor", chunked(data, 3));
+
+        expect(report.rows).toBe(4);
+        expect(report.loaded).toBe(3);
+        expect(report.failures).toEqual([{ row: 2, error: "Malformed CBOR item" }]);
+        expect(await surreal.select(new Table("item"))).toHaveLength(3);
+    });
+
+    test("incomplete cbor item", async () => {
+        const { engine } = await createEmbeddedSurreal();
+        const item = cbor.encode({ value: 1 });
+
+        const report = await engine.bulkLoad("item", "cbor", item.slice(0, -1));
+
+        expect(report.loaded).toBe(0);
+        expect(report.failures).toEqual([{ row: 1, error: "Incomplete CBOR item" }]);
+    });
+
+    test("rejected records", async () => {
+        const { engine } = await createEmbeddedSurreal();
+        await engine.bulkLoad("item", "ndjson", encoder.encode('{"id": 1}\n'));
+
+        const report = await engine.bulkLoad(
+            "item",
+            "ndjson",
+            encoder.encode('{"id": 1}\n{"id": 2}\n[1]\n'),
+        );
+
+        expect(report.loaded).toBe(1);
+        expect(report.failures.map(({ row }) => row).sort()).toEqual([1, 3]);
+    });
+});
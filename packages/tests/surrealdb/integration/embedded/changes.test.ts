@@ -0,0 +1,104 @@
+import { describe, expect, test } from "bun:test";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { call, cbor, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+interface Delta {
+    since: string;
+    versionstamp: string;
+    changes: { changes: { update?: { id: unknown } }[] }[];
+}
+
+async function define(engine: SurrealNodeEngine, ...tables: string[]) {
+    for (const table of tables) {
+        await call(engine, "query", [`DEFINE TABLE ${table} CHANGEFEED 1h`]);
+    }
+}
+
+/** Exports every change in pages of `limit` change sets, returning the updated ids */
+async function exportAll(engine: SurrealNodeEngine, tables: string[], limit: number) {
+    const updated: string[] = [];
+    let since = "0";
+
+    while (true) {
+        const { data, versionstamp } = await engine.exportChanges({ tables, since, limit });
+        const delta = cbor.decode<Delta>(data);
+        if (delta.changes.length === 0) break;
+
+        for (const set of delta.changes) {
+            for (const change of set.changes) {
+                if (change.update) updated.push(String(change.update.id));
+            }
+        }
+
+        since = versionstamp ?? since;
+    }
+
+    return updated;
+}
+
+describe.if(IS_NODE_ENGINE)("change export and import", async () => {
+    test("limit across tables", async () => {
+        const engine = await connectEngine();
+        await define(engine, "a", "b");
+
+        for (const id of ["a:1", "a:2", "a:3", "b:1", "b:2", "a:4"]) {
+            await call(engine, "create", [id]);
+        }
+
+        expect(await exportAll(engine, ["a", "b"], 2)).toEqual([
+            "a:1",
+            "a:2",
+            "a:3",
+            "b:1",
+            "b:2",
+            "a:4",
+        ]);
+    });
+
+    test("changes of one transaction stay together", async () => {
+        const engine = await connectEngine();
+        await define(engine, "a", "b");
+
+        await call(engine, "query", ["BEGIN; CREATE a:1; CREATE b:1; COMMIT"]);
+        await call(engine, "create", ["a:2"]);
+
+        const { data } = await engine.exportChanges({ tables: ["a", "b"], limit: 1 });
+
+        expect(cbor.decode<Delta>(data).changes).toHaveLength(2);
+        expect((await exportAll(engine, ["a", "b"], 1)).sort()).toEqual(["a:1", "a:2", "b:1"]);
+    });
+
+    test("import into another engine", async () => {
+        const source = await connectEngine();
+        const target = await connectEngine();
+        await define(source, "item");
+
+        await call(source, "create", ["item:1", { value: 1 }]);
+        await call(source, "create", ["item:2", { value: 2 }]);
+        await call(source, "delete", ["item:1"]);
+
+        const { data, versionstamp } = await source.exportChanges({ tables: ["item"] });
+        const applied = await target.importChanges(data);
+
+        expect(applied).toBe(versionstamp ?? "0");
+        expect(await call(target, "select", ["item"])).toEqual([
+            expect.objectContaining({ value: 2 }),
+        ]);
+    });
+
+    test("versionstamps are decimal strings", async () => {
+        const engine = await connectEngine();
+        await define(engine, "item");
+        await call(engine, "create", ["item:1"]);
+
+        const { data, versionstamp } = await engine.exportChanges({ tables: ["item"] });
+        const delta = cbor.decode<Delta>(data);
+
+        expect(versionstamp).toMatch(/^\d+$/);
+        expect(delta).toMatchObject({ since: "0", versionstamp });
+
+        const next = await engine.exportChanges({ tables: ["item"], since: versionstamp ?? "0" });
+        expect(cbor.decode<Delta>(next.data).changes).toEqual([]);
+        await expect(engine.exportChanges({ since: "-1" })).rejects.toThrow("Invalid versionstamp");
+    });
+});
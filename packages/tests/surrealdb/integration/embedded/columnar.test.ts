@@ -0,0 +1,81 @@
+import { describe, expect, test } from "bun:test";
+import { call, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+const decoder = new TextDecoder();
+
+/** An Arrow IPC stream message starts with a continuation marker */
+const ARROW_CONTINUATION = [0xff, 0xff, 0xff, 0xff];
+
+describe.if(IS_NODE_ENGINE)("columnar export", async () => {
+    test("table as arrow in batches", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", [
+            `DEFINE TABLE person SCHEMAFULL;
+            DEFINE FIELD name ON person TYPE string;
+            DEFINE FIELD age ON person TYPE option<int>;
+            FOR $i IN 0..5 { CREATE person CONTENT { name: 'Person ' + <string> $i, age: $i } }`,
+        ]);
+
+        const data = await engine.exportColumnar("arrow", { table: "person", batch_size: 2 });
+
+        expect([...data.slice(0, 4)]).toEqual(ARROW_CONTINUATION);
+        expect(decoder.decode(data)).toContain("name");
+    });
+
+    test("table as parquet", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["FOR $i IN 0..5 { CREATE item CONTENT { value: $i } }"]);
+
+        const data = await engine.exportColumnar("parquet", { table: "item", batch_size: 2 });
+
+        expect(decoder.decode(data.slice(0, 4))).toBe("PAR1");
+        expect(decoder.decode(data.slice(-4))).toBe("PAR1");
+    });
+
+    test("value not fitting its column fails the export", async () => {
+        const engine = await connectEngine();
+        // Field definitions do not apply to records written before them
+        await call(engine, "query", [
+            "CREATE item:1 CONTENT { age: 'old' }; DEFINE FIELD age ON item TYPE int",
+        ]);
+
+        await expect(engine.exportColumnar("arrow", { table: "item" })).rejects.toThrow(
+            'Expected an int in column `age`, found "old"',
+        );
+    });
+
+    test("column types from field definitions", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", [
+            `CREATE a:1 CONTENT { value: 'x' };
+            DEFINE FIELD value ON a TYPE option<int>;
+            CREATE b:1 CONTENT { value: 'x' };
+            DEFINE FIELD value ON b TYPE 1 | 2 | 3;
+            CREATE c:1 CONTENT { tags: 'x', owner: 1 };
+            DEFINE FIELD tags ON c TYPE option<array<string>>;
+            DEFINE FIELD owner ON c TYPE option<record<person>>;`,
+        ]);
+
+        await expect(engine.exportColumnar("arrow", { table: "a" })).rejects.toThrow(
+            "Expected an int in column `value`",
+        );
+        await expect(engine.exportColumnar("arrow", { table: "b" })).rejects.toThrow(
+            "Expected an int in column `value`",
+        );
+
+        // Arrays are written as JSON text and record ids as strings, which any value fits
+        expect(await engine.exportColumnar("arrow", { table: "c" })).toBeInstanceOf(Uint8Array);
+    });
+
+    test("query result", async () => {
+        const engine = await connectEngine();
+
+        const data = await engine.exportColumnar("arrow", {
+            query: "SELECT * FROM [{ id: 1, value: 'a' }, { id: 2, other: true }]",
+        });
+
+        const text = decoder.decode(data);
+        expect(text).toContain("value");
+        expect(text).toContain("other");
+    });
+});
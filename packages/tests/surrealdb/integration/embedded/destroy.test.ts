@@ -0,0 +1,77 @@
+import { describe, expect, test } from "bun:test";
+import { existsSync } from "node:fs";
+import { writeFile } from "node:fs/promises";
+import { join } from "node:path";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import {
+    call,
+    connectEngine,
+    createEmbeddedSurreal,
+    IS_NODE_ENGINE,
+    temporaryDirectory,
+} from "../__helpers__";
+
+/** Loads the engine binding, which exists only when the Node.js engine is under test */
+async function nodeEngine(): Promise<typeof SurrealNodeEngine> {
+    const { SurrealNodeEngine } = await import("../../../../node/napi");
+    return SurrealNodeEngine;
+}
+
+describe.if(IS_NODE_ENGINE)("destroy", async () => {
+    test("removes a closed datastore", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const directory = await temporaryDirectory();
+        const engine = await connectEngine(`rocksdb://${directory}`);
+        await call(engine, "create", ["item:1"]);
+        await engine.free();
+
+        await SurrealNodeEngine.destroy(`rocksdb://${directory}`);
+
+        expect(existsSync(directory)).toBe(false);
+    });
+
+    test("refuses a directory which does not hold a datastore", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const directory = await temporaryDirectory();
+        await writeFile(join(directory, "notes.txt"), "keep me");
+
+        await expect(SurrealNodeEngine.destroy(`rocksdb://${directory}`)).rejects.toThrow(
+            "does not hold a datastore",
+        );
+        await expect(SurrealNodeEngine.destroy(`surrealkv://${directory}`)).rejects.toThrow(
+            "does not hold a datastore",
+        );
+
+        expect(existsSync(join(directory, "notes.txt"))).toBe(true);
+    });
+
+    test("fails while the datastore is open", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const directory = await temporaryDirectory();
+        const engine = await connectEngine(`rocksdb://${directory}`);
+        await call(engine, "create", ["item:1"]);
+
+        await expect(SurrealNodeEngine.destroy(`rocksdb://${directory}`)).rejects.toThrow(
+            "The datastore is still open",
+        );
+
+        expect(await call(engine, "select", ["item:1"])).toMatchObject({ id: expect.anything() });
+    });
+
+    test("closes the engines connected to the datastore", async () => {
+        const { NodeEngine } = await import("../../../../node/dist/surrealdb-node");
+        const directory = await temporaryDirectory();
+        const endpoint = `rocksdb://${directory}`;
+        await createEmbeddedSurreal(undefined, endpoint);
+
+        await NodeEngine.destroy(endpoint);
+
+        expect(existsSync(directory)).toBe(false);
+    });
+
+    test("in-memory datastores have nothing to remove", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+
+        expect(await SurrealNodeEngine.destroy("mem://")).toBeUndefined();
+    });
+});
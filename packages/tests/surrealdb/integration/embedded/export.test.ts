@@ -0,0 +1,93 @@
+import { describe, expect, test } from "bun:test";
+import type { DataExportChunk, SurrealNodeEngine } from "../../../../node/napi";
+import { call, cbor, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+const decoder = new TextDecoder();
+
+async function chunks(
+    engine: SurrealNodeEngine,
+    format: "ndjson" | "json" | "cbor",
+    options?: Record<string, unknown>,
+): Promise<DataExportChunk[]> {
+    const receiver = await engine.exportData(format, options);
+    const chunks = [];
+    for (let chunk = await receiver.recv(); chunk; chunk = await receiver.recv()) {
+        chunks.push(chunk);
+    }
+    return chunks;
+}
+
+function ndjson(chunks: DataExportChunk[]): { id: unknown; value: number }[] {
+    return chunks
+        .flatMap(({ data }) => decoder.decode(data).split("\n"))
+        .filter((line) => line.length > 0)
+        .map((line) => JSON.parse(line));
+}
+
+describe.if(IS_NODE_ENGINE)("data export", async () => {
+    test("pages of ndjson records", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["FOR $i IN 0..25 { CREATE item CONTENT { value: $i } }"]);
+
+        const exported = await chunks(engine, "ndjson", { tables: ["item"], page_size: 10 });
+
+        expect(exported.map(({ table }) => table)).toEqual(["item", "item", "item"]);
+        expect(ndjson(exported)).toHaveLength(25);
+        expect(new Set(ndjson(exported).map(({ id }) => id)).size).toBe(25);
+    });
+
+    test("json array across pages", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["FOR $i IN 0..5 { CREATE item CONTENT { value: $i } }"]);
+
+        const exported = await chunks(engine, "json", { tables: ["item"], page_size: 2 });
+        const text = exported.map(({ data }) => decoder.decode(data)).join("");
+
+        expect(JSON.parse(text)).toHaveLength(5);
+    });
+
+    test("empty table", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["DEFINE TABLE item"]);
+
+        const exported = await chunks(engine, "json", { tables: ["item"] });
+
+        expect(decoder.decode(exported[0].data)).toBe("[]");
+    });
+
+    test("records written during the export", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", [
+            "FOR $i IN 0..200 { CREATE item:[$i] CONTENT { value: $i } }",
+        ]);
+
+        // Pages are read ahead of the receiver up to the buffer size, well short of 120
+        const receiver = await engine.exportData("cbor", { tables: ["item"], page_size: 1 });
+        const first = await receiver.recv();
+
+        // Behind the export, ahead of it, and removed ahead of it
+        await call(engine, "query", [
+            "CREATE item:[-1] CONTENT { value: -1 }; CREATE item:[500] CONTENT { value: 500 }",
+        ]);
+        await call(engine, "query", ["DELETE item:[120]"]);
+
+        const rest = [];
+        for (let chunk = await receiver.recv(); chunk; chunk = await receiver.recv()) {
+            rest.push(chunk);
+        }
+
+        const values = [first, ...rest].flatMap((chunk) =>
+            cborSequence(chunk?.data ?? new Uint8Array()).map(({ value }) => value),
+        );
+
+        expect(values).toEqual([
+            ...Array.from({ length: 200 }, (_, i) => i).filter((i) => i !== 120),
+            500,
+        ]);
+    });
+});
+
+function cborSequence(data: Uint8Array): { value: number }[] {
+    // The items of a CBOR sequence decode as an indefinite-length array
+    return cbor.decode<{ value: number }[]>(new Uint8Array([0x9f, ...data, 0xff]));
+}
@@ -0,0 +1,70 @@
+import { describe, expect, test } from "bun:test";
+import { call, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+function json(request: Record<string, unknown>): Uint8Array {
+    return encoder.encode(JSON.stringify(request));
+}
+
+describe.if(IS_NODE_ENGINE)("execute formats", async () => {
+    test("json request and response", async () => {
+        const engine = await connectEngine();
+
+        const response = await engine.execute(
+            json({ id: 1, method: "query", params: ["RETURN 1 + 1"] }),
+            "json",
+        );
+
+        const [statement] = JSON.parse(decoder.decode(response));
+        expect(statement.status).toBe("OK");
+        expect(statement.result).toBe(2);
+    });
+
+    test("json and cbor requests share the session", async () => {
+        const engine = await connectEngine();
+
+        await call(engine, "create", ["person:tobie", { name: "Tobie" }]);
+        const response = await engine.execute(
+            json({ id: 1, method: "select", params: ["person:tobie"] }),
+            "json",
+        );
+
+        expect(JSON.parse(decoder.decode(response))).toMatchObject({
+            id: "person:tobie",
+            name: "Tobie",
+        });
+    });
+
+    test("json error envelope", async () => {
+        const engine = await connectEngine();
+
+        const response = await engine.execute(json({ id: 1, method: "unknown" }), "json");
+
+        expect(JSON.parse(decoder.decode(response))).toHaveProperty("error");
+    });
+
+    test("json notifications", async () => {
+        const engine = await connectEngine();
+        const notifications = await engine.notifications("json");
+
+        await engine.execute(json({ id: 1, method: "live", params: ["person"] }), "json");
+        await call(engine, "create", ["person:tobie", { name: "Tobie" }]);
+
+        const notification = await notifications.recv();
+        expect(notification).not.toBeNull();
+        expect(JSON.parse(decoder.decode(notification as Uint8Array))).toMatchObject({
+            action: "CREATE",
+            result: { id: "person:tobie", name: "Tobie" },
+        });
+    });
+
+    test("unsupported format", async () => {
+        const engine = await connectEngine();
+
+        await expect(
+            engine.execute(json({ id: 1, method: "ping" }), "xml" as "json"),
+        ).rejects.toThrow("Unsupported format: xml");
+    });
+});
@@ -0,0 +1,69 @@
+import { describe, expect, test } from "bun:test";
+import type { Uuid } from "surrealdb";
+import {
+    attachSession,
+    call,
+    connectEngine,
+    IS_NODE_ENGINE,
+    SURREAL_DB,
+    SURREAL_NS,
+} from "../__helpers__";
+
+describe.if(IS_NODE_ENGINE)("engine inspection", async () => {
+    test("sessions with their live queries", async () => {
+        const engine = await connectEngine();
+        const session = await attachSession(engine);
+        await call(engine, "use", ["other", "other"], { session });
+        const live = await call<Uuid>(engine, "live", ["person"]);
+
+        const { sessions } = await engine.inspect();
+        const byId = (id: string | null) => sessions.find((session) => session.id === id);
+
+        expect(byId(null)).toMatchObject({
+            namespace: SURREAL_NS,
+            database: SURREAL_DB,
+            live_queries: [live.toString()],
+        });
+        expect(byId(session.toString())).toMatchObject({
+            namespace: "other",
+            database: "other",
+            live_queries: [],
+        });
+    });
+
+    test("kill a live query", async () => {
+        const engine = await connectEngine();
+        const live = await call<Uuid>(engine, "live", ["person"]);
+
+        expect(await engine.killLiveQuery(live.toString())).toBeTrue();
+        expect(await engine.killLiveQuery(live.toString())).toBeFalse();
+
+        const { sessions } = await engine.inspect();
+        expect(sessions.flatMap(({ live_queries }) => live_queries)).toEqual([]);
+    });
+
+    test("open transactions", async () => {
+        const engine = await connectEngine();
+        const txn = await call<Uuid>(engine, "begin");
+        await call(engine, "create", ["person:1"], { txn });
+
+        const { transactions } = await engine.inspect();
+
+        expect(transactions).toEqual([
+            { id: txn.toString(), started: expect.any(Number), age: expect.any(Number) },
+        ]);
+        expect(transactions[0].age).toBeGreaterThanOrEqual(0);
+    });
+
+    test("cancel a transaction", async () => {
+        const engine = await connectEngine();
+        const txn = await call<Uuid>(engine, "begin");
+        await call(engine, "create", ["person:1"], { txn });
+
+        expect(await engine.cancelTransaction(txn.toString())).toBeTrue();
+        expect(await engine.cancelTransaction(txn.toString())).toBeFalse();
+
+        expect((await engine.inspect()).transactions).toEqual([]);
+        expect(await call(engine, "select", ["person"])).toEqual([]);
+    });
+});
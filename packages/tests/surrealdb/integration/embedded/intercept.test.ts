@@ -0,0 +1,114 @@
+import { describe, expect, test } from "bun:test";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { call, cbor, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+interface Request {
+    method: string;
+    params: unknown[];
+}
+
+interface Interceptor {
+    before?: (request: Request) => object | undefined;
+    after?: (request: Request, response: unknown) => object | undefined;
+}
+
+/** Adds an interceptor exchanging decoded values, as the `interceptors` option does */
+async function intercept(engine: SurrealNodeEngine, { before, after }: Interceptor) {
+    await engine.addInterceptor(
+        before &&
+            (async (request) => {
+                const action = before(cbor.decode<Request>(request));
+                return action === undefined ? null : cbor.encode(action);
+            }),
+        after &&
+            (async (exchange) => {
+                const { request, response } = cbor.decode<{ request: Request; response: unknown }>(
+                    exchange,
+                );
+                const action = after(request, response);
+                return action === undefined ? null : cbor.encode(action);
+            }),
+    );
+}
+
+describe.if(IS_NODE_ENGINE)("interceptors", async () => {
+    test("run in order before and in reverse after", async () => {
+        const engine = await connectEngine();
+        const calls: string[] = [];
+        for (const name of ["a", "b"]) {
+            await intercept(engine, {
+                before: ({ method }) => void calls.push(`before ${name} ${method}`),
+                after: ({ method }) => void calls.push(`after ${name} ${method}`),
+            });
+        }
+
+        await call(engine, "query", ["RETURN 1"]);
+
+        expect(calls).toEqual([
+            "before a query",
+            "before b query",
+            "after b query",
+            "after a query",
+        ]);
+    });
+
+    test("replace the request", async () => {
+        const engine = await connectEngine();
+        await intercept(engine, {
+            before: (request) => ({ request: { ...request, params: ["RETURN 2"] } }),
+        });
+
+        const [{ result }] = await call<{ result: unknown }[]>(engine, "query", ["RETURN 1"]);
+
+        expect(result).toBe(2);
+    });
+
+    test("respond without running the request", async () => {
+        const engine = await connectEngine();
+        const calls: string[] = [];
+        await intercept(engine, { before: () => ({ response: "cached" }) });
+        await intercept(engine, {
+            before: () => void calls.push("before"),
+            after: () => void calls.push("after"),
+        });
+
+        expect(await call(engine, "query", ["CREATE person:1"])).toBe("cached");
+        expect(calls).toEqual([]);
+    });
+
+    test("replace the response of a failed request", async () => {
+        const engine = await connectEngine();
+        const responses: unknown[] = [];
+        await intercept(engine, {
+            after: (_, response) => {
+                responses.push(response);
+                return { response: "recovered" };
+            },
+        });
+
+        expect(await call(engine, "query", ["SELEC"])).toBe("recovered");
+        expect(responses).toEqual([
+            { error: expect.objectContaining({ message: expect.any(String) }) },
+        ]);
+    });
+
+    test("a response replaced with an error stays an error", async () => {
+        const engine = await connectEngine();
+        await intercept(engine, {
+            after: () => ({ response: { error: { code: -32000, message: "Denied" } } }),
+        });
+
+        await expect(call(engine, "query", ["RETURN 1"])).rejects.toThrow("Denied");
+    });
+
+    test("a failing interceptor fails the request", async () => {
+        const engine = await connectEngine();
+        await intercept(engine, {
+            before: () => {
+                throw new Error("Not today");
+            },
+        });
+
+        await expect(call(engine, "query", ["RETURN 1"])).rejects.toThrow("Not today");
+    });
+});
@@ -0,0 +1,58 @@
+import { beforeAll, describe, expect, test } from "bun:test";
+import { RecordId, Table } from "surrealdb";
+import { IS_WASM_ENGINE, openLeaderTab, provideWebLocks } from "../__helpers__";
+
+describe.if(IS_WASM_ENGINE)("leader", async () => {
+    beforeAll(provideWebLocks);
+
+    test("each tab has its own session", async () => {
+        const leader = await openLeaderTab();
+        const follower = await openLeaderTab();
+
+        await leader.use({ namespace: "leader", database: "test" });
+        await follower.use({ namespace: "follower", database: "test" });
+
+        const [namespace] = await leader.query("RETURN session::ns()").collect<[string]>();
+        expect(namespace).toBe("leader");
+        expect(await follower.query("RETURN session::ns()").collect()).toEqual(["follower"]);
+    });
+
+    test("requests wait for the next leader", async () => {
+        const leader = await openLeaderTab();
+        const follower = await openLeaderTab();
+
+        await leader.close();
+
+        expect(await follower.query("RETURN 1 + 1").collect()).toEqual([2]);
+    });
+
+    test("sessions are restored in the next leader", async () => {
+        const leader = await openLeaderTab();
+        const follower = await openLeaderTab();
+        await follower.use({ namespace: "follower", database: "test" });
+        await follower.set("name", "Tobie");
+
+        await leader.close();
+
+        const [namespace, name] = await follower
+            .query("RETURN session::ns(); RETURN $name")
+            .collect<[string, string]>();
+        expect([namespace, name]).toEqual(["follower", "Tobie"]);
+
+        await follower.create(new RecordId("item", 1)).content({ name });
+        expect(await follower.select(new Table("item"))).toEqual([
+            { id: new RecordId("item", 1), name: "Tobie" },
+        ]);
+    });
+
+    test("requests made while the leader closes", async () => {
+        const leader = await openLeaderTab();
+        const follower = await openLeaderTab();
+
+        const closing = leader.close();
+        const result = follower.query("RETURN 1 + 1").collect();
+        await closing;
+
+        expect(await result).toEqual([2]);
+    });
+});
@@ -0,0 +1,71 @@
+import { afterEach, describe, expect, test } from "bun:test";
+import type { LogRecord } from "../../../../node/napi";
+import { call, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+async function engineClass() {
+    const { SurrealNodeEngine } = await import("../../../../node/napi");
+    return SurrealNodeEngine;
+}
+
+/** Collects the log records forwarded to the handler */
+async function collect(): Promise<LogRecord[]> {
+    const records: LogRecord[] = [];
+    (await engineClass()).setLogHandler((record) => records.push(record));
+    return records;
+}
+
+/** Records are forwarded asynchronously, so wait for them to arrive */
+async function settle(): Promise<void> {
+    await Bun.sleep(100);
+}
+
+describe.if(IS_NODE_ENGINE)("logging", async () => {
+    afterEach(async () => {
+        const SurrealNodeEngine = await engineClass();
+        SurrealNodeEngine.setLogHandler(null);
+        SurrealNodeEngine.setLogFilter("off");
+    });
+
+    test("records passing the filter", async () => {
+        const records = await collect();
+        (await engineClass()).setLogFilter("surrealdb_core=trace");
+
+        const engine = await connectEngine();
+        await call(engine, "query", ["RETURN 1"]);
+        await settle();
+
+        expect(records.length).toBeGreaterThan(0);
+        for (const record of records) {
+            expect(record.target).toStartWith("surrealdb_core");
+            expect(["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]).toContain(record.level);
+            expect(record.spans).toBeArray();
+        }
+    });
+
+    test("records below the filter level are not forwarded", async () => {
+        const records = await collect();
+        (await engineClass()).setLogFilter("surrealdb_core=error");
+
+        const engine = await connectEngine();
+        await call(engine, "query", ["RETURN 1"]);
+        await settle();
+
+        expect(records.filter(({ level }) => level !== "ERROR")).toEqual([]);
+    });
+
+    test("filter from the connection options", async () => {
+        const records = await collect();
+
+        const engine = await connectEngine("mem://", { log_filter: "surrealdb_core=trace" });
+        await call(engine, "query", ["RETURN 1"]);
+        await settle();
+
+        expect(records.length).toBeGreaterThan(0);
+    });
+
+    test("invalid filter", async () => {
+        const SurrealNodeEngine = await engineClass();
+
+        expect(() => SurrealNodeEngine.setLogFilter("surrealdb_core=loud")).toThrow();
+    });
+});
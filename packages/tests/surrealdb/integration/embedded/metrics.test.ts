@@ -0,0 +1,51 @@
+import { describe, expect, test } from "bun:test";
+import { attachSession, call, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+describe.if(IS_NODE_ENGINE)("engine metrics", async () => {
+    test("calls and errors per method", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["RETURN 1"]);
+        await call(engine, "query", ["RETURN 2"]);
+        await expect(call(engine, "query", ["SELEC"])).rejects.toThrow();
+
+        const { methods } = await engine.metrics();
+        const query = methods.find(({ method }) => method === "query");
+
+        expect(query).toMatchObject({ calls: 3, errors: 1 });
+        expect(query?.duration).toBeGreaterThan(0);
+
+        const counts = query?.buckets.map(({ count }) => count) ?? [];
+        expect(counts).toEqual(counts.toSorted((a, b) => a - b));
+        expect(counts.at(-1)).toBeLessThanOrEqual(3);
+    });
+
+    test("gauges", async () => {
+        const engine = await connectEngine();
+        await attachSession(engine);
+        await call(engine, "live", ["person"]);
+        await call(engine, "begin");
+
+        expect(await engine.metrics()).toMatchObject({
+            sessions: 2,
+            live_queries: 1,
+            transactions: 1,
+        });
+    });
+
+    test("prometheus exposition", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["RETURN 1"]);
+        await call(engine, "begin");
+
+        const lines = (await engine.metrics()).prometheus.split("\n");
+
+        expect(lines).toContain('surrealdb_requests_total{method="query"} 1');
+        expect(lines).toContain('surrealdb_request_errors_total{method="query"} 0');
+        expect(lines).toContain('surrealdb_request_duration_seconds_count{method="query"} 1');
+        expect(lines).toContain(
+            'surrealdb_request_duration_seconds_bucket{method="query",le="+Inf"} 1',
+        );
+        expect(lines).toContain("# TYPE surrealdb_transactions gauge");
+        expect(lines).toContain("surrealdb_transactions 1");
+    });
+});
@@ -0,0 +1,112 @@
+import { describe, expect, test } from "bun:test";
+import {
+    attachSession,
+    call,
+    connectEngine,
+    IS_NODE_ENGINE,
+    SURREAL_DB,
+    SURREAL_NS,
+} from "../__helpers__";
+
+const pin = { namespace: SURREAL_NS, database: SURREAL_DB };
+
+describe.if(IS_NODE_ENGINE)("session pinning", async () => {
+    test("selections outside the pin are rejected", async () => {
+        const engine = await connectEngine("mem://", { pin });
+
+        await expect(call(engine, "use", ["other", SURREAL_DB])).rejects.toThrow(
+            `The session is pinned to namespace ${SURREAL_NS}`,
+        );
+        await expect(
+            call(engine, "signin", [{ ns: "other", user: "root", pass: "root" }]),
+        ).rejects.toThrow(`The session is pinned to namespace ${SURREAL_NS}`);
+        expect(await call(engine, "query", [`USE DB ${SURREAL_DB}; RETURN 1`])).toHaveLength(2);
+    });
+
+    test("a query leaving the pin is rejected before it runs", async () => {
+        const engine = await connectEngine("mem://", { pin });
+
+        await expect(
+            call(engine, "query", ["CREATE item:1; USE NS other; CREATE item:2"]),
+        ).rejects.toThrow(`The session is pinned to namespace ${SURREAL_NS}`);
+        await expect(call(engine, "query", ["CREATE item:1; USE NS ⟨other⟩"])).rejects.toThrow(
+            "pinned",
+        );
+
+        expect(await call(engine, "select", ["item"])).toEqual([]);
+    });
+
+    test("statements above the pinned database are rejected", async () => {
+        const engine = await connectEngine("mem://", { pin });
+        await call(engine, "query", ["CREATE item:1"]);
+
+        for (const sql of [
+            `REMOVE NAMESPACE ${SURREAL_NS}`,
+            "INFO FOR ROOT",
+            "INFO FOR NS",
+            "DEFINE DATABASE other",
+            "DEFINE USER admin ON ROOT PASSWORD 'admin' ROLES OWNER",
+        ]) {
+            await expect(call(engine, "query", [sql])).rejects.toThrow("pinned");
+        }
+
+        expect(await call(engine, "query", ["INFO FOR DB"])).toHaveLength(1);
+        expect(await call(engine, "select", ["item"])).toHaveLength(1);
+    });
+
+    test("namespace statements are allowed in a namespace pin", async () => {
+        const engine = await connectEngine("mem://", { pin: { namespace: SURREAL_NS } });
+
+        await expect(call(engine, "query", ["REMOVE NAMESPACE other"])).rejects.toThrow("pinned");
+        await expect(call(engine, "query", ["INFO FOR ROOT"])).rejects.toThrow("pinned");
+        expect(await call(engine, "query", ["DEFINE DATABASE other; INFO FOR NS"])).toHaveLength(2);
+    });
+
+    test("statements mentioning USE are not mistaken for selections", async () => {
+        const engine = await connectEngine("mem://", { pin });
+
+        const [result] = await call<{ result: unknown }[]>(engine, "query", [
+            "-- USE NS other\nRETURN 'USE NS other'",
+        ]);
+
+        expect(result.result).toBe("USE NS other");
+    });
+
+    test("pinned sessions", async () => {
+        const engine = await connectEngine();
+        const session = await attachSession(engine);
+        await engine.pinSession(session.toString(), { namespace: SURREAL_NS });
+
+        await expect(call(engine, "use", ["other", SURREAL_DB], { session })).rejects.toThrow(
+            "pinned",
+        );
+        expect(await call(engine, "use", [SURREAL_NS, "other"], { session })).toBeNull();
+
+        // A pin cannot be widened
+        await expect(engine.pinSession(session.toString(), { namespace: "other" })).rejects.toThrow(
+            "pinned",
+        );
+    });
+
+    test("imports and columnar exports", async () => {
+        const engine = await connectEngine("mem://", { pin });
+
+        await expect(engine.import("USE NS other; CREATE item:1;")).rejects.toThrow("pinned");
+        await expect(
+            engine.exportColumnar("arrow", { query: "USE NS other; SELECT * FROM item" }),
+        ).rejects.toThrow("pinned");
+        expect(await call(engine, "select", ["item"])).toEqual([]);
+    });
+
+    test("stats only cover the pin", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["CREATE item:1; USE NS other DB other; CREATE item:1"]);
+        await call(engine, "use", [SURREAL_NS, SURREAL_DB]);
+
+        await engine.pinSession(null, pin);
+        const stats = await engine.stats();
+
+        expect(Object.keys(stats.namespaces)).toEqual([SURREAL_NS]);
+        expect(Object.keys(stats.namespaces[SURREAL_NS].databases)).toEqual([SURREAL_DB]);
+    });
+});
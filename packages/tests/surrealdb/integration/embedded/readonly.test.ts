@@ -0,0 +1,104 @@
+import { describe, expect, test } from "bun:test";
+import { RecordId, Surreal } from "surrealdb";
+import {
+    call,
+    connectEngine,
+    connections,
+    IS_NODE_ENGINE,
+    IS_WASM_ENGINE,
+    SURREAL_DB,
+    SURREAL_NS,
+    temporaryDirectory,
+} from "../__helpers__";
+
+/** IndexedDB is only shared between the engines of a browser */
+const HAS_INDEXED_DB = "indexedDB" in globalThis;
+
+describe.if(IS_NODE_ENGINE)("read-only engine", async () => {
+    test("rejects the methods which write", async () => {
+        const engine = await connectEngine("mem://", { read_only: true });
+
+        for (const [method, params] of [
+            ["create", ["item:2"]],
+            ["update", ["item:1", { value: 2 }]],
+            ["delete", ["item:1"]],
+            ["live", ["item"]],
+        ] as const) {
+            await expect(call(engine, method, [...params])).rejects.toThrow(
+                `The engine is read-only and cannot run the ${method} method`,
+            );
+        }
+    });
+
+    test("queries which write fail without writing", async () => {
+        const engine = await connectEngine("mem://", { read_only: true });
+
+        const results = await call<{ status: string }[]>(engine, "query", [
+            "CREATE item:1 SET value = 1",
+        ]);
+
+        expect(results[0].status).toBe("ERR");
+    });
+
+    test("rejects imports, loads and syncs", async () => {
+        const engine = await connectEngine("mem://", { read_only: true });
+        const denied = "The engine is read-only";
+
+        await expect(engine.import("CREATE item:2;")).rejects.toThrow(denied);
+        await expect(engine.bulkLoad("item", "ndjson")).rejects.toThrow(denied);
+        await expect(engine.importChanges(new Uint8Array())).rejects.toThrow(denied);
+        await expect(
+            engine.sync("peer", async () => new Uint8Array(), async () => {}),
+        ).rejects.toThrow(denied);
+        await expect(
+            engine.exportColumnar("arrow", { query: "SELECT * FROM item" }),
+        ).rejects.toThrow(denied);
+    });
+
+    test("a store in use by another engine is neither locked nor written", async () => {
+        for (const backend of ["rocksdb", "surrealkv"]) {
+            const endpoint = `${backend}://${await temporaryDirectory()}`;
+            const writer = await connectEngine(endpoint);
+            await call(writer, "create", ["item:1", { value: 1 }]);
+
+            await expect(connectEngine(endpoint, { read_only: true })).rejects.toThrow(
+                `The ${backend} backend cannot be opened read-only`,
+            );
+
+            // The writer keeps its lock, and the store is unchanged
+            await call(writer, "create", ["item:2", { value: 2 }]);
+            expect(await call(writer, "select", ["item:1"])).toMatchObject({ value: 1 });
+            expect(await call(writer, "select", ["item:2"])).toMatchObject({ value: 2 });
+        }
+    });
+});
+
+describe.if(IS_WASM_ENGINE && HAS_INDEXED_DB)("read-only engine in a browser", async () => {
+    async function openTab(endpoint: string, read_only: boolean): Promise<Surreal> {
+        const { createWasmEngines } = await import("../../../../wasm/dist/surrealdb-wasm");
+        const surreal = new Surreal({ engines: createWasmEngines({ read_only }) });
+        connections.push(surreal);
+
+        await surreal.connect(endpoint);
+        await surreal.use({ namespace: SURREAL_NS, database: SURREAL_DB });
+
+        return surreal;
+    }
+
+    test("reads a store in use by another engine", async () => {
+        const endpoint = `indxdb://readonly-${crypto.randomUUID()}`;
+        const writer = await openTab(endpoint, false);
+        await writer.create(new RecordId("item", 1)).content({ value: 1 });
+
+        const reader = await openTab(endpoint, true);
+        expect(await reader.select(new RecordId("item", 1))).toMatchObject({ value: 1 });
+        await expect(
+            reader.create(new RecordId("item", 2)).content({ value: 2 }),
+        ).rejects.toThrow("The engine is read-only");
+
+        // The writer keeps writing, and the reader sees its changes
+        await writer.create(new RecordId("item", 3)).content({ value: 3 });
+        expect(await reader.select(new RecordId("item", 3))).toMatchObject({ value: 3 });
+        expect(await writer.select(new RecordId("item", 2))).toBeUndefined();
+    });
+});
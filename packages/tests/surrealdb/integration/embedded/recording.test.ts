@@ -0,0 +1,105 @@
+import { describe, expect, test } from "bun:test";
+import { readFile } from "node:fs/promises";
+import { join } from "node:path";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import {
+    call,
+    cbor,
+    connectEngine,
+    IS_NODE_ENGINE,
+    SURREAL_DB,
+    SURREAL_NS,
+    temporaryDirectory,
+} from "../__helpers__";
+
+async function recordingEngine(): Promise<[SurrealNodeEngine, string]> {
+    const log = join(await temporaryDirectory(), "requests.log");
+    return [await connectEngine("mem://", { record: log }), log];
+}
+
+/** Decodes the entries of a log, each prefixed with its length */
+async function entries(log: string): Promise<Record<string, unknown>[]> {
+    const data = await readFile(log);
+    const decoded = [];
+    for (let offset = 0; offset < data.length; ) {
+        const end = offset + 4 + data.readUInt32BE(offset);
+        decoded.push(cbor.decode<Record<string, unknown>>(data.subarray(offset + 4, end)));
+        offset = end;
+    }
+    return decoded;
+}
+
+/** Frees the engine, so every entry is written, and replays its log */
+async function replay(engine: SurrealNodeEngine, log: string) {
+    const { SurrealNodeEngine } = await import("../../../../node/napi");
+    await engine.free();
+    return SurrealNodeEngine.replay(await readFile(log));
+}
+
+describe.if(IS_NODE_ENGINE)("record and replay", async () => {
+    test("replay without divergences", async () => {
+        const [engine, log] = await recordingEngine();
+        await call(engine, "query", ["CREATE person:1 CONTENT { name: 'Tobie' }"]);
+        const txn = await call(engine, "begin");
+        await call(engine, "create", ["person:2"], { txn });
+        await call(engine, "commit", [txn]);
+        await call(engine, "live", ["person"]);
+        await call(engine, "select", ["person"]);
+
+        // The `use` request of the connection is recorded as well
+        expect(await replay(engine, log)).toEqual({ requests: 7, skipped: 0, divergences: [] });
+    });
+
+    test("streamed queries are recorded per statement", async () => {
+        const [engine, log] = await recordingEngine();
+        const request = cbor.encode({
+            id: 1,
+            method: "query",
+            params: ["CREATE person:1; SELECT * FROM person"],
+        });
+
+        const receiver = await engine.executeStream(request);
+        while (await receiver.recv());
+
+        expect(await replay(engine, log)).toEqual({ requests: 3, skipped: 0, divergences: [] });
+    });
+
+    test("nondeterministic results diverge", async () => {
+        const [engine, log] = await recordingEngine();
+        await call(engine, "query", ["RETURN 1"]);
+        // Only the ids of `live`, `begin` and `attach` are mapped to the replayed ones
+        await call(engine, "run", ["rand::uuid"]);
+
+        const report = await replay(engine, log);
+
+        expect(report.requests).toBe(3);
+        expect(report.divergences).toEqual([
+            expect.objectContaining({ index: 2, method: "run" }),
+        ]);
+    });
+
+    test("credentials are redacted", async () => {
+        const [engine, log] = await recordingEngine();
+        await call(engine, "query", [
+            "DEFINE USER tobie ON DATABASE PASSWORD 'secret' ROLES OWNER",
+        ]);
+        const { access } = await call<{ access: string }>(engine, "signin", [
+            { ns: SURREAL_NS, db: SURREAL_DB, user: "tobie", pass: "secret" },
+        ]);
+        await call(engine, "authenticate", [access]);
+        await engine.free();
+
+        const [signin, authenticate] = (await entries(log)).slice(2);
+        expect(signin).toMatchObject({
+            method: "signin",
+            params: [{ ns: SURREAL_NS, db: SURREAL_DB, user: "tobie", pass: "[REDACTED]" }],
+            result: "[REDACTED]",
+            redacted: true,
+        });
+        expect(authenticate).toMatchObject({ params: ["[REDACTED]"], redacted: true });
+
+        const report = await replay(engine, log);
+
+        expect(report).toMatchObject({ requests: 2, skipped: 2 });
+    });
+});
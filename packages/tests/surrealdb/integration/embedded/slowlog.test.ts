@@ -0,0 +1,70 @@
+import { describe, expect, test } from "bun:test";
+import { call, connectEngine, IS_NODE_ENGINE, SURREAL_DB, SURREAL_NS } from "../__helpers__";
+
+describe.if(IS_NODE_ENGINE)("slow query log", async () => {
+    test("queries above the threshold", async () => {
+        const engine = await connectEngine("mem://", { slow_queries: { threshold: 0 } });
+        await call(engine, "query", ["RETURN $value", { value: 1 }]);
+        await call(engine, "select", ["person"]);
+
+        expect(await engine.slowQueries()).toEqual([
+            expect.objectContaining({
+                query: "RETURN $value",
+                namespace: SURREAL_NS,
+                database: SURREAL_DB,
+                params: { value: 1 },
+                failed: false,
+                explain: null,
+                dropped: 0,
+            }),
+        ]);
+    });
+
+    test("threshold not reached", async () => {
+        const engine = await connectEngine("mem://", { slow_queries: { threshold: 60000 } });
+        await call(engine, "query", ["RETURN 1"]);
+
+        expect(await engine.slowQueries()).toEqual([]);
+    });
+
+    test("capacity keeps the most recent queries", async () => {
+        const engine = await connectEngine("mem://", {
+            slow_queries: { threshold: 0, capacity: 2 },
+        });
+        for (const value of [1, 2, 3]) {
+            await call(engine, "query", [`RETURN ${value}`]);
+        }
+
+        const queries = await engine.slowQueries();
+        expect(queries.map(({ query }) => query)).toEqual(["RETURN 2", "RETURN 3"]);
+    });
+
+    test("redacted parameters and plans", async () => {
+        const engine = await connectEngine("mem://", {
+            slow_queries: { threshold: 0, redact_params: true, explain: true },
+        });
+        await call(engine, "query", ["SELECT * FROM person WHERE age > $age", { age: 30 }]);
+
+        const [query] = await engine.slowQueries();
+        expect(query.params).toEqual({ age: "[redacted]" });
+        expect(query.explain).not.toBeNull();
+    });
+
+    test("subscriber falling behind drops and counts queries", async () => {
+        const engine = await connectEngine("mem://", {
+            slow_queries: { threshold: 0, buffer: 1 },
+        });
+        const receiver = await engine.slowQueryReceiver();
+
+        for (const value of [1, 2, 3]) {
+            await call(engine, "query", [`RETURN ${value}`]);
+        }
+        expect(await receiver.recv()).toMatchObject({ query: "RETURN 1", dropped: 0 });
+
+        await call(engine, "query", ["RETURN 4"]);
+        expect(await receiver.recv()).toMatchObject({ query: "RETURN 4", dropped: 2 });
+
+        // The log itself keeps every query
+        expect(await engine.slowQueries()).toHaveLength(4);
+    });
+});
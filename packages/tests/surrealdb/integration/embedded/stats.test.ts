@@ -0,0 +1,82 @@
+import { describe, expect, test } from "bun:test";
+import {
+    call,
+    connectEngine,
+    IS_NODE_ENGINE,
+    SURREAL_DB,
+    SURREAL_NS,
+    temporaryDirectory,
+} from "../__helpers__";
+
+describe.if(IS_NODE_ENGINE)("datastore stats", async () => {
+    test("records and index entries per table", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", [
+            `DEFINE INDEX by_name ON person FIELDS name;
+            DEFINE INDEX by_email ON person FIELDS email UNIQUE;
+            FOR $i IN 0..10 {
+                CREATE person CONTENT { name: 'Person ' + <string> $i, email: <string> $i }
+            };
+            CREATE item:1 CONTENT { text: string::repeat('x', 10000) };`,
+        ]);
+
+        const stats = await engine.stats();
+        const database = stats.namespaces[SURREAL_NS].databases[SURREAL_DB];
+        const { person, item } = database.tables;
+
+        expect(person.records.keys).toBe(10);
+        expect(Object.keys(person.indexes).sort()).toEqual(["by_email", "by_name"]);
+        expect(person.indexes.by_name.keys).toBeGreaterThanOrEqual(10);
+        expect(person.indexes.by_email.keys).toBeGreaterThanOrEqual(10);
+        expect(person.keys).toBeGreaterThanOrEqual(
+            person.records.keys + person.indexes.by_name.keys + person.indexes.by_email.keys,
+        );
+
+        // Sizes are those of the stored keys and values
+        expect(item.records).toMatchObject({ keys: 1 });
+        expect(item.records.bytes).toBeGreaterThan(10000);
+
+        const tables = Object.values(database.tables);
+        expect(database.keys).toBe(tables.reduce((keys, table) => keys + table.keys, 0));
+        expect(database.bytes).toBe(tables.reduce((bytes, table) => bytes + table.bytes, 0));
+        expect(stats.backend).toEqual({ kind: "memory" });
+    });
+
+    test("index without entries", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", [
+            "DEFINE TABLE person; DEFINE INDEX by_name ON person FIELDS name",
+        ]);
+
+        const stats = await engine.stats();
+        const { person } = stats.namespaces[SURREAL_NS].databases[SURREAL_DB].tables;
+
+        expect(person.records).toEqual({ keys: 0, bytes: 0 });
+        expect(person.indexes.by_name).toEqual({ keys: 0, bytes: 0 });
+    });
+
+    test("only the selected tables", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["CREATE person:1; CREATE item:1; CREATE item:2"]);
+
+        const stats = await engine.stats({ tables: ["item"] });
+        const database = stats.namespaces[SURREAL_NS].databases[SURREAL_DB];
+
+        expect(Object.keys(database.tables)).toEqual(["item"]);
+        expect(database.tables.item.records.keys).toBe(2);
+        expect(await engine.stats({ namespace: "other" })).toMatchObject({ namespaces: {} });
+    });
+
+    test("rocksdb memtables", async () => {
+        const directory = await temporaryDirectory();
+        const engine = await connectEngine(`rocksdb://${directory}`);
+        await call(engine, "create", ["item:1", { text: "x".repeat(1000) }]);
+
+        const { backend } = await engine.stats();
+
+        expect(backend).toMatchObject({ kind: "rocksdb", path: directory });
+        if (backend.kind !== "rocksdb") return;
+        expect(backend.memtable_bytes).toBeGreaterThan(1000);
+        expect(backend.memtable_limit).toBeGreaterThan(0);
+    });
+});
@@ -0,0 +1,70 @@
+import { describe, expect, test } from "bun:test";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { call, connectEngine, IS_NODE_ENGINE, temporaryDirectory } from "../__helpers__";
+
+/** Updates a record, resolving to its value as of before the update */
+async function previousValue(engine: SurrealNodeEngine): Promise<unknown> {
+    await call(engine, "create", ["item:1", { value: 1 }]);
+    const [{ result: before }] = await call<{ result: unknown }[]>(engine, "query", [
+        "RETURN time::now()",
+    ]);
+    await call(engine, "update", ["item:1", { value: 2 }]);
+
+    const [{ result }] = await call<{ result: unknown }[]>(engine, "query", [
+        "SELECT VALUE value FROM item:1 VERSION $before",
+        { before },
+    ]);
+    return result;
+}
+
+describe.if(IS_NODE_ENGINE)("storage options", async () => {
+    test("versioned storage keeps previous versions", async () => {
+        const engine = await connectEngine("mem://", { storage: { versioned: true } });
+
+        expect(await previousValue(engine)).toEqual([1]);
+    });
+
+    test("on-disk backends honour their options", async () => {
+        const surrealkv = `surrealkv://${await temporaryDirectory()}`;
+        const engine = await connectEngine(surrealkv, {
+            storage: { versioned: true, retention: "1h" },
+        });
+
+        expect(await previousValue(engine)).toEqual([1]);
+    });
+
+    test("options the backends would ignore are rejected", async () => {
+        const rocksdb = `rocksdb://${await temporaryDirectory()}`;
+
+        for (const key of ["sync=every", "cache_size=1048576", "compression=lz4"]) {
+            await expect(connectEngine(`${rocksdb}?${key}`)).rejects.toThrow(
+                "Unknown storage option",
+            );
+        }
+        // @ts-expect-error sync is not a storage option
+        await expect(connectEngine(rocksdb, { storage: { sync: "every" } })).rejects.toThrow(
+            "sync",
+        );
+    });
+
+    test("invalid options fail the connection", async () => {
+        for (const [endpoint, storage, error] of [
+            ["mem://?colour=blue", undefined, "Unknown storage option colour"],
+            ["rocksdb://data?versioned=true", undefined, "not supported by the rocksdb backend"],
+            ["mem://?retention=1h", undefined, "retention requires versioned storage"],
+            ["mem://?versioned=yes", undefined, "Invalid value yes for the storage option"],
+            ["mem://?versioned=true", { versioned: false }, "is set to true in the endpoint"],
+            ["mem://?versioned=true&versioned=true", undefined, "set more than once"],
+        ] as const) {
+            await expect(connectEngine(endpoint, { storage })).rejects.toThrow(error);
+        }
+    });
+
+    test("options set in both places must agree", async () => {
+        const engine = await connectEngine("mem://?versioned=true", {
+            storage: { versioned: true, retention: "30d" },
+        });
+
+        expect(await call(engine, "query", ["RETURN 1"])).toHaveLength(1);
+    });
+});
@@ -0,0 +1,114 @@
+import { describe, expect, test } from "bun:test";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { call, cbor, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+interface Frame {
+    query: number;
+    batch: number;
+    kind: "single" | "batched" | "batched-final";
+    status?: string;
+    result: unknown;
+}
+
+async function stream(
+    engine: SurrealNodeEngine,
+    method: string,
+    params?: unknown[],
+    batchSize?: number,
+): Promise<(Frame | { error: unknown })[]> {
+    const receiver = await engine.executeStream(cbor.encode({ id: 1, method, params }), batchSize);
+
+    const frames = [];
+    for (let chunk = await receiver.recv(); chunk; chunk = await receiver.recv()) {
+        frames.push(cbor.decode<Frame | { error: unknown }>(chunk));
+    }
+
+    return frames;
+}
+
+describe.if(IS_NODE_ENGINE)("execute stream", async () => {
+    test("array results in batches", async () => {
+        const engine = await connectEngine();
+        await call(engine, "query", ["CREATE |item:25|"]);
+
+        const frames = (await stream(engine, "query", ["SELECT * FROM item"], 10)) as Frame[];
+
+        expect(frames.map(({ kind, batch }) => [kind, batch])).toEqual([
+            ["batched", 0],
+            ["batched", 1],
+            ["batched-final", 2],
+        ]);
+        expect(frames.map(({ result }) => (result as unknown[]).length)).toEqual([10, 10, 5]);
+        expect(frames.every(({ status }) => status === "OK")).toBe(true);
+    });
+
+    test("frames per statement", async () => {
+        const engine = await connectEngine();
+
+        const frames = (await stream(engine, "query", [
+            "CREATE item:1; RETURN 'value'; THROW 'failed'; SELECT * FROM item",
+        ])) as Frame[];
+
+        expect(frames.map(({ query, kind }) => [query, kind])).toEqual([
+            [0, "batched-final"],
+            [1, "single"],
+            [2, "single"],
+            [3, "batched-final"],
+        ]);
+        expect(frames[1].result).toBe("value");
+        expect(frames[2].status).toBe("ERR");
+        expect(frames[3].result).toHaveLength(1);
+    });
+
+    test("statements depending on each other", async () => {
+        const engine = await connectEngine();
+
+        const frames = (await stream(engine, "query", [
+            "LET $value = 42; RETURN $value",
+        ])) as Frame[];
+
+        expect(frames.map(({ query }) => query)).toEqual([0, 1]);
+        expect(frames[1].result).toBe(42);
+    });
+
+    test("transaction statements", async () => {
+        const engine = await connectEngine();
+
+        const frames = (await stream(engine, "query", [
+            "BEGIN; CREATE item:1; CANCEL; SELECT * FROM item",
+        ])) as Frame[];
+
+        expect(frames.at(-1)?.result).toEqual([]);
+    });
+
+    test("query variables", async () => {
+        const engine = await connectEngine();
+
+        const frames = (await stream(engine, "query", [
+            "RETURN $a; RETURN $b",
+            { a: 1, b: 2 },
+        ])) as Frame[];
+
+        expect(frames.map(({ result }) => result)).toEqual([1, 2]);
+    });
+
+    test("error envelope ends the stream", async () => {
+        const engine = await connectEngine();
+
+        const frames = await stream(engine, "query", ["SELECT * FROM"]);
+
+        expect(frames).toHaveLength(1);
+        expect(frames[0]).toHaveProperty("error");
+    });
+
+    test("result holding an error field", async () => {
+        const engine = await connectEngine();
+        await call(engine, "create", ["item:1", { error: "not an error" }]);
+
+        const frames = (await stream(engine, "select", ["item:1"])) as Frame[];
+
+        expect(frames).toHaveLength(1);
+        expect(frames[0].kind).toBe("single");
+        expect(frames[0].result).toMatchObject({ error: "not an error" });
+    });
+});
@@ -0,0 +1,121 @@
+import { describe, expect, test } from "bun:test";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { call, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+async function engines(): Promise<[SurrealNodeEngine, SurrealNodeEngine]> {
+    const local = await connectEngine();
+    const remote = await connectEngine();
+    for (const engine of [local, remote]) {
+        await call(engine, "query", ["DEFINE TABLE item CHANGEFEED 1h"]);
+    }
+    return [local, remote];
+}
+
+interface During {
+    /** Runs once the local changes have been read */
+    pull?: () => Promise<unknown>;
+    /** Runs once the remote changes have been read */
+    push?: () => Promise<unknown>;
+}
+
+function sync(
+    local: SurrealNodeEngine,
+    remote: SurrealNodeEngine,
+    options?: Record<string, unknown>,
+    during: During = {},
+) {
+    return local.sync(
+        "remote",
+        async (since) => {
+            await during.pull?.();
+            const { data } = await remote.exportChanges({ tables: ["item"], since });
+            return data;
+        },
+        async (delta) => {
+            await during.push?.();
+            await remote.importChanges(delta);
+        },
+        { tables: ["item"], ...options },
+    );
+}
+
+async function ids(engine: SurrealNodeEngine): Promise<string[]> {
+    const records = await call<{ id: unknown }[]>(engine, "select", ["item"]);
+    return records.map(({ id }) => String(id)).sort();
+}
+
+describe.if(IS_NODE_ENGINE)("sync", async () => {
+    test("changes in both directions", async () => {
+        const [local, remote] = await engines();
+        await call(local, "create", ["item:local"]);
+        await call(remote, "create", ["item:remote"]);
+
+        const report = await sync(local, remote);
+
+        expect(report).toEqual({ pulled: 1, pushed: 1, conflicts: 0 });
+        expect(await ids(local)).toEqual(["item:local", "item:remote"]);
+        expect(await ids(remote)).toEqual(["item:local", "item:remote"]);
+    });
+
+    test("local writes during the sync are pushed by the next one", async () => {
+        const [local, remote] = await engines();
+        await call(local, "create", ["item:local"]);
+        await call(remote, "create", ["item:remote"]);
+
+        await sync(local, remote, {}, { pull: () => call(local, "create", ["item:during"]) });
+        expect(await ids(remote)).toEqual(["item:local", "item:remote"]);
+
+        // The pulled record is not pushed back to the peer
+        const report = await sync(local, remote);
+
+        expect(report.pushed).toBe(1);
+        expect(await ids(remote)).toEqual(["item:during", "item:local", "item:remote"]);
+    });
+
+    test("remote writes during the sync are pulled by the next one", async () => {
+        const [local, remote] = await engines();
+        await call(local, "create", ["item:local"]);
+        await call(remote, "create", ["item:remote"]);
+
+        await sync(local, remote, {}, { push: () => call(remote, "create", ["item:during"]) });
+        expect(await ids(local)).toEqual(["item:local", "item:remote"]);
+
+        await sync(local, remote);
+
+        expect(await ids(local)).toEqual(["item:during", "item:local", "item:remote"]);
+    });
+
+    test("checkpoints are kept outside the database", async () => {
+        const [local, remote] = await engines();
+        await call(local, "create", ["item:local"]);
+
+        await sync(local, remote);
+        const report = await sync(local, remote);
+
+        expect(report).toEqual({ pulled: 0, pushed: 0, conflicts: 0 });
+        const [{ result }] = await call<{ result: { tables: Record<string, unknown> } }[]>(
+            local,
+            "query",
+            ["INFO FOR DB"],
+        );
+        expect(Object.keys(result.tables)).toEqual(["item"]);
+    });
+
+    test("conflict strategies", async () => {
+        for (const [conflict, winner] of [
+            ["local-wins", "local"],
+            ["remote-wins", "remote"],
+        ]) {
+            const [local, remote] = await engines();
+            await call(local, "create", ["item:1", { side: "local" }]);
+            await call(remote, "create", ["item:1", { side: "remote" }]);
+
+            const report = await sync(local, remote, { conflict });
+
+            expect(report.conflicts).toBe(1);
+            for (const engine of [local, remote]) {
+                expect(await call(engine, "select", ["item:1"])).toMatchObject({ side: winner });
+            }
+        }
+    });
+});
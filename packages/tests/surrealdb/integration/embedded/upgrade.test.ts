@@ -0,0 +1,79 @@
+import { describe, expect, test } from "bun:test";
+import type { SurrealNodeEngine } from "../../../../node/napi";
+import { call, connectEngine, IS_NODE_ENGINE, temporaryDirectory } from "../__helpers__";
+
+/** Creates a datastore on disk holding a few records, closed once written */
+async function datastore(): Promise<string> {
+    const endpoint = `rocksdb://${await temporaryDirectory()}`;
+    const engine = await connectEngine(endpoint);
+    await call(engine, "query", ["FOR $i IN 0..5 { CREATE item CONTENT { value: $i } }"]);
+    await engine.free();
+    return endpoint;
+}
+
+/** Loads the engine binding, which exists only when the Node.js engine is under test */
+async function nodeEngine(): Promise<typeof SurrealNodeEngine> {
+    const { SurrealNodeEngine } = await import("../../../../node/napi");
+    return SurrealNodeEngine;
+}
+
+describe.if(IS_NODE_ENGINE)("storage upgrade", async () => {
+    test("a current datastore needs no upgrade", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const endpoint = await datastore();
+
+        expect(await SurrealNodeEngine.needsUpgrade(endpoint)).toBe(false);
+    });
+
+    test("upgrade in place", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const endpoint = await datastore();
+        const stages: string[] = [];
+
+        const report = await SurrealNodeEngine.upgrade(endpoint, null, ({ stage }) => {
+            stages.push(stage);
+        });
+
+        expect(report.copied).toBe(0);
+        expect(report.to).toBe(report.from);
+        // Progress is delivered to the callback without blocking the upgrade
+        await Bun.sleep(10);
+        expect(stages).toEqual(["complete"]);
+
+        const engine = await connectEngine(endpoint);
+        expect(await call(engine, "select", ["item"])).toHaveLength(5);
+    });
+
+    test("upgrade a copy into another datastore", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const endpoint = await datastore();
+        const target = `rocksdb://${await temporaryDirectory()}`;
+        const progress: { stage: string; copied: number }[] = [];
+
+        const report = await SurrealNodeEngine.upgrade(endpoint, target, (stage) => {
+            progress.push(stage);
+        });
+
+        expect(report.copied).toBeGreaterThan(5);
+        // Progress is delivered to the callback without blocking the upgrade
+        await Bun.sleep(10);
+        expect(progress.at(-1)).toMatchObject({ stage: "complete", copied: report.copied });
+        expect(progress.filter(({ stage }) => stage === "copying").length).toBeGreaterThan(0);
+
+        const engine = await connectEngine(target);
+        expect(await call(engine, "select", ["item"])).toHaveLength(5);
+    });
+
+    test("the target must be another, empty datastore", async () => {
+        const SurrealNodeEngine = await nodeEngine();
+        const endpoint = await datastore();
+        const target = await datastore();
+
+        await expect(SurrealNodeEngine.upgrade(endpoint, endpoint)).rejects.toThrow(
+            "The target of an upgrade must differ from the datastore",
+        );
+        await expect(SurrealNodeEngine.upgrade(endpoint, target)).rejects.toThrow(
+            "The datastore to upgrade into is not empty",
+        );
+    });
+});
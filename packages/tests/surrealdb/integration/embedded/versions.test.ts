@@ -0,0 +1,61 @@
+import { describe, expect, test } from "bun:test";
+import { call, connectEngine, IS_NODE_ENGINE } from "../__helpers__";
+
+const decoder = new TextDecoder();
+
+/** Connects an in-memory engine keeping previous versions of every record */
+function versionedEngine() {
+    return connectEngine("mem://", { storage: { versioned: true } });
+}
+
+/** Returns the current time, after letting the clock move past the last write */
+async function now(): Promise<string> {
+    await Bun.sleep(5);
+    const time = new Date().toISOString();
+    await Bun.sleep(5);
+    return time;
+}
+
+describe.if(IS_NODE_ENGINE)("record versions", async () => {
+    test("every version of a record from the oldest", async () => {
+        const engine = await versionedEngine();
+        await call(engine, "create", ["item:1", { value: 1 }]);
+        await call(engine, "update", ["item:1", { value: 2 }]);
+        await call(engine, "delete", ["item:1"]);
+
+        const versions = await engine.recordVersions("item:1");
+
+        expect(versions.map(({ deleted }) => deleted)).toEqual([false, false, true]);
+        expect(versions[0].value).toMatchObject({ value: 1 });
+        expect(versions[1].value).toMatchObject({ value: 2 });
+        expect(versions[2].value).toBeNull();
+
+        const stamps = versions.map(({ version }) => BigInt(version));
+        expect(stamps).toEqual([...stamps].sort((a, b) => (a < b ? -1 : 1)));
+    });
+
+    test("a record which was never written has no versions", async () => {
+        const engine = await versionedEngine();
+
+        expect(await engine.recordVersions("item:missing")).toEqual([]);
+        await expect(engine.recordVersions("not a record")).rejects.toThrow();
+    });
+
+    test("export as of a point in time", async () => {
+        const engine = await versionedEngine();
+        await call(engine, "create", ["item:1", { value: 1 }]);
+        const asOf = await now();
+        await call(engine, "update", ["item:1", { value: 2 }]);
+        await call(engine, "create", ["item:2", { value: 3 }]);
+
+        const receiver = await engine.exportData("ndjson", { tables: ["item"], as_of: asOf });
+        const lines = [];
+        for (let chunk = await receiver.recv(); chunk; chunk = await receiver.recv()) {
+            lines.push(...decoder.decode(chunk.data).split("\n").filter(Boolean));
+        }
+
+        expect(lines.map((line) => JSON.parse(line))).toEqual([
+            expect.objectContaining({ value: 1 }),
+        ]);
+    });
+});
@@ -0,0 +1,73 @@
+use surrealdb_types::Value;
+use uuid::Uuid;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::Uint8Array;
+use web_sys::{BroadcastChannel, MessageEvent};
+
+/// Returns the name of the channel shared by every engine opening an endpoint.
+///
+/// Only IndexedDB stores are shared between tabs and workers, so other
+/// endpoints have no channel.
+pub fn channel_name(endpoint: &str) -> Option<String> {
+	endpoint.starts_with("indxdb:").then(|| format!("surrealdb:live:{endpoint}"))
+}
+
+/// Forwards live query notifications between engines sharing an IndexedDB store.
+///
+/// Live query definitions are stored in the shared store, so a write made in
+/// one tab produces notifications for the live queries of every tab, but only
+/// on the local notification channel of the writing engine. Notifications for
+/// live queries owned by another engine are posted to a `BroadcastChannel`,
+/// and each engine delivers the received notifications which it owns.
+pub struct Broadcast {
+	channel: BroadcastChannel,
+	_onmessage: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl Broadcast {
+	/// Joins a channel, returning a receiver of the CBOR encoded notifications posted to it
+	pub fn open(name: &str) -> Result<(Self, channel::Receiver<Vec<u8>>), JsValue> {
+		let channel = BroadcastChannel::new(name)?;
+		let (tx, rx) = channel::unbounded();
+
+		let onmessage = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+			if let Ok(data) = event.data().dyn_into::<Uint8Array>() {
+				let _ = tx.try_send(data.to_vec());
+			}
+		});
+		channel.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+
+		Ok((
+			Broadcast {
+				channel,
+				_onmessage: onmessage,
+			},
+			rx,
+		))
+	}
+
+	/// Posts a CBOR encoded notification to the other engines on the channel
+	pub fn post(&self, data: &[u8]) {
+		let data: Uint8Array = data.into();
+		let _ = self.channel.post_message(&data);
+	}
+}
+
+impl Drop for Broadcast {
+	fn drop(&mut self) {
+		self.channel.set_onmessage(None);
+		self.channel.close();
+	}
+}
+
+/// Returns the live query id of a notification
+pub fn live_query_id(notification: &Value) -> Option<Uuid> {
+	match notification {
+		Value::Object(notification) => match notification.get("id") {
+			Some(Value::Uuid(id)) => Some((*id).into_inner()),
+			_ => None,
+		},
+		_ => None,
+	}
+}
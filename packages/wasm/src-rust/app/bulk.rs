@@ -0,0 +1,635 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+use serde::Deserialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_core::rpc::format::json;
+use surrealdb_types::Array;
+use surrealdb_types::Object;
+use surrealdb_types::Value;
+use surrealdb_types::Variables;
+
+/// The number of records written in each transaction when not specified
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+const INSERT_QUERY: &str = "INSERT INTO type::table($table) $rows RETURN NONE";
+
+/// The deepest nesting of arrays, maps and tags accepted in a CBOR item
+const MAX_CBOR_DEPTH: usize = 128;
+
+#[derive(Deserialize, Default)]
+pub struct BulkLoadOptions {
+	/// The number of records written in each transaction
+	pub batch_size: Option<u32>,
+	/// The CSV field delimiter, defaulting to a comma
+	pub delimiter: Option<char>,
+	/// Maps CSV header names to record field names
+	pub columns: Option<BTreeMap<String, String>>,
+	/// The type each CSV field is coerced into, keyed by record field name
+	pub types: Option<BTreeMap<String, Coercion>>,
+	/// The CSV field text loaded as `null`, such as `NULL` or an empty string.
+	/// Without it, every field is loaded as text, including empty fields.
+	pub null: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Coercion {
+	#[default]
+	Auto,
+	String,
+	Int,
+	Float,
+	Bool,
+	Json,
+}
+
+#[derive(Clone, Copy)]
+pub enum Input {
+	Ndjson,
+	Csv,
+	Cbor,
+}
+
+impl Input {
+	pub fn parse(input: &str) -> Result<Self, String> {
+		match input {
+			"ndjson" => Ok(Input::Ndjson),
+			"csv" => Ok(Input::Csv),
+			"cbor" => Ok(Input::Cbor),
+			other => Err(format!("Unsupported bulk load format: {other}")),
+		}
+	}
+}
+
+/// A record which could not be parsed or written
+pub struct Failure {
+	pub row: u64,
+	pub error: String,
+}
+
+/// Parses records from chunks of input and writes them to a table in batches
+pub struct BulkLoader {
+	table: String,
+	input: Input,
+	batch_size: usize,
+	delimiter: u8,
+	columns: BTreeMap<String, String>,
+	types: BTreeMap<String, Coercion>,
+	null: Option<String>,
+	buffer: Vec<u8>,
+	header: Option<Vec<String>>,
+	pending: Vec<(u64, Value)>,
+	/// The number of records read from the input
+	pub rows: u64,
+	/// The number of records written to the table
+	pub loaded: u64,
+	/// The records which could not be parsed or written
+	pub failures: Vec<Failure>,
+}
+
+impl BulkLoader {
+	pub fn new(table: String, input: &str, options: BulkLoadOptions) -> Result<Self, String> {
+		let delimiter = match options.delimiter.unwrap_or(',') {
+			c if c.is_ascii() => c as u8,
+			c => return Err(format!("Unsupported CSV delimiter: {c}")),
+		};
+
+		Ok(BulkLoader {
+			table,
+			input: Input::parse(input)?,
+			batch_size: options.batch_size.map_or(DEFAULT_BATCH_SIZE, |b| b.max(1) as usize),
+			delimiter,
+			columns: options.columns.unwrap_or_default(),
+			types: options.types.unwrap_or_default(),
+			null: options.null,
+			buffer: Vec::new(),
+			header: None,
+			pending: Vec::new(),
+			rows: 0,
+			loaded: 0,
+			failures: Vec::new(),
+		})
+	}
+
+	/// Parses every complete record in the chunk, writing any full batches
+	pub async fn write(
+		&mut self,
+		kvs: &Datastore,
+		session: &Session,
+		chunk: &[u8],
+	) -> Result<(), String> {
+		self.buffer.extend_from_slice(chunk);
+		self.parse(false);
+
+		while self.pending.len() >= self.batch_size {
+			let batch = self.pending.drain(..self.batch_size).collect();
+			self.load(kvs, session, batch).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Parses any trailing input and writes the remaining records
+	pub async fn finish(&mut self, kvs: &Datastore, session: &Session) -> Result<(), String> {
+		self.parse(true);
+
+		let batch = mem::take(&mut self.pending);
+		if !batch.is_empty() {
+			self.load(kvs, session, batch).await?;
+		}
+
+		Ok(())
+	}
+
+	fn parse(&mut self, last: bool) {
+		match self.input {
+			Input::Ndjson => self.parse_ndjson(last),
+			Input::Csv => self.parse_csv(last),
+			Input::Cbor => self.parse_cbor(last),
+		}
+	}
+
+	fn parse_ndjson(&mut self, last: bool) {
+		let end = match self.buffer.iter().rposition(|b| *b == b'\n') {
+			_ if last => self.buffer.len(),
+			Some(pos) => pos + 1,
+			None => return,
+		};
+
+		let data: Vec<u8> = self.buffer.drain(..end).collect();
+
+		for line in data.split(|b| *b == b'\n') {
+			if line.iter().all(u8::is_ascii_whitespace) {
+				continue;
+			}
+
+			self.rows += 1;
+			match json::decode(line) {
+				Ok(value @ Value::Object(_)) => self.pending.push((self.rows, value)),
+				Ok(_) => self.fail(self.rows, "Expected a JSON object"),
+				Err(e) => self.fail(self.rows, e),
+			}
+		}
+	}
+
+	fn parse_csv(&mut self, last: bool) {
+		let mut start = 0;
+		let mut quoted = false;
+		let mut records = Vec::new();
+
+		for (pos, byte) in self.buffer.iter().enumerate() {
+			match byte {
+				b'"' => quoted = !quoted,
+				b'\n' if !quoted => {
+					records.push(split_csv_record(&self.buffer[start..pos], self.delimiter));
+					start = pos + 1;
+				}
+				_ => (),
+			}
+		}
+
+		if last && start < self.buffer.len() {
+			records.push(split_csv_record(&self.buffer[start..], self.delimiter));
+			start = self.buffer.len();
+		}
+
+		self.buffer.drain(..start);
+
+		for fields in records {
+			let fields = match fields {
+				Ok(fields) => fields,
+				Err(e) => {
+					self.rows += 1;
+					self.fail(self.rows, e);
+					continue;
+				}
+			};
+
+			let Some(header) = &self.header else {
+				// Blank lines before the header hold no records
+				if is_blank(&fields) {
+					continue;
+				}
+				let header = fields
+					.into_iter()
+					.map(|name| self.columns.get(&name).cloned().unwrap_or(name))
+					.collect();
+				self.header = Some(header);
+				continue;
+			};
+
+			self.rows += 1;
+			// A blank line is a single empty field, which only fits a single column
+			if is_blank(&fields) && header.len() != 1 {
+				self.fail(self.rows, "Empty row");
+				continue;
+			}
+			if fields.len() != header.len() {
+				self.failures.push(Failure {
+					row: self.rows,
+					error: format!("Expected {} fields, found {}", header.len(), fields.len()),
+				});
+				continue;
+			}
+
+			let mut record = Object::default();
+			let mut error = None;
+
+			for (name, field) in header.iter().zip(fields) {
+				if self.null.as_ref() == Some(&field) {
+					record.insert(name.clone(), Value::Null);
+					continue;
+				}
+
+				match coerce(self.types.get(name).copied().unwrap_or_default(), field) {
+					Ok(value) => {
+						record.insert(name.clone(), value);
+					}
+					Err(e) => {
+						error = Some(format!("Invalid value for field `{name}`: {e}"));
+						break;
+					}
+				}
+			}
+
+			match error {
+				Some(error) => self.failures.push(Failure {
+					row: self.rows,
+					error,
+				}),
+				None => self.pending.push((self.rows, Value::Object(record))),
+			}
+		}
+	}
+
+	fn parse_cbor(&mut self, last: bool) {
+		let mut start = 0;
+
+		loop {
+			let (len, malformed) = match cbor_item(&self.buffer[start..]) {
+				CborItem::Complete(len) => (len, false),
+				CborItem::Malformed(len) => (len, true),
+				CborItem::Incomplete => break,
+			};
+			let item = &self.buffer[start..start + len];
+			start += len;
+
+			self.rows += 1;
+			if malformed {
+				self.failures.push(Failure {
+					row: self.rows,
+					error: "Malformed CBOR item".to_string(),
+				});
+				continue;
+			}
+
+			match cbor::decode(item) {
+				Ok(value @ Value::Object(_)) => self.pending.push((self.rows, value)),
+				Ok(_) => self.failures.push(Failure {
+					row: self.rows,
+					error: "Expected a CBOR map".to_string(),
+				}),
+				Err(e) => self.failures.push(Failure {
+					row: self.rows,
+					error: e.to_string(),
+				}),
+			}
+		}
+
+		self.buffer.drain(..start);
+
+		if last && !self.buffer.is_empty() {
+			self.buffer.clear();
+			self.rows += 1;
+			self.fail(self.rows, "Incomplete CBOR item");
+		}
+	}
+
+	/// Writes a batch in a single transaction, retrying records one at a time on failure
+	async fn load(
+		&mut self,
+		kvs: &Datastore,
+		session: &Session,
+		batch: Vec<(u64, Value)>,
+	) -> Result<(), String> {
+		let (rows, values): (Vec<u64>, Vec<Value>) = batch.into_iter().unzip();
+		let count = values.len() as u64;
+
+		if self.insert(kvs, session, values.clone()).await?.is_ok() {
+			self.loaded += count;
+			return Ok(());
+		}
+
+		for (row, value) in rows.into_iter().zip(values) {
+			match self.insert(kvs, session, vec![value]).await? {
+				Ok(()) => self.loaded += 1,
+				Err(e) => self.fail(row, e),
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Runs the insert, separating datastore failures from rejected records
+	async fn insert(
+		&self,
+		kvs: &Datastore,
+		session: &Session,
+		rows: Vec<Value>,
+	) -> Result<Result<(), String>, String> {
+		let mut vars = Variables::new();
+		vars.insert("table".to_string(), Value::String(self.table.clone()));
+		vars.insert("rows".to_string(), Value::Array(Array::from(rows)));
+
+		let res =
+			kvs.execute(INSERT_QUERY, session, Some(vars)).await.map_err(|e| e.to_string())?;
+
+		for res in res {
+			if let Err(e) = res.result {
+				return Ok(Err(e.to_string()));
+			}
+		}
+
+		Ok(Ok(()))
+	}
+
+	fn fail(&mut self, row: u64, error: impl ToString) {
+		self.failures.push(Failure {
+			row,
+			error: error.to_string(),
+		});
+	}
+}
+
+/// Splits a single CSV record into its fields, unescaping quoted fields
+fn split_csv_record(line: &[u8], delimiter: u8) -> Result<Vec<String>, String> {
+	let line = line.strip_suffix(b"\r").unwrap_or(line);
+	let mut fields = Vec::new();
+	let mut field = Vec::new();
+	let mut quoted = false;
+	let mut bytes = line.iter().peekable();
+
+	while let Some(&byte) = bytes.next() {
+		match byte {
+			b'"' if quoted && bytes.peek() == Some(&&b'"') => {
+				field.push(b'"');
+				bytes.next();
+			}
+			b'"' => quoted = !quoted,
+			b if b == delimiter && !quoted => fields.push(mem::take(&mut field)),
+			b => field.push(b),
+		}
+	}
+
+	fields.push(field);
+
+	fields.into_iter().map(|f| String::from_utf8(f).map_err(|e| e.to_string())).collect()
+}
+
+/// Returns whether a CSV record is a blank line
+fn is_blank(fields: &[String]) -> bool {
+	matches!(fields, [field] if field.is_empty())
+}
+
+/// Converts a CSV field into a value of the requested type
+fn coerce(coercion: Coercion, field: String) -> Result<Value, String> {
+	match coercion {
+		Coercion::String => Ok(Value::String(field)),
+		Coercion::Int => field.parse::<i64>().map(Value::from_t).map_err(|e| e.to_string()),
+		Coercion::Float => field.parse::<f64>().map(Value::from_t).map_err(|e| e.to_string()),
+		Coercion::Bool => match field.as_str() {
+			"true" | "1" => Ok(Value::from_t(true)),
+			"false" | "0" => Ok(Value::from_t(false)),
+			_ => Err("expected a boolean".to_string()),
+		},
+		Coercion::Json => json::decode(field.as_bytes()).map_err(|e| e.to_string()),
+		// Text such as `nan` or `inf` parses as a float, but is kept as a string
+		Coercion::Auto => Ok(if let Ok(v) = field.parse::<i64>() {
+			Value::from_t(v)
+		} else if let Some(v) = field.parse::<f64>().ok().filter(|v| v.is_finite()) {
+			Value::from_t(v)
+		} else if let Ok(v) = field.parse::<bool>() {
+			Value::from_t(v)
+		} else {
+			Value::String(field)
+		}),
+	}
+}
+
+/// The first item of a CBOR sequence
+enum CborItem {
+	/// A well-formed item of the given length
+	Complete(usize),
+	/// The item continues past the end of the buffer
+	Incomplete,
+	/// An item which is not well-formed, skipped up to where it became invalid.
+	/// As a CBOR sequence has no delimiters, the items after it may fail too.
+	Malformed(usize),
+}
+
+/// Why a CBOR item could not be skipped
+enum Invalid {
+	Incomplete,
+	Malformed,
+}
+
+/// Finds the extent of the first item in a CBOR sequence
+fn cbor_item(buf: &[u8]) -> CborItem {
+	let mut pos = 0;
+	match skip_cbor_item(buf, &mut pos, 0) {
+		Ok(()) => CborItem::Complete(pos),
+		Err(Invalid::Incomplete) => CborItem::Incomplete,
+		Err(Invalid::Malformed) => CborItem::Malformed(pos.clamp(1, buf.len())),
+	}
+}
+
+fn skip_cbor_item(buf: &[u8], pos: &mut usize, depth: usize) -> Result<(), Invalid> {
+	if depth > MAX_CBOR_DEPTH {
+		return Err(Invalid::Malformed);
+	}
+
+	let initial = *buf.get(*pos).ok_or(Invalid::Incomplete)?;
+	*pos += 1;
+
+	let major = initial >> 5;
+	let arg = match initial & 0x1f {
+		info @ 0..=23 => Some(info as u64),
+		info @ 24..=27 => {
+			let len = 1 << (info - 24);
+			let bytes = buf.get(*pos..*pos + len).ok_or(Invalid::Incomplete)?;
+			*pos += len;
+			Some(bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64))
+		}
+		31 => None,
+		_ => return Err(Invalid::Malformed),
+	};
+
+	match (major, arg) {
+		(0 | 1 | 7, Some(_)) => Ok(()),
+		(2 | 3, Some(len)) => {
+			let end = usize::try_from(len).ok().and_then(|len| pos.checked_add(len));
+			*pos = end.ok_or(Invalid::Malformed)?;
+			match *pos <= buf.len() {
+				true => Ok(()),
+				false => Err(Invalid::Incomplete),
+			}
+		}
+		(4, Some(len)) => (0..len).try_for_each(|_| skip_cbor_item(buf, pos, depth + 1)),
+		(5, Some(len)) => {
+			let items = len.checked_mul(2).ok_or(Invalid::Malformed)?;
+			(0..items).try_for_each(|_| skip_cbor_item(buf, pos, depth + 1))
+		}
+		(6, Some(_)) => skip_cbor_item(buf, pos, depth + 1),
+		(2..=5, None) => loop {
+			if *buf.get(*pos).ok_or(Invalid::Incomplete)? == 0xff {
+				*pos += 1;
+				break Ok(());
+			}
+			skip_cbor_item(buf, pos, depth + 1)?;
+		},
+		_ => Err(Invalid::Malformed),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn loader(input: &str) -> BulkLoader {
+		BulkLoader::new("item".to_string(), input, BulkLoadOptions::default()).unwrap()
+	}
+
+	/// Parses a chunk as `write` does, without loading the records
+	fn feed(loader: &mut BulkLoader, chunk: &[u8]) {
+		loader.buffer.extend_from_slice(chunk);
+		loader.parse(false);
+	}
+
+	fn record(fields: &[(&str, Value)]) -> Value {
+		let mut record = Object::default();
+		for (name, value) in fields {
+			record.insert(name.to_string(), value.clone());
+		}
+		Value::Object(record)
+	}
+
+	fn encoded(value: Value) -> Vec<u8> {
+		cbor::encode(value).map(Vec::from).unwrap()
+	}
+
+	fn failed_rows(loader: &BulkLoader) -> Vec<u64> {
+		loader.failures.iter().map(|f| f.row).collect()
+	}
+
+	#[test]
+	fn cbor_item_split_across_chunks() {
+		let item = encoded(record(&[("name", Value::String("a".repeat(100)))]));
+
+		for split in 0..item.len() {
+			let mut loader = loader("cbor");
+			feed(&mut loader, &item[..split]);
+			assert!(loader.pending.is_empty());
+			feed(&mut loader, &item[split..]);
+			assert_eq!(loader.pending.len(), 1, "split at {split}");
+			assert!(loader.failures.is_empty());
+		}
+	}
+
+	#[test]
+	fn malformed_cbor_item_is_skipped() {
+		let item = encoded(record(&[("value", Value::from_t(1i64))]));
+		let mut loader = loader("cbor");
+
+		// A reserved additional information value
+		feed(&mut loader, &[item.as_slice(), &[0x1c], item.as_slice()].concat());
+
+		assert_eq!(loader.rows, 3);
+		assert_eq!(loader.pending.len(), 2);
+		assert_eq!(failed_rows(&loader), vec![2]);
+		assert!(loader.buffer.is_empty());
+	}
+
+	#[test]
+	fn cbor_map_length_overflow() {
+		let mut loader = loader("cbor");
+
+		feed(&mut loader, &[0xbb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+		assert_eq!(failed_rows(&loader), vec![1]);
+		assert!(loader.buffer.is_empty());
+	}
+
+	#[test]
+	fn cbor_nesting_too_deep() {
+		let mut loader = loader("cbor");
+		let mut item = vec![0x81; MAX_CBOR_DEPTH + 1];
+		item.push(0x00);
+
+		feed(&mut loader, &item);
+
+		assert_eq!(loader.failures[0].row, 1);
+		assert_eq!(loader.failures[0].error, "Malformed CBOR item");
+		assert!(loader.pending.is_empty());
+	}
+
+	#[test]
+	fn incomplete_cbor_item_fails_on_finish() {
+		let item = encoded(record(&[("value", Value::from_t(1i64))]));
+		let mut loader = loader("cbor");
+
+		feed(&mut loader, &item[..item.len() - 1]);
+		assert!(loader.failures.is_empty());
+		loader.parse(true);
+
+		assert_eq!(failed_rows(&loader), vec![1]);
+	}
+
+	#[test]
+	fn ndjson_line_split_across_chunks() {
+		let mut loader = loader("ndjson");
+
+		feed(&mut loader, b"{\"value\": 1}\n{\"val");
+		assert_eq!(loader.pending.len(), 1);
+		feed(&mut loader, b"ue\": 2}\n");
+
+		assert_eq!(loader.pending.len(), 2);
+		assert!(loader.failures.is_empty());
+	}
+
+	#[test]
+	fn csv_row_split_across_chunks() {
+		let mut loader = loader("csv");
+
+		feed(&mut loader, b"name,note\nTobie,\"split\n");
+		assert!(loader.pending.is_empty());
+		feed(&mut loader, b"across\"\n");
+
+		let note = Value::String("split\nacross".to_string());
+		let expected = record(&[("name", Value::String("Tobie".to_string())), ("note", note)]);
+		assert_eq!(loader.pending, vec![(1, expected)]);
+	}
+
+	#[test]
+	fn csv_field_count_mismatch() {
+		let mut loader = loader("csv");
+
+		feed(&mut loader, b"a,b,c\n1,2,3\n1,2\n1,2,3,4\n,,\n");
+
+		assert_eq!(loader.pending.len(), 1);
+		assert_eq!(failed_rows(&loader), vec![2, 3]);
+	}
+
+	#[test]
+	fn auto_coercion_keeps_non_finite_text() {
+		for text in ["nan", "NaN", "inf", "-inf", "infinity", "1e400"] {
+			let value = coerce(Coercion::Auto, text.to_string()).unwrap();
+			assert_eq!(value, Value::String(text.to_string()));
+		}
+
+		assert_eq!(coerce(Coercion::Auto, "1.5".to_string()).unwrap(), Value::from_t(1.5f64));
+		assert_eq!(coerce(Coercion::Auto, "42".to_string()).unwrap(), Value::from_t(42i64));
+	}
+}
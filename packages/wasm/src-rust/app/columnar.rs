@@ -0,0 +1,393 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use arrow_array::ArrayRef;
+use arrow_array::BooleanArray;
+use arrow_array::Float64Array;
+use arrow_array::Int64Array;
+use arrow_array::RecordBatch;
+use arrow_array::StringArray;
+use arrow_array::TimestampMicrosecondArray;
+use arrow_cast::parse::string_to_timestamp_nanos;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use arrow_schema::SchemaRef;
+use arrow_schema::TimeUnit;
+use serde::Deserialize;
+use serde_json::Map;
+use serde_json::Value as JsonValue;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::rpc::format::json;
+use surrealdb_core::sql::kind::KindLiteral;
+use surrealdb_core::sql::statements::DefineStatement;
+use surrealdb_core::sql::Expr;
+use surrealdb_core::sql::Kind;
+use surrealdb_core::sql::TopLevelExpr;
+use surrealdb_types::Value;
+
+use super::data::last_id;
+use super::data::page;
+use super::data::query;
+
+/// The number of records in each record batch when not specified
+const DEFAULT_BATCH_SIZE: usize = 8192;
+
+type Row = Map<String, JsonValue>;
+
+#[derive(Deserialize)]
+pub struct ColumnarExportOptions {
+	/// The table to export, inferring the schema from its field definitions
+	pub table: Option<String>,
+	/// A query whose result is exported instead of a table
+	pub query: Option<String>,
+	/// The number of records in each record batch
+	pub batch_size: Option<u32>,
+}
+
+#[derive(Clone, Copy)]
+pub enum ColumnarFormat {
+	/// The Arrow IPC streaming format
+	Arrow,
+}
+
+impl ColumnarFormat {
+	pub fn parse(format: &str) -> Result<Self, String> {
+		match format {
+			"arrow" => Ok(ColumnarFormat::Arrow),
+			other => Err(format!("Unsupported columnar format: {other}")),
+		}
+	}
+}
+
+/// The Arrow type a column is written as
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnType {
+	Int,
+	Float,
+	Bool,
+	String,
+	Datetime,
+	/// Any other or schemaless value, written as JSON text
+	Json,
+}
+
+impl ColumnType {
+	/// Maps the type of a `DEFINE FIELD` statement onto a column type
+	fn from_definition(definition: &str) -> Self {
+		let Ok(ast) = surrealdb_core::syn::parse(definition) else {
+			return ColumnType::Json;
+		};
+
+		match ast.expressions.first() {
+			Some(TopLevelExpr::Expr(Expr::Define(define))) => match define.as_ref() {
+				DefineStatement::Field(field) => {
+					field.field_kind.as_ref().map_or(ColumnType::Json, ColumnType::from_kind)
+				}
+				_ => ColumnType::Json,
+			},
+			_ => ColumnType::Json,
+		}
+	}
+
+	fn from_kind(kind: &Kind) -> Self {
+		match kind {
+			Kind::Int => ColumnType::Int,
+			Kind::Float | Kind::Number | Kind::Decimal => ColumnType::Float,
+			Kind::Bool => ColumnType::Bool,
+			Kind::String | Kind::Uuid | Kind::Duration | Kind::Record(_) => ColumnType::String,
+			Kind::Datetime => ColumnType::Datetime,
+			Kind::Literal(literal) => match literal {
+				KindLiteral::Integer(_) => ColumnType::Int,
+				KindLiteral::Float(_) | KindLiteral::Decimal(_) => ColumnType::Float,
+				KindLiteral::Bool(_) => ColumnType::Bool,
+				KindLiteral::String(_) | KindLiteral::Duration(_) => ColumnType::String,
+				_ => ColumnType::Json,
+			},
+			// An optional value is a null in the column, and a union of kinds which
+			// share a column type is written as that type
+			Kind::Either(kinds) => {
+				let mut types = kinds
+					.iter()
+					.filter(|kind| !matches!(kind, Kind::None | Kind::Null))
+					.map(ColumnType::from_kind);
+				match types.next() {
+					Some(first) if types.all(|kind| kind == first) => first,
+					_ => ColumnType::Json,
+				}
+			}
+			_ => ColumnType::Json,
+		}
+	}
+
+	fn data_type(&self) -> DataType {
+		match self {
+			ColumnType::Int => DataType::Int64,
+			ColumnType::Float => DataType::Float64,
+			ColumnType::Bool => DataType::Boolean,
+			ColumnType::Datetime => {
+				DataType::Timestamp(TimeUnit::Microsecond, Some("+00:00".into()))
+			}
+			ColumnType::String | ColumnType::Json => DataType::Utf8,
+		}
+	}
+}
+
+/// Reads a table or query result and encodes it in a columnar format.
+///
+/// Table columns are typed from their `DEFINE FIELD` statements, falling back
+/// to a JSON text column for schemaless fields. Record ids are written as
+/// `table:id` strings, and every column is nullable. A value which does not fit
+/// the type of its column fails the export.
+pub async fn export(
+	kvs: &Datastore,
+	session: &Session,
+	format: ColumnarFormat,
+	options: ColumnarExportOptions,
+) -> Result<Vec<u8>, String> {
+	let batch_size = options.batch_size.map_or(DEFAULT_BATCH_SIZE, |b| b.max(1) as usize);
+
+	match (options.table, options.query) {
+		(Some(table), None) => export_table(kvs, session, format, &table, batch_size).await,
+		(None, Some(sql)) => {
+			let rows = match query(kvs, session, &sql, None).await? {
+				Value::Array(values) => rows(values.into_vec())?,
+				value => rows(vec![value])?,
+			};
+
+			let mut columns = vec![("id".to_string(), ColumnType::String)];
+			let undefined = undefined_fields(&columns, &rows);
+			columns.extend(undefined.into_iter().map(|name| (name, ColumnType::Json)));
+			let schema = schema(&columns);
+
+			let mut writer = Writer::new(format, &schema)?;
+			for rows in rows.chunks(batch_size) {
+				writer.write(&record_batch(&schema, &columns, rows)?)?;
+			}
+			writer.finish()
+		}
+		_ => Err("Expected either a table or a query to export".to_string()),
+	}
+}
+
+/// Exports a table a batch at a time.
+///
+/// Fields without a definition are found by a first pass over the table, so a
+/// field first written while the table is exported fails the export.
+async fn export_table(
+	kvs: &Datastore,
+	session: &Session,
+	format: ColumnarFormat,
+	table: &str,
+	batch_size: usize,
+) -> Result<Vec<u8>, String> {
+	let mut columns = vec![("id".to_string(), ColumnType::String)];
+	columns.extend(defined_columns(kvs, session, table).await?);
+
+	let mut undefined = BTreeSet::new();
+	let mut after = None;
+	loop {
+		let records = page(kvs, session, table, after.take(), batch_size, None).await?;
+		let count = records.len();
+		after = last_id(&records);
+
+		let rows = rows(records)?;
+		undefined.extend(undefined_fields(&columns, &rows));
+
+		if count < batch_size || after.is_none() {
+			break;
+		}
+	}
+	columns.extend(undefined.into_iter().map(|name| (name, ColumnType::Json)));
+
+	let schema = schema(&columns);
+	let mut writer = Writer::new(format, &schema)?;
+	let mut after = None;
+	loop {
+		let records = page(kvs, session, table, after.take(), batch_size, None).await?;
+		let count = records.len();
+		after = last_id(&records);
+
+		let rows = rows(records)?;
+		if !rows.is_empty() {
+			writer.write(&record_batch(&schema, &columns, &rows)?)?;
+		}
+
+		if count < batch_size || after.is_none() {
+			break;
+		}
+	}
+
+	writer.finish()
+}
+
+/// Converts records into JSON rows, skipping any value which is not an object
+fn rows(values: Vec<Value>) -> Result<Vec<Row>, String> {
+	values
+		.into_iter()
+		.map(|value| {
+			let json = json::encode(value).map(Vec::from).map_err(|e| e.to_string())?;
+			serde_json::from_slice::<JsonValue>(&json).map_err(|e| e.to_string())
+		})
+		.filter_map(|row| match row {
+			Ok(JsonValue::Object(row)) => Some(Ok(row)),
+			Ok(_) => None,
+			Err(e) => Some(Err(e)),
+		})
+		.collect()
+}
+
+/// Finds the fields of the rows without a column, which are written as JSON text
+fn undefined_fields(columns: &[(String, ColumnType)], rows: &[Row]) -> BTreeSet<String> {
+	rows.iter()
+		.flat_map(|row| row.keys())
+		.filter(|key| !columns.iter().any(|(name, _)| name == *key))
+		.cloned()
+		.collect()
+}
+
+/// Reads the top level field definitions of a table
+async fn defined_columns(
+	kvs: &Datastore,
+	session: &Session,
+	table: &str,
+) -> Result<Vec<(String, ColumnType)>, String> {
+	let sql = format!("INFO FOR TABLE `{}`", table.replace('`', "\\`"));
+	let Value::Object(info) = query(kvs, session, &sql, None).await? else {
+		return Ok(Vec::new());
+	};
+	let Some(Value::Object(fields)) = info.get("fields") else {
+		return Ok(Vec::new());
+	};
+
+	Ok(fields
+		.iter()
+		.filter(|(name, _)| !name.contains(['.', '[']) && name.as_str() != "id")
+		.map(|(name, definition)| {
+			let kind = match definition {
+				Value::String(definition) => ColumnType::from_definition(definition),
+				_ => ColumnType::Json,
+			};
+			(name.clone(), kind)
+		})
+		.collect())
+}
+
+/// Builds the Arrow schema for a set of columns, all of which are nullable
+fn schema(columns: &[(String, ColumnType)]) -> SchemaRef {
+	let fields: Vec<Field> =
+		columns.iter().map(|(name, kind)| Field::new(name, kind.data_type(), true)).collect();
+	Arc::new(Schema::new(fields))
+}
+
+/// Converts rows into a record batch, failing on a value which does not fit its column
+fn record_batch(
+	schema: &SchemaRef,
+	columns: &[(String, ColumnType)],
+	rows: &[Row],
+) -> Result<RecordBatch, String> {
+	let unknown = rows
+		.iter()
+		.flat_map(|row| row.keys())
+		.find(|key| !columns.iter().any(|(name, _)| name == *key));
+	if let Some(field) = unknown {
+		return Err(format!("The field `{field}` was first written during the export"));
+	}
+
+	let arrays = columns
+		.iter()
+		.map(|(name, kind)| {
+			let values = rows.iter().map(|row| row.get(name).filter(|v| !v.is_null()));
+			column(name, *kind, values)
+		})
+		.collect::<Result<Vec<_>, _>>()?;
+
+	RecordBatch::try_new(schema.clone(), arrays).map_err(|e| e.to_string())
+}
+
+fn column<'a>(
+	name: &str,
+	kind: ColumnType,
+	values: impl Iterator<Item = Option<&'a JsonValue>>,
+) -> Result<ArrayRef, String> {
+	let array: ArrayRef = match kind {
+		ColumnType::Int => {
+			Arc::new(Int64Array::from(convert(name, "an int", values, |v| v.as_i64())?))
+		}
+		// A number may be held in a string, as a decimal can be
+		ColumnType::Float => {
+			Arc::new(Float64Array::from(convert(name, "a number", values, |v| {
+				v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+			})?))
+		}
+		ColumnType::Bool => {
+			Arc::new(BooleanArray::from(convert(name, "a bool", values, |v| v.as_bool())?))
+		}
+		ColumnType::Datetime => {
+			let timestamps = convert(name, "a datetime", values, |v| {
+				v.as_str().and_then(|s| string_to_timestamp_nanos(s).ok()).map(|ns| ns / 1000)
+			})?;
+			Arc::new(TimestampMicrosecondArray::from(timestamps).with_timezone("+00:00"))
+		}
+		ColumnType::String => Arc::new(StringArray::from(
+			values
+				.map(|v| match v {
+					Some(JsonValue::String(s)) => Some(s.clone()),
+					v => v.map(JsonValue::to_string),
+				})
+				.collect::<Vec<_>>(),
+		)),
+		ColumnType::Json => Arc::new(StringArray::from(
+			values.map(|v| v.map(JsonValue::to_string)).collect::<Vec<_>>(),
+		)),
+	};
+
+	Ok(array)
+}
+
+/// Converts the values of a column, failing on the first which does not fit its type
+fn convert<'a, T>(
+	name: &str,
+	expected: &str,
+	values: impl Iterator<Item = Option<&'a JsonValue>>,
+	convert: impl Fn(&JsonValue) -> Option<T>,
+) -> Result<Vec<Option<T>>, String> {
+	values
+		.map(|value| match value {
+			Some(value) => convert(value)
+				.map(Some)
+				.ok_or_else(|| format!("Expected {expected} in column `{name}`, found {value}")),
+			None => Ok(None),
+		})
+		.collect()
+}
+
+/// Encodes record batches as they are written
+enum Writer {
+	/// An Arrow IPC stream
+	Arrow(StreamWriter<Vec<u8>>),
+}
+
+impl Writer {
+	fn new(format: ColumnarFormat, schema: &SchemaRef) -> Result<Self, String> {
+		match format {
+			ColumnarFormat::Arrow => StreamWriter::try_new(Vec::new(), schema)
+				.map(Writer::Arrow)
+				.map_err(|e| e.to_string()),
+		}
+	}
+
+	fn write(&mut self, batch: &RecordBatch) -> Result<(), String> {
+		match self {
+			Writer::Arrow(writer) => writer.write(batch).map_err(|e| e.to_string()),
+		}
+	}
+
+	fn finish(self) -> Result<Vec<u8>, String> {
+		match self {
+			Writer::Arrow(writer) => writer.into_inner().map_err(|e| e.to_string()),
+		}
+	}
+}
@@ -0,0 +1,276 @@
+use std::collections::VecDeque;
+use std::ops::Bound;
+
+use serde::Deserialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::kvs::export::Config;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_core::rpc::format::json;
+use surrealdb_types::Array;
+use surrealdb_types::Object;
+use surrealdb_types::RecordId;
+use surrealdb_types::RecordIdKey;
+use surrealdb_types::RecordIdKeyRange;
+use surrealdb_types::Value;
+use surrealdb_types::Variables;
+
+/// The number of records read in each page when not specified
+const DEFAULT_PAGE_SIZE: usize = 1000;
+
+/// Reads a page of records from the record id range `$range`, which a range
+/// scan returns in record id order without reading the records before it
+const PAGE_QUERY: &str = "SELECT * FROM $range LIMIT $limit";
+
+/// Reads a page as it was at a point in time, on a versioned datastore
+const VERSION_CLAUSE: &str = " VERSION <datetime> $as_of";
+
+#[derive(Deserialize, Default)]
+pub struct DataExportOptions {
+	/// The tables to export, defaulting to every table in the database
+	pub tables: Option<Vec<String>>,
+	/// The number of records read from the datastore at a time
+	pub page_size: Option<u32>,
+	/// Whether to send the table definitions as a SurrealQL sidecar
+	pub schema: Option<bool>,
+	/// A datetime to export the records as they were at, on a versioned datastore
+	pub as_of: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+pub enum DataFormat {
+	/// One JSON document per line
+	Ndjson,
+	/// A single JSON array per table
+	Json,
+	/// A CBOR sequence, using the SurrealDB CBOR tags
+	Cbor,
+}
+
+impl DataFormat {
+	pub fn parse(format: &str) -> Result<Self, String> {
+		match format {
+			"ndjson" => Ok(DataFormat::Ndjson),
+			"json" => Ok(DataFormat::Json),
+			"cbor" => Ok(DataFormat::Cbor),
+			other => Err(format!("Unsupported export format: {other}")),
+		}
+	}
+}
+
+/// A chunk of exported data, or the schema sidecar when no table is set
+pub struct DataChunk {
+	pub table: Option<String>,
+	pub data: Vec<u8>,
+}
+
+/// Reads tables page by page, encoding each page of records as a chunk.
+///
+/// Each page follows the last record id of the previous one, so records written
+/// during the export are neither skipped nor repeated, though those written
+/// behind the last page read are not exported. JSON encodes record ids as
+/// `table:id` strings, datetimes as RFC 3339 strings, durations as SurrealQL
+/// duration strings, and geometries as GeoJSON. CBOR keeps every value intact
+/// using the SurrealDB CBOR tags.
+pub struct DataExport {
+	format: DataFormat,
+	page_size: usize,
+	schema: bool,
+	tables: Option<VecDeque<String>>,
+	as_of: Option<String>,
+	/// The id of the last record exported from the current table
+	after: Option<Value>,
+}
+
+impl DataExport {
+	pub fn new(format: &str, options: DataExportOptions) -> Result<Self, String> {
+		Ok(DataExport {
+			format: DataFormat::parse(format)?,
+			page_size: options.page_size.map_or(DEFAULT_PAGE_SIZE, |p| p.max(1) as usize),
+			schema: options.schema.unwrap_or(false),
+			tables: options.tables.map(VecDeque::from),
+			as_of: options.as_of,
+			after: None,
+		})
+	}
+
+	/// Reads and encodes the next chunk, returning `None` once every table is exported
+	pub async fn next(
+		&mut self,
+		kvs: &Datastore,
+		session: &Session,
+	) -> Result<Option<DataChunk>, String> {
+		if self.tables.is_none() {
+			self.tables = Some(list_tables(kvs, session).await?);
+		}
+
+		if std::mem::take(&mut self.schema) {
+			return Ok(Some(DataChunk {
+				table: None,
+				data: self.export_schema(kvs, session).await?,
+			}));
+		}
+
+		while let Some(table) = self.tables.as_ref().and_then(|t| t.front()).cloned() {
+			let first = self.after.is_none();
+			let records = page(
+				kvs,
+				session,
+				&table,
+				self.after.take(),
+				self.page_size,
+				self.as_of.as_deref(),
+			)
+			.await?;
+			self.after = last_id(&records);
+			let last = records.len() < self.page_size || self.after.is_none();
+			let mut data = Vec::new();
+
+			for (index, record) in records.into_iter().enumerate() {
+				match self.format {
+					DataFormat::Ndjson => {
+						data.extend(
+							json::encode(record).map(Vec::from).map_err(|e| e.to_string())?,
+						);
+						data.push(b'\n');
+					}
+					DataFormat::Json => {
+						data.push(if first && index == 0 {
+							b'['
+						} else {
+							b','
+						});
+						data.extend(
+							json::encode(record).map(Vec::from).map_err(|e| e.to_string())?,
+						);
+					}
+					DataFormat::Cbor => {
+						data.extend(
+							cbor::encode(record).map(Vec::from).map_err(|e| e.to_string())?,
+						);
+					}
+				}
+			}
+
+			if let DataFormat::Json = self.format {
+				if first && data.is_empty() {
+					data.push(b'[');
+				}
+				if last {
+					data.push(b']');
+				}
+			}
+
+			if last {
+				self.tables.as_mut().map(VecDeque::pop_front);
+				self.after = None;
+			}
+
+			if !data.is_empty() {
+				return Ok(Some(DataChunk {
+					table: Some(table),
+					data,
+				}));
+			}
+		}
+
+		Ok(None)
+	}
+
+	/// Exports the definitions of the selected tables as SurrealQL
+	async fn export_schema(&self, kvs: &Datastore, session: &Session) -> Result<Vec<u8>, String> {
+		let tables = self.tables.iter().flatten().map(|t| Value::String(t.clone())).collect();
+		let mut config = Object::default();
+		config.insert("users".to_string(), Value::from_t(false));
+		config.insert("accesses".to_string(), Value::from_t(false));
+		config.insert("versions".to_string(), Value::from_t(false));
+		config.insert("records".to_string(), Value::from_t(false));
+		config.insert("tables".to_string(), Value::Array(Array::from(tables)));
+		let config = Value::Object(config).into_t::<Config>().map_err(|e| e.to_string())?;
+
+		let (tx, rx) = channel::unbounded();
+		kvs.export_with_config(session, tx, config)
+			.await
+			.map_err(|e| e.to_string())?
+			.await
+			.map_err(|e| e.to_string())?;
+
+		let mut buffer = Vec::new();
+		while let Ok(item) = rx.try_recv() {
+			buffer.push(item);
+		}
+
+		Ok(buffer.concat().into())
+	}
+}
+
+/// Reads up to `limit` records of a table in record id order, following the record id `after`
+pub async fn page(
+	kvs: &Datastore,
+	session: &Session,
+	table: &str,
+	after: Option<Value>,
+	limit: usize,
+	as_of: Option<&str>,
+) -> Result<Vec<Value>, String> {
+	let start = match after {
+		Some(Value::RecordId(after)) => Bound::Excluded(after.key),
+		Some(_) => return Err(format!("Expected a record id to page {table} from")),
+		None => Bound::Unbounded,
+	};
+	let range = RecordId {
+		table: table.into(),
+		key: RecordIdKey::Range(Box::new(RecordIdKeyRange {
+			start,
+			end: Bound::Unbounded,
+		})),
+	};
+
+	let mut vars = Variables::new();
+	vars.insert("range".to_string(), Value::RecordId(range));
+	vars.insert("limit".to_string(), Value::from_t(limit as i64));
+	let mut sql = PAGE_QUERY.to_string();
+	if let Some(as_of) = as_of {
+		vars.insert("as_of".to_string(), Value::String(as_of.to_string()));
+		sql.push_str(VERSION_CLAUSE);
+	}
+
+	match query(kvs, session, &sql, Some(vars)).await? {
+		Value::Array(records) => Ok(records.into_vec()),
+		_ => Ok(Vec::new()),
+	}
+}
+
+/// Returns the id of the last record of a page
+pub fn last_id(records: &[Value]) -> Option<Value> {
+	match records.last()? {
+		Value::Object(record) => record.get("id").cloned(),
+		_ => None,
+	}
+}
+
+/// Lists every table in the session database
+async fn list_tables(kvs: &Datastore, session: &Session) -> Result<VecDeque<String>, String> {
+	match query(kvs, session, "INFO FOR DB", None).await? {
+		Value::Object(info) => match info.get("tables") {
+			Some(Value::Object(tables)) => Ok(tables.keys().cloned().collect()),
+			_ => Ok(VecDeque::new()),
+		},
+		_ => Ok(VecDeque::new()),
+	}
+}
+
+/// Runs a single statement query, returning its result
+pub async fn query(
+	kvs: &Datastore,
+	session: &Session,
+	sql: &str,
+	vars: Option<Variables>,
+) -> Result<Value, String> {
+	let res = kvs.execute(sql, session, vars).await.map_err(|e| e.to_string())?;
+
+	match res.into_iter().next() {
+		Some(res) => res.result.map_err(|e| e.to_string()),
+		None => Ok(Value::None),
+	}
+}
@@ -0,0 +1,56 @@
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{global, Function, Promise, Reflect};
+
+/// Deletes the IndexedDB database of an `indxdb` endpoint.
+///
+/// Deleting waits for every connection to the database to be closed, so it is
+/// rejected when the database is still open in another tab or worker. IndexedDB
+/// cannot cancel a pending deletion, which stays queued and goes ahead once the
+/// database is closed there. In-memory endpoints have nothing to delete.
+pub async fn destroy(endpoint: &str) -> Result<(), JsValue> {
+	let Some(name) = endpoint.strip_prefix("indxdb:") else {
+		return Ok(());
+	};
+	let name = name.trim_start_matches("//");
+	let name = name.split_once('?').map_or(name, |(name, _)| name);
+
+	let factory = Reflect::get(&global(), &"indexedDB".into())?;
+	if factory.is_undefined() {
+		return Err("IndexedDB is not available in this context".into());
+	}
+	let delete = Reflect::get(&factory, &"deleteDatabase".into())?.dyn_into::<Function>()?;
+	let request = delete.call1(&factory, &name.into())?;
+
+	let failed = format!("Failed to delete the database {name}");
+	let blocked = format!(
+		"The database {name} is still open in another tab or worker, and will be deleted once it is closed"
+	);
+
+	let promise = Promise::new(&mut |resolve, reject| {
+		let (failed, blocked) = (JsValue::from(&failed), JsValue::from(&blocked));
+		let on = |event: &str, handler: JsValue| Reflect::set(&request, &event.into(), &handler);
+		let _ = on(
+			"onsuccess",
+			Closure::once_into_js(move || {
+				let _ = resolve.call0(&JsValue::NULL);
+			}),
+		);
+		let error = reject.clone();
+		let _ = on(
+			"onerror",
+			Closure::once_into_js(move || {
+				let _ = error.call1(&JsValue::NULL, &failed);
+			}),
+		);
+		let _ = on(
+			"onblocked",
+			Closure::once_into_js(move || {
+				let _ = reject.call1(&JsValue::NULL, &blocked);
+			}),
+		);
+	});
+
+	JsFuture::from(promise).await.map(|_| ())
+}
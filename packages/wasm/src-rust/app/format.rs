@@ -0,0 +1,57 @@
+use crate::err::Error;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_core::rpc::format::json;
+use surrealdb_core::rpc::Request;
+use surrealdb_types::Value;
+
+/// The serialisation format used for requests, responses and notifications
+#[derive(Clone, Copy, Default)]
+pub enum Format {
+	#[default]
+	Cbor,
+	Json,
+}
+
+impl Format {
+	/// Parses an optional format name, defaulting to CBOR
+	pub fn parse(format: Option<&str>) -> Result<Self, Error> {
+		match format {
+			None | Some("cbor") => Ok(Format::Cbor),
+			Some("json") => Ok(Format::Json),
+			Some(other) => Err(Error::from(format!("Unsupported format: {other}"))),
+		}
+	}
+
+	pub fn decode(&self, data: &[u8]) -> Result<Value, Error> {
+		match self {
+			Format::Cbor => cbor::decode(data).map_err(|e| Error::from(e.to_string())),
+			Format::Json => json::decode(data).map_err(|e| Error::from(e.to_string())),
+		}
+	}
+
+	/// Decodes a single RPC request
+	pub fn request(&self, data: &[u8]) -> Result<Request, Error> {
+		let obj = self.decode(data)?.into_object()?;
+		Ok(Request::from_object(obj)?)
+	}
+
+	pub fn encode(&self, value: Value) -> Result<Vec<u8>, Error> {
+		match self {
+			Format::Cbor => {
+				cbor::encode(value).map(Vec::from).map_err(|e| Error::from(e.to_string()))
+			}
+			Format::Json => {
+				json::encode(value).map(Vec::from).map_err(|e| Error::from(e.to_string()))
+			}
+		}
+	}
+}
+
+/// Converts a value into JSON, as encoded by the JSON wire format
+pub fn to_json(value: Value) -> serde_json::Value {
+	json::encode(value)
+		.map(Vec::from)
+		.ok()
+		.and_then(|data| serde_json::from_slice(&data).ok())
+		.unwrap_or_default()
+}
@@ -0,0 +1,195 @@
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::RwLock;
+
+use futures::future::LocalBoxFuture;
+use surrealdb_core::rpc::format::cbor;
+use surrealdb_core::rpc::Request;
+use surrealdb_types::Object;
+use surrealdb_types::Value;
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+use web_sys::js_sys::Uint8Array;
+
+use super::call_async;
+use super::error_envelope;
+use super::recording::entry;
+
+/// What happens to a request once an interceptor has seen it
+pub enum Before {
+	/// Passes the request on to the next interceptor, and then to the engine
+	Continue,
+	/// Skips the remaining interceptors and the engine, responding with this value
+	Respond(Value),
+}
+
+/// Runs around the execution of every request.
+///
+/// The response seen by `after` is either the result of the request or an object
+/// holding its `error`, as sent to the SDK. Returning an error from either method
+/// replaces the response with an error envelope, while a response changed in place
+/// keeps whether it is an error. Interceptors run on the thread
+/// of the engine, so they need not be `Send` or `Sync`.
+pub trait Interceptor {
+	/// Inspects or changes a request before it executes
+	fn before<'a>(
+		&'a self,
+		_req: &'a mut Request,
+	) -> LocalBoxFuture<'a, Result<Before, surrealdb_types::Error>> {
+		Box::pin(async { Ok(Before::Continue) })
+	}
+
+	/// Inspects or changes the response to a request, described as by `recording::entry`
+	fn after<'a>(
+		&'a self,
+		_req: &'a Object,
+		_response: &'a mut Value,
+	) -> LocalBoxFuture<'a, Result<(), surrealdb_types::Error>> {
+		Box::pin(async { Ok(()) })
+	}
+}
+
+/// An ordered chain of interceptors.
+///
+/// `before` runs in the order the interceptors were added, and `after` in the
+/// reverse order, for every interceptor whose `before` ran. An interceptor which
+/// responds, or fails, in `before` stops the request from reaching the remaining
+/// interceptors and the engine, though its own `after` still runs.
+#[derive(Default)]
+pub struct Interceptors {
+	chain: RwLock<Vec<Rc<dyn Interceptor>>>,
+}
+
+impl Interceptors {
+	/// Adds an interceptor at the end of the chain
+	pub fn push(&self, interceptor: Rc<dyn Interceptor>) {
+		self.chain.write().unwrap().push(interceptor);
+	}
+
+	/// Runs a request through the chain, executing it unless an interceptor responds.
+	///
+	/// Resolves to the result of the request, or to its error envelope.
+	pub async fn run<F, Fut>(&self, mut req: Request, execute: F) -> Result<Value, Value>
+	where
+		F: FnOnce(Request) -> Fut,
+		Fut: Future<Output = Result<Value, Value>>,
+	{
+		let chain = self.chain.read().unwrap().clone();
+		if chain.is_empty() {
+			return execute(req).await;
+		}
+
+		let mut entered = 0;
+		let mut response = None;
+		for interceptor in &chain {
+			entered += 1;
+			match interceptor.before(&mut req).await {
+				Ok(Before::Continue) => continue,
+				Ok(Before::Respond(value)) => response = Some(Ok(value)),
+				Err(err) => response = Some(Err(error_envelope(err))),
+			}
+			break;
+		}
+
+		let entry = entry(&req);
+		let mut response = match response {
+			Some(response) => response,
+			None => execute(req).await,
+		};
+
+		for interceptor in chain[..entered].iter().rev() {
+			let (Ok(value) | Err(value)) = &mut response;
+			let failed = interceptor.after(&entry, value).await.err();
+			if let Some(err) = failed {
+				response = Err(error_envelope(err));
+			}
+		}
+
+		response
+	}
+}
+
+/// An interceptor calling JavaScript functions with CBOR encoded values.
+///
+/// `before` receives the request and resolves to `{ request }` to replace it,
+/// `{ response }` to respond without executing it, or `null` to continue. `after`
+/// receives `{ request, response }` and resolves to `{ response }` to replace the
+/// response, or `null` to keep it.
+pub struct JsInterceptor {
+	pub before: Option<Function>,
+	pub after: Option<Function>,
+}
+
+impl Interceptor for JsInterceptor {
+	fn before<'a>(
+		&'a self,
+		req: &'a mut Request,
+	) -> LocalBoxFuture<'a, Result<Before, surrealdb_types::Error>> {
+		Box::pin(async move {
+			let Some(before) = &self.before else {
+				return Ok(Before::Continue);
+			};
+
+			let Some(mut action) = call(before, Value::Object(entry(req))).await? else {
+				return Ok(Before::Continue);
+			};
+
+			if let Some(response) = action.remove("response") {
+				return Ok(Before::Respond(response));
+			}
+			if let Some(Value::Object(replacement)) = action.remove("request") {
+				*req = Request::from_object(replacement).map_err(failed)?;
+			}
+
+			Ok(Before::Continue)
+		})
+	}
+
+	fn after<'a>(
+		&'a self,
+		req: &'a Object,
+		response: &'a mut Value,
+	) -> LocalBoxFuture<'a, Result<(), surrealdb_types::Error>> {
+		Box::pin(async move {
+			let Some(after) = &self.after else {
+				return Ok(());
+			};
+
+			let mut exchange = Object::default();
+			exchange.insert("request".to_string(), Value::Object(req.clone()));
+			exchange.insert("response".to_string(), response.clone());
+
+			if let Some(mut action) = call(after, Value::Object(exchange)).await? {
+				if let Some(replacement) = action.remove("response") {
+					*response = replacement;
+				}
+			}
+
+			Ok(())
+		})
+	}
+}
+
+/// Calls a hook with a CBOR encoded value, decoding the object it resolves to
+async fn call(hook: &Function, value: Value) -> Result<Option<Object>, surrealdb_types::Error> {
+	let data = cbor::encode(value).map_err(failed)?;
+	let data: Uint8Array = data.as_slice().into();
+	let action = call_async(hook, &JsValue::from(data)).await.map_err(failed)?;
+	if action.is_null() || action.is_undefined() {
+		return Ok(None);
+	}
+
+	let action = action
+		.dyn_into::<Uint8Array>()
+		.map_err(|_| failed("Expected the interceptor to resolve to a Uint8Array or null"))?;
+	match cbor::decode(&action.to_vec()).map_err(failed)? {
+		Value::Object(action) => Ok(Some(action)),
+		_ => Err(failed("Expected the interceptor to resolve to an object or null")),
+	}
+}
+
+fn failed(err: impl std::fmt::Display) -> surrealdb_types::Error {
+	surrealdb_core::rpc::types_error_from_anyhow(anyhow::anyhow!("Interceptor failed: {err}"))
+}
@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing::Event;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::reload;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::Registry;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+
+/// The filter used until directives are configured
+const DEFAULT_FILTER: &str = "warn";
+
+/// A tracing event, along with the spans it was recorded in
+#[derive(Serialize)]
+pub struct LogRecord {
+	pub level: String,
+	pub target: String,
+	pub message: String,
+	pub fields: HashMap<String, String>,
+	/// The names of the enclosing spans, from the outermost
+	pub spans: Vec<String>,
+}
+
+thread_local! {
+	/// The JS function receiving records, which can only be used on its own thread
+	static HANDLER: RefCell<Option<Function>> = const { RefCell::new(None) };
+}
+
+static FILTER: OnceLock<Result<reload::Handle<EnvFilter, Registry>, String>> = OnceLock::new();
+
+/// Installs the forwarding subscriber the first time it is needed.
+///
+/// The subscriber is global, so the handler and filter apply to every engine
+/// in the process. Installing fails if the process already has a global
+/// subscriber of its own, as records would never reach the handler.
+fn install() -> Result<&'static reload::Handle<EnvFilter, Registry>, String> {
+	let handle = FILTER.get_or_init(|| {
+		let (filter, handle) = reload::Layer::new(EnvFilter::new(DEFAULT_FILTER));
+		match tracing_subscriber::registry().with(filter).with(Forward).try_init() {
+			Ok(()) => Ok(handle),
+			Err(e) => Err(format!("Logs cannot be forwarded, as a global subscriber is set: {e}")),
+		}
+	});
+	handle.as_ref().map_err(Clone::clone)
+}
+
+/// Replaces the filter with directives such as `surrealdb_core::dbs=debug`
+pub fn set_filter(directives: &str) -> Result<(), String> {
+	let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+	install()?.reload(filter).map_err(|e| e.to_string())
+}
+
+/// Sets the function receiving every record which passes the filter, or removes it
+pub fn set_handler(handler: Option<Function>) -> Result<(), String> {
+	if handler.is_some() {
+		install()?;
+	}
+	HANDLER.with(|h| *h.borrow_mut() = handler);
+	Ok(())
+}
+
+struct Forward;
+
+impl<S> Layer<S> for Forward
+where
+	S: Subscriber + for<'a> LookupSpan<'a>,
+{
+	fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+		// Clone the handler so it can replace itself while being called
+		let Some(handler) = HANDLER.with(|h| h.borrow().clone()) else {
+			return;
+		};
+
+		let mut visitor = FieldVisitor::default();
+		event.record(&mut visitor);
+
+		let spans = ctx
+			.event_scope(event)
+			.map(|scope| scope.from_root().map(|span| span.name().to_string()).collect())
+			.unwrap_or_default();
+
+		let metadata = event.metadata();
+		let record = LogRecord {
+			level: metadata.level().to_string(),
+			target: metadata.target().to_string(),
+			message: visitor.message,
+			fields: visitor.fields,
+			spans,
+		};
+
+		let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+		if let Ok(record) = record.serialize(&serializer) {
+			let _ = handler.call1(&JsValue::NULL, &record);
+		}
+	}
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+	message: String,
+	fields: HashMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+	fn record_str(&mut self, field: &Field, value: &str) {
+		match field.name() {
+			"message" => self.message = value.to_string(),
+			name => {
+				self.fields.insert(name.to_string(), value.to_string());
+			}
+		}
+	}
+
+	fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+		match field.name() {
+			"message" => self.message = format!("{value:?}"),
+			name => {
+				self.fields.insert(name.to_string(), format!("{value:?}"));
+			}
+		}
+	}
+}
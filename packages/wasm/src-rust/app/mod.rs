@@ -1,42 +1,123 @@
 use std::future::Future;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::RwLock as StdRwLock;
 use std::time::Duration;
 
+mod audit;
+mod broadcast;
+mod bulk;
+mod changes;
+mod columnar;
+mod data;
+mod destroy;
+mod format;
+mod inspect;
+pub mod intercept;
+mod logs;
 mod options;
+mod pin;
+mod readonly;
+mod recording;
+mod slowlog;
+mod stats;
+mod storage;
+mod stream;
+mod sync;
 mod types;
+mod upgrade;
 
 macro_rules! wasm_trace {
 	($($arg:tt)*) => {
+		tracing::debug!(target: "surrealdb_wasm", $($arg)*);
 		#[cfg(feature = "debug")]
 		web_sys::console::log_1(&format!($($arg)*).into());
 	};
 }
 
 pub use crate::err::Error;
+use audit::Audit;
+use broadcast::Broadcast;
+use bulk::{BulkLoadOptions, BulkLoader};
+use changes::ChangesExportOptions;
+use columnar::{ColumnarExportOptions, ColumnarFormat};
 use dashmap::DashMap;
+use data::{DataExport, DataExportOptions};
+use format::Format;
 use futures::channel::oneshot;
+use futures::future::join_all;
 use futures::StreamExt;
+use intercept::{Interceptor, Interceptors, JsInterceptor};
 use options::Options;
-use serde_wasm_bindgen::from_value;
+use pin::Pin;
+use recording::Recorder;
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use slowlog::SlowLog;
+use stats::StatsOptions;
 use surrealdb_core::dbs::Session;
 use surrealdb_core::kvs::export::Config;
 use surrealdb_core::kvs::{Datastore, LockType, Transaction, TransactionType};
 use surrealdb_core::rpc::format::cbor;
 use surrealdb_core::rpc::{DbResult, Request, RpcProtocol};
 use surrealdb_types::Value;
-use surrealdb_types::{Array, HashMap, Notification};
+use surrealdb_types::{Array, HashMap};
+use sync::SyncOptions;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use wasm_bindgen::prelude::wasm_bindgen;
-use wasm_bindgen::JsValue;
-use wasm_bindgen_futures::spawn_local;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 use wasm_streams::readable::sys;
 use wasm_streams::ReadableStream;
-use web_sys::js_sys::Uint8Array;
+use web_sys::js_sys::{Function, Object, Promise, Reflect, Uint8Array};
 
 #[wasm_bindgen]
-pub struct SurrealWasmEngine(SurrealWasmConnection);
+pub struct SurrealWasmEngine(Rc<SurrealWasmConnection>, Rc<Interceptors>);
+
+/// An encoded response kept in linear memory, which JavaScript reads in place
+#[wasm_bindgen]
+pub struct EncodedResponse(Vec<u8>);
+
+#[wasm_bindgen]
+impl EncodedResponse {
+	/// Returns a view of the response over the memory of the module, without copying.
+	///
+	/// The view is detached once the memory grows, so it must be read before calling
+	/// into the module again, and the response freed once it has been read.
+	pub fn view(&self) -> Uint8Array {
+		// SAFETY: the buffer lives as long as the response, and the caller reads the
+		// view before anything else can allocate in the module
+		unsafe { Uint8Array::view(&self.0) }
+	}
+}
+
+#[derive(Serialize)]
+struct BulkLoadProgress {
+	rows: u64,
+	loaded: u64,
+	failed: usize,
+}
+
+#[derive(Serialize)]
+struct BulkLoadFailure {
+	row: u64,
+	error: String,
+}
+
+#[derive(Serialize)]
+struct BulkLoadReport {
+	rows: u64,
+	loaded: u64,
+	failures: Vec<BulkLoadFailure>,
+}
+
+#[derive(Serialize)]
+struct SyncReport {
+	pulled: u64,
+	pushed: u64,
+	conflicts: u64,
+}
 
 #[wasm_bindgen]
 impl SurrealWasmEngine {
@@ -44,58 +125,158 @@ impl SurrealWasmEngine {
 	/// Using `&mut self` here causes "Unreachable code" panics when the engine is still
 	/// considered borrowed (e.g. by the notification stream or async completion), before
 	/// the function body runs.
-	pub async fn execute(&self, data: Uint8Array) -> Result<Uint8Array, Error> {
+	///
+	/// The optional `format` selects the wire format of the request and response, and
+	/// accepts either `"cbor"` (the default) or `"json"`. The response is read through
+	/// a view of linear memory, and must be freed once read.
+	pub async fn execute(
+		&self,
+		data: Uint8Array,
+		format: Option<String>,
+	) -> Result<EncodedResponse, Error> {
+		let format = Format::parse(format.as_deref())?;
+		// The module cannot read JavaScript memory, so the request is copied into
+		// linear memory once and decoded from there
 		let data = data.to_vec();
-		let obj = cbor::decode(data.as_slice()).map_err(|e| e.to_string())?.into_object()?;
-		let req = Request::from_object(obj)?;
-		let res = RpcProtocol::execute(
-			&self.0,
-			req.txn.map(Into::into),
-			req.session_id.map(Into::into),
-			req.method,
-			req.params,
-		)
-		.await;
+		let req = format.request(data.as_slice())?;
+		// The response is read in place through a view, rather than copied out
+		Ok(EncodedResponse(self.handle(format, req).await?))
+	}
 
-		match res {
-			Ok(result) => {
-				let value = Value::from_t(result);
-				let out = cbor::encode(value).map_err(|e| e.to_string())?;
-				Ok(out.as_slice().into())
+	/// Executes many requests in a single call, returning one response per request.
+	///
+	/// Requests run in order unless `concurrent` is set. A malformed request is
+	/// answered with an error envelope in its own slot, and the others still run.
+	pub async fn execute_batch(
+		&self,
+		data: Vec<Uint8Array>,
+		concurrent: Option<bool>,
+		format: Option<String>,
+	) -> Result<Vec<Uint8Array>, Error> {
+		let format = Format::parse(format.as_deref())?;
+		let reqs = data
+			.iter()
+			.map(|data| {
+				format.request(data.to_vec().as_slice()).map_err(|err| {
+					let err = JsValue::from(err).as_string().unwrap_or_default();
+					surrealdb_core::rpc::invalid_params(&err)
+				})
+			})
+			.collect::<Vec<_>>();
+		let run = |req: Result<Request, surrealdb_types::Error>| async move {
+			match req {
+				Ok(req) => self.handle(format, req).await,
+				Err(err) => format.encode(error_envelope(err)),
 			}
-			Err(rpc_err) => {
-				let mut envelope = surrealdb_types::Object::default();
-				envelope.insert("error".to_string(), Value::from_t(rpc_err));
-				let out = cbor::encode(Value::Object(envelope)).map_err(|e| e.to_string())?;
-				Ok(out.as_slice().into())
+		};
+
+		let out = if concurrent.unwrap_or(false) {
+			join_all(reqs.into_iter().map(run)).await.into_iter().collect::<Result<Vec<_>, _>>()?
+		} else {
+			let mut out = Vec::with_capacity(reqs.len());
+			for req in reqs {
+				out.push(run(req).await?);
 			}
-		}
+			out
+		};
+
+		Ok(out.iter().map(|out| out.as_slice().into()).collect())
 	}
 
-	pub fn notifications(&self) -> Result<sys::ReadableStream, Error> {
+	/// Executes a request, streaming its result back as a sequence of chunks.
+	///
+	/// Query results are sent per statement, with array results split into batches
+	/// of `batch_size` records. Independent statements of a query execute one at a
+	/// time, and chunks are only encoded, as the stream is pulled, so a slow reader
+	/// applies backpressure rather than buffering the whole response. An error
+	/// envelope ends the stream.
+	pub async fn execute_stream(
+		&self,
+		data: Uint8Array,
+		batch_size: Option<u32>,
+		format: Option<String>,
+	) -> Result<sys::ReadableStream, Error> {
+		let format = Format::parse(format.as_deref())?;
+		let batch_size = batch_size.map_or(stream::DEFAULT_BATCH_SIZE, |b| b as usize);
+		let req = format.request(data.to_vec().as_slice())?;
+		let is_query = req.method.to_str() == "query";
+		let requests = stream::requests(req).into_iter().enumerate();
+		let state = (requests, Rc::clone(&self.0), Rc::clone(&self.1));
+
+		let frames = futures::stream::unfold(Some(state), move |state| async move {
+			let (mut requests, connection, interceptors) = state?;
+			let (offset, req) = requests.next()?;
+			let response = interceptors.run(req, |req| connection.record(req)).await;
+			let next = response.is_ok().then_some((requests, connection, interceptors));
+			let frames = stream::frames(response, is_query, offset, batch_size);
+			Some((futures::stream::iter(frames), next))
+		});
+
+		let response = frames.flatten().map(move |frame| {
+			let out = format.encode(frame)?;
+			let out_arr: Uint8Array = out.as_slice().into();
+			Ok::<JsValue, JsValue>(out_arr.into())
+		});
+
+		Ok(ReadableStream::from_stream(response).into_raw())
+	}
+
+	/// Streams live query notifications in the requested wire format.
+	///
+	/// When the endpoint is an IndexedDB store, notifications are also exchanged
+	/// with the other engines opening the same store, so live queries fire for
+	/// writes made in any tab or worker.
+	pub fn notifications(&self, format: Option<String>) -> Result<sys::ReadableStream, Error> {
+		let format = Format::parse(format.as_deref())?;
 		let stream = self.0.kvs.notifications().ok_or("Notifications not enabled")?;
+		let broadcast = self.0.broadcast.as_deref().and_then(|name| Broadcast::open(name).ok());
+		let (broadcast, remote) = match broadcast {
+			Some((broadcast, remote)) => (Some(broadcast), Some(remote)),
+			None => (None, None),
+		};
 
-		fn process_notification(notification: Notification) -> Result<JsValue, JsValue> {
-			// Into CBOR value
+		// Forward notifications for live queries owned by other engines
+		let live_queries = Arc::clone(&self.0.live_queries);
+		let local = stream.filter_map(move |notification| {
 			let value = Value::from_t(notification);
+			let owned = broadcast::live_query_id(&value)
+				.is_some_and(|id| live_queries.read().unwrap().contains_key(&id));
 
-			let res = cbor::encode(value).map_err(|e| e.to_string())?;
-			let out_arr: Uint8Array = res.as_slice().into();
+			let value = match &broadcast {
+				Some(broadcast) if !owned => {
+					if let Ok(data) = cbor::encode(value) {
+						broadcast.post(&data);
+					}
+					None
+				}
+				_ => Some(value),
+			};
 
-			Ok(out_arr.into())
-		}
+			futures::future::ready(value)
+		});
 
-		let response = stream.map(process_notification);
+		// Deliver notifications posted by other engines for the live queries of this one
+		let live_queries = Arc::clone(&self.0.live_queries);
+		let remote = futures::stream::iter(remote).flatten().filter_map(move |data| {
+			let value = cbor::decode(&data).ok().filter(|value| {
+				broadcast::live_query_id(value)
+					.is_some_and(|id| live_queries.read().unwrap().contains_key(&id))
+			});
+
+			futures::future::ready(value)
+		});
+
+		let response = futures::stream::select(local, remote).map(move |value| {
+			// Into the requested wire format
+			let res = format.encode(value)?;
+			let out_arr: Uint8Array = res.as_slice().into();
+			Ok::<JsValue, JsValue>(out_arr.into())
+		});
 
 		Ok(ReadableStream::from_stream(response).into_raw())
 	}
 
 	pub async fn connect(endpoint: String, opts: JsValue) -> Result<SurrealWasmEngine, Error> {
-		let endpoint = match &endpoint {
-			s if s.starts_with("mem:") => "memory",
-			s => s,
-		};
-
 		// Avoid from_value(undefined): that path can trigger a wasm_bindgen closure that panics
 		// (Unreachable) when used with panic=abort. Handle undefined/null explicitly.
 		let opts: Option<Options> = if opts.is_undefined() || opts.is_null() {
@@ -103,10 +284,33 @@ impl SurrealWasmEngine {
 		} else {
 			Some(from_value::<Options>(opts)?)
 		};
+		let endpoint =
+			storage::endpoint(&endpoint, opts.as_ref().and_then(|o| o.storage.as_ref()))?;
+		let broadcast = broadcast::channel_name(&endpoint);
 		let defaults = opts.as_ref().and_then(|o| o.defaults.clone()).unwrap_or_default();
 
+		if let Some(filter) = opts.as_ref().and_then(|o| o.log_filter.as_deref()) {
+			logs::set_filter(filter)?;
+		}
+
+		let slow_log = opts.as_ref().and_then(|o| {
+			let planner = o.capabilities.as_ref().map(|c| c.planner_strategy()).unwrap_or_default();
+			o.slow_queries.clone().map(|s| Arc::new(SlowLog::new(s, planner.as_str())))
+		});
+		let audit = opts.as_ref().and_then(|o| o.audit.clone()).map(Audit::new);
+		let pin = opts.as_ref().and_then(|o| o.pin.clone());
+		let read_only = opts.as_ref().and_then(|o| o.read_only).unwrap_or(false);
+		if read_only {
+			readonly::check_backend(&endpoint)?;
+		}
+		let recorder = opts
+			.as_ref()
+			.and_then(|o| o.record)
+			.unwrap_or(false)
+			.then(|| Recorder::new(Vec::new()));
+
 		wasm_trace!("[wasm] creating datastore at {endpoint}");
-		let kvs = Datastore::new(endpoint).await?;
+		let kvs = Datastore::new(&endpoint).await?;
 		wasm_trace!("[wasm] enabling notifications");
 		let kvs = kvs.with_notifications();
 		wasm_trace!("[wasm] configuring datastore");
@@ -122,15 +326,18 @@ impl SurrealWasmEngine {
 				.with_query_timeout(opts.query_timeout.map(|qt| Duration::from_secs(qt as u64))),
 		};
 
-		wasm_trace!("[wasm] checking version");
-		let (_, is_new) = kvs.check_version().await?;
-		wasm_trace!("[wasm] version checked, is_new={is_new}");
+		// A read-only engine neither upgrades the storage version nor writes the defaults
+		if !read_only {
+			wasm_trace!("[wasm] checking version");
+			let (_, is_new) = kvs.check_version().await?;
+			wasm_trace!("[wasm] version checked, is_new={is_new}");
 
-		if is_new {
-			if let Some(defaults) = defaults.get_defaults() {
-				wasm_trace!("[wasm] initialising defaults");
-				kvs.initialise_defaults(&defaults.0, &defaults.1).await?;
-				wasm_trace!("[wasm] defaults initialised");
+			if is_new {
+				if let Some(defaults) = defaults.get_defaults() {
+					wasm_trace!("[wasm] initialising defaults");
+					kvs.initialise_defaults(&defaults.0, &defaults.1).await?;
+					wasm_trace!("[wasm] defaults initialised");
+				}
 			}
 		}
 
@@ -139,23 +346,104 @@ impl SurrealWasmEngine {
 			kvs: Arc::new(kvs),
 			live_queries: Arc::new(StdRwLock::new(HashMap::new())),
 			transactions: Default::default(),
+			transaction_started: Default::default(),
 			sessions: Default::default(),
+			broadcast,
+			slow_log,
+			audit,
+			recorder,
+			pin,
+			pins: DashMap::new(),
+			read_only,
+			endpoint,
 		};
 
 		// Store the default session
-		let session = Session::default().with_rt(true);
+		let mut session = Session::default().with_rt(true);
+		if let Some(pin) = &connection.pin {
+			pin.select(&mut session);
+		}
 		connection.set_session(None, Arc::new(RwLock::new(session)));
 
-		Ok(SurrealWasmEngine(connection))
+		Ok(SurrealWasmEngine(Rc::new(connection), Rc::default()))
 	}
 
-	pub async fn export(&self, config: Option<Uint8Array>) -> Result<String, Error> {
-		let (tx, rx) = channel::unbounded();
+	/// Loads NDJSON, CSV or CBOR sequence records from a stream into a table.
+	///
+	/// Records are written in batched transactions, and the optional `progress`
+	/// callback receives the row counts after each chunk has been written.
+	pub async fn bulk_load(
+		&self,
+		table: String,
+		format: String,
+		stream: sys::ReadableStream,
+		opts: JsValue,
+		progress: Option<Function>,
+	) -> Result<JsValue, Error> {
+		self.0.check_writable("load records")?;
+		let opts: BulkLoadOptions = if opts.is_undefined() || opts.is_null() {
+			BulkLoadOptions::default()
+		} else {
+			from_value(opts)?
+		};
+
+		let mut loader = BulkLoader::new(table, &format, opts)?;
 
 		let Some(session) = self.0.sessions.get(&None) else {
 			return Err(Error::from("session not found"));
 		};
 
+		let session = session.read().await;
+		let mut chunks = ReadableStream::from_raw(stream).into_stream();
+
+		while let Some(chunk) = chunks.next().await {
+			let chunk = chunk?
+				.dyn_into::<Uint8Array>()
+				.map_err(|_| "Expected a stream of Uint8Array chunks")?;
+
+			loader.write(&self.0.kvs, &session, chunk.to_vec().as_slice()).await?;
+
+			if let Some(progress) = &progress {
+				let value = to_value(&BulkLoadProgress {
+					rows: loader.rows,
+					loaded: loader.loaded,
+					failed: loader.failures.len(),
+				})?;
+				progress.call1(&JsValue::NULL, &value)?;
+			}
+		}
+
+		loader.finish(&self.0.kvs, &session).await?;
+
+		let report = BulkLoadReport {
+			rows: loader.rows,
+			loaded: loader.loaded,
+			failures: loader
+				.failures
+				.into_iter()
+				.map(|f| BulkLoadFailure {
+					row: f.row,
+					error: f.error,
+				})
+				.collect(),
+		};
+
+		Ok(to_value(&report)?)
+	}
+
+	/// Exports the database selected by a session, or by the default session when
+	/// `session` is `null`, as SurrealQL
+	pub async fn export(
+		&self,
+		config: Option<Uint8Array>,
+		session: Option<String>,
+	) -> Result<String, Error> {
+		let (tx, rx) = channel::unbounded();
+
+		let Some(session) = self.0.sessions.get(&session_id(session)?) else {
+			return Err(Error::from("session not found"));
+		};
+
 		let session = session.read().await;
 
 		match config {
@@ -180,31 +468,621 @@ impl SurrealWasmEngine {
 		Ok(result)
 	}
 
-	pub async fn import(&self, input: String) -> Result<(), Error> {
+	/// Exports table records as NDJSON, JSON arrays or CBOR sequences.
+	///
+	/// Returns a stream of `{ table, data }` chunks, reading each page of records
+	/// as the stream is pulled. When `schema` is set, the first chunk has a `null`
+	/// table and holds the table definitions as SurrealQL.
+	pub fn export_data(&self, format: String, opts: JsValue) -> Result<sys::ReadableStream, Error> {
+		let opts: DataExportOptions = if opts.is_undefined() || opts.is_null() {
+			DataExportOptions::default()
+		} else {
+			from_value(opts)?
+		};
+
+		let export = DataExport::new(&format, opts)?;
+		let kvs = Arc::clone(&self.0.kvs);
+		let Some(session) = self.0.sessions.get(&None) else {
+			return Err(Error::from("session not found"));
+		};
+		let session = session.clone();
+
+		let response = futures::stream::unfold(Some(export), move |export| {
+			let kvs = Arc::clone(&kvs);
+			let session = Arc::clone(&session);
+			async move {
+				let mut export = export?;
+				let session = session.read().await;
+				match export.next(&kvs, &session).await {
+					Ok(Some(chunk)) => {
+						let out = Object::new();
+						let table = chunk.table.map_or(JsValue::NULL, JsValue::from);
+						let data: Uint8Array = chunk.data.as_slice().into();
+						let _ = Reflect::set(&out, &"table".into(), &table);
+						let _ = Reflect::set(&out, &"data".into(), &data);
+						Some((Ok(out.into()), Some(export)))
+					}
+					Ok(None) => None,
+					Err(err) => Some((Err(JsValue::from(err)), None)),
+				}
+			}
+		});
+
+		Ok(ReadableStream::from_stream(response).into_raw())
+	}
+
+	/// Exports a table or query result as an Arrow IPC stream
+	pub async fn export_columnar(
+		&self,
+		format: String,
+		opts: JsValue,
+	) -> Result<Uint8Array, Error> {
+		let format = ColumnarFormat::parse(&format)?;
+		let opts = from_value::<ColumnarExportOptions>(opts)?;
+		// A query may contain statements which write, and runs outside of a read transaction
+		if let Some(query) = &opts.query {
+			self.0.check_writable("export the result of a query")?;
+			if let Some(pin) = self.0.pin(None) {
+				pin.check_query(query)?;
+			}
+		}
+
+		let Some(session) = self.0.sessions.get(&None) else {
+			return Err(Error::from("session not found"));
+		};
+
+		let session = session.read().await;
+		let out = columnar::export(&self.0.kvs, &session, format, opts).await?;
+
+		Ok(out.as_slice().into())
+	}
+
+	/// Exports the change feeds after a versionstamp as a replayable delta file.
+	///
+	/// Resolves to `{ data, versionstamp }`, where `versionstamp` is the highest
+	/// exported versionstamp as a decimal string, or `null` when there were no
+	/// changes.
+	pub async fn export_changes(&self, opts: JsValue) -> Result<JsValue, Error> {
+		let opts: ChangesExportOptions = if opts.is_undefined() || opts.is_null() {
+			ChangesExportOptions::default()
+		} else {
+			from_value(opts)?
+		};
+
+		let Some(session) = self.0.sessions.get(&None) else {
+			return Err(Error::from("session not found"));
+		};
+
+		let session = session.read().await;
+		let delta = changes::export(&self.0.kvs, &session, opts).await?;
+
+		let out = Object::new();
+		let data: Uint8Array = delta.data.as_slice().into();
+		let versionstamp =
+			delta.versionstamp.map_or(JsValue::NULL, |v| JsValue::from(v.to_string()));
+		let _ = Reflect::set(&out, &"data".into(), &data);
+		let _ = Reflect::set(&out, &"versionstamp".into(), &versionstamp);
+
+		Ok(out.into())
+	}
+
+	/// Applies a delta file in a single transaction, returning its highest versionstamp
+	pub async fn import_changes(&self, data: Uint8Array) -> Result<String, Error> {
+		self.0.check_writable("apply changes")?;
+		let Some(session) = self.0.sessions.get(&None) else {
+			return Err(Error::from("session not found"));
+		};
+
+		let session = session.read().await;
+		let versionstamp = changes::apply(&self.0.kvs, &session, &data.to_vec()).await?;
+
+		Ok(versionstamp.to_string())
+	}
+
+	/// Returns the queries which exceeded the slow query threshold, from the oldest
+	pub fn slow_queries(&self) -> Result<JsValue, Error> {
+		let records = self.0.slow_log.as_ref().map(|log| log.records()).unwrap_or_default();
+
+		let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+		Ok(records.serialize(&serializer)?)
+	}
+
+	/// Streams every slow query recorded from now on
+	pub fn slow_query_stream(&self) -> Result<sys::ReadableStream, Error> {
+		let log = self.0.slow_log.as_ref().ok_or("The slow query log is not enabled")?;
+
+		let response = log.subscribe().map(|record| {
+			let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+			Ok::<JsValue, JsValue>(record.serialize(&serializer)?)
+		});
+
+		Ok(ReadableStream::from_stream(response).into_raw())
+	}
+
+	/// Streams the audit records, from a channel shared by every caller
+	pub fn audit_stream(&self) -> Result<sys::ReadableStream, Error> {
+		let audit = self.0.audit.as_ref().ok_or("Auditing is not enabled")?;
+
+		let response = audit.receiver().map(|record| {
+			let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+			Ok::<JsValue, JsValue>(record.serialize(&serializer)?)
+		});
+
+		Ok(ReadableStream::from_stream(response).into_raw())
+	}
+
+	/// Describes the active sessions with their live queries, and the open transactions
+	pub async fn inspect(&self) -> Result<JsValue, Error> {
+		let inspection = inspect::inspect(
+			&self.0.sessions,
+			&self.0.live_queries,
+			&self.0.transactions,
+			&self.0.transaction_started,
+		)
+		.await;
+
+		let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+		Ok(inspection.serialize(&serializer)?)
+	}
+
+	/// Kills a live query, returning whether it was found
+	pub async fn kill_live_query(&self, id: String) -> Result<bool, Error> {
+		let id = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+
+		if self.0.live_queries.write().unwrap().remove(&id).is_none() {
+			return Ok(false);
+		}
+		self.0.kvs.delete_queries(vec![id]).await?;
+
+		Ok(true)
+	}
+
+	/// Cancels an open transaction, returning whether it was found
+	pub async fn cancel_transaction(&self, id: String) -> Result<bool, Error> {
+		let id = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+
+		let Some((_, tx)) = self.0.transactions.remove(&id) else {
+			return Ok(false);
+		};
+		self.0.transaction_started.remove(&id);
+		tx.cancel().await?;
+
+		Ok(true)
+	}
+
+	/// Measures the key counts and stored sizes of every namespace, database,
+	/// table and index within the pin of the engine, or only those selected by
+	/// the options, along with details of the storage backend.
+	///
+	/// Every key of each measured table is read, so measuring a whole datastore
+	/// is best kept to debugging, while single tables can be measured with `tables`.
+	pub async fn stats(&self, opts: JsValue) -> Result<JsValue, Error> {
+		let opts: StatsOptions = if opts.is_undefined() || opts.is_null() {
+			StatsOptions::default()
+		} else {
+			from_value(opts)?
+		};
+		let mut stats = stats::collect(&self.0.kvs, self.0.pin(None).as_ref(), &opts).await?;
+		stats.backend = Some(stats::backend(&self.0.endpoint).await);
+
+		// Serialize maps as plain objects rather than a `Map`
+		let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+		Ok(stats.serialize(&serializer)?)
+	}
+
+	/// Syncs the change feeds with a peer through the supplied transport.
+	///
+	/// `pull` receives the remote versionstamp of the last sync, as a decimal
+	/// string, and resolves to a delta file of the remote changes since then,
+	/// while `push` receives a delta file of the local changes. With the `custom`
+	/// strategy, `resolve` receives each conflict as CBOR and resolves to the
+	/// merged record, or `null` to delete it. Resolves to `{ pulled, pushed, conflicts }`.
+	pub async fn sync(
+		&self,
+		peer: String,
+		pull: Function,
+		push: Function,
+		opts: JsValue,
+		resolve: Option<Function>,
+	) -> Result<JsValue, Error> {
+		self.0.check_writable("sync with a peer")?;
+		let opts: SyncOptions = if opts.is_undefined() || opts.is_null() {
+			SyncOptions::default()
+		} else {
+			from_value(opts)?
+		};
+
 		let Some(session) = self.0.sessions.get(&None) else {
 			return Err(Error::from("session not found"));
 		};
 
 		let session = session.read().await;
 
+		let pull = |since: u64| async move {
+			let data = call_async(&pull, &JsValue::from(since.to_string())).await?;
+			let data = data
+				.dyn_into::<Uint8Array>()
+				.map_err(|_| "Expected pull to return a Uint8Array")?;
+			Ok(data.to_vec())
+		};
+
+		let push = |delta: Vec<u8>| async move {
+			let delta: Uint8Array = delta.as_slice().into();
+			call_async(&push, &delta).await.map(|_| ())
+		};
+
+		let resolve = |conflict: Value| {
+			let resolve = resolve.as_ref();
+			async move {
+				let Some(resolve) = resolve else {
+					return Err("The custom conflict strategy requires a resolver".to_string());
+				};
+				let conflict = cbor::encode(conflict).map_err(|e| e.to_string())?;
+				let conflict: Uint8Array = conflict.as_slice().into();
+				let record = call_async(resolve, &conflict).await?;
+				if record.is_null() || record.is_undefined() {
+					return Ok(None);
+				}
+				let record = record
+					.dyn_into::<Uint8Array>()
+					.map_err(|_| "Expected resolve to return a Uint8Array or null")?;
+				cbor::decode(&record.to_vec()).map(Some).map_err(|e| e.to_string())
+			}
+		};
+
+		let report = sync::sync(&self.0.kvs, &session, &peer, opts, pull, push, resolve).await?;
+
+		Ok(to_value(&SyncReport {
+			pulled: report.pulled,
+			pushed: report.pushed,
+			conflicts: report.conflicts,
+		})?)
+	}
+
+	/// Imports SurrealQL into the database selected by a session, or by the default
+	/// session when `session` is `null`
+	pub async fn import(&self, input: String, session: Option<String>) -> Result<(), Error> {
+		self.0.check_writable("import data")?;
+		let session_id = session_id(session)?;
+		if let Some(pin) = self.0.pin(session_id) {
+			pin.check_query(&input)?;
+		}
+
+		let Some(session) = self.0.sessions.get(&session_id) else {
+			return Err(Error::from("session not found"));
+		};
+
+		let session = session.read().await;
+
 		self.0.kvs.import(&input, &session).await?;
 
 		Ok(())
 	}
 
+	/// Sends the tracing records of every engine on this thread to a callback,
+	/// or stops sending them when `handler` is `null`. Fails when a global
+	/// tracing subscriber of another library is already set.
+	pub fn set_log_handler(handler: Option<Function>) -> Result<(), Error> {
+		Ok(logs::set_handler(handler)?)
+	}
+
+	/// Replaces the tracing filter with directives such as `surrealdb_core::dbs=debug`
+	pub fn set_log_filter(directives: String) -> Result<(), Error> {
+		Ok(logs::set_filter(&directives)?)
+	}
+
+	/// Returns the log of requests and their results recorded with the `record` option
+	pub fn recording(&self) -> Result<Uint8Array, Error> {
+		let recorder = self.0.recorder.as_ref().ok_or("Recording is not enabled")?;
+		Ok(recorder.contents().as_slice().into())
+	}
+
+	/// Re-executes a recorded log against a fresh engine, reporting the requests
+	/// whose result differs from the recorded one.
+	///
+	/// The engine is opened at `endpoint`, defaulting to an in-memory datastore.
+	pub async fn replay(
+		log: Uint8Array,
+		endpoint: Option<String>,
+		opts: JsValue,
+	) -> Result<JsValue, Error> {
+		let engine = Self::connect(endpoint.unwrap_or("mem://".to_string()), opts).await?;
+		let report = recording::replay(&log.to_vec(), |req| engine.0.envelope(req)).await?;
+
+		let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+		Ok(report.serialize(&serializer)?)
+	}
+
+	/// Returns whether the datastore at an endpoint was written with an older
+	/// storage format, and must be upgraded before it can be connected to
+	pub async fn needs_upgrade(endpoint: String) -> Result<bool, Error> {
+		let kvs = Datastore::new(&endpoint).await?;
+		Ok(upgrade::needs_upgrade(&kvs).await?)
+	}
+
+	/// Migrates the storage format of the datastore at an endpoint to the latest
+	/// version, in place or into an empty datastore at `target`, leaving the
+	/// original untouched. The datastore must not be open in another engine.
+	///
+	/// The optional `progress` callback receives each stage of the upgrade.
+	pub async fn upgrade(
+		endpoint: String,
+		target: Option<String>,
+		progress: Option<Function>,
+	) -> Result<JsValue, Error> {
+		if target.as_ref() == Some(&endpoint) {
+			return Err(Error::from("The target of an upgrade must differ from the datastore"));
+		}
+
+		let source = Datastore::new(&endpoint).await?;
+		let target = match target {
+			Some(target) => Some(Datastore::new(&target).await?),
+			None => None,
+		};
+		let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+		let report = upgrade::upgrade(&source, target.as_ref(), |stage| {
+			if let Some(progress) = &progress {
+				let stage = stage.serialize(&serializer).map_err(|e| e.to_string())?;
+				progress.call1(&JsValue::NULL, &stage).map_err(|e| format!("{e:?}"))?;
+			}
+			Ok(())
+		})
+		.await?;
+
+		Ok(report.serialize(&serializer)?)
+	}
+
+	/// Deletes the IndexedDB database of an endpoint, once every engine which
+	/// opened it has been freed. When it is still open elsewhere, this fails and
+	/// the deletion goes ahead once it is closed.
+	pub async fn destroy(endpoint: String) -> Result<(), Error> {
+		Ok(destroy::destroy(&endpoint).await?)
+	}
+
+	/// Restricts a session, or the default session when `session` is `null`, to a
+	/// namespace and optionally a database, within the pin of the engine.
+	///
+	/// Requests, `USE` statements and imports selecting anything outside the pin
+	/// fail with a permission error. A pin cannot be removed once set.
+	pub async fn pin_session(&self, session: Option<String>, pin: JsValue) -> Result<(), Error> {
+		let session_id = session_id(session)?;
+		let pin = from_value::<Pin>(pin)?;
+
+		if let Some(current) = self.0.pin(session_id) {
+			if !current.contains(&pin) {
+				return Err(pin::denied(format!(
+					"The session is pinned to namespace {}",
+					current.namespace
+				))
+				.into());
+			}
+		}
+
+		if let Some(session) = self.0.sessions.get(&session_id).map(|s| s.clone()) {
+			pin.select(&mut *session.write().await);
+		}
+		self.0.pins.insert(session_id, pin);
+
+		Ok(())
+	}
+
+	/// Creates a session with the given id, unless it exists already. Like the
+	/// default session, it is restricted to the pin of the engine.
+	pub async fn attach_session(&self, session: String) -> Result<(), Error> {
+		let session_id = session_id(Some(session))?;
+
+		if self.0.sessions.get(&session_id).is_none() {
+			let mut session = Session::default().with_rt(true);
+			if let Some(pin) = &self.0.pin {
+				pin.select(&mut session);
+			}
+			self.0.set_session(session_id, Arc::new(RwLock::new(session)));
+		}
+
+		Ok(())
+	}
+
+	/// Adds an interceptor at the end of the chain run around every request.
+	///
+	/// `before` receives the CBOR encoded request and resolves to `{ request }` to
+	/// replace it, `{ response }` to respond without executing it, or `null` to
+	/// continue. `after` receives `{ request, response }` and resolves to
+	/// `{ response }` to replace the response, or `null` to keep it.
+	pub fn add_interceptor(&self, before: Option<Function>, after: Option<Function>) {
+		self.intercept(Rc::new(JsInterceptor {
+			before,
+			after,
+		}));
+	}
+
 	pub fn version() -> Result<String, Error> {
 		Ok(env!("SURREALDB_VERSION").into())
 	}
 }
 
+impl SurrealWasmEngine {
+	/// Adds an interceptor, implemented in Rust, at the end of the chain run
+	/// around every request
+	pub fn intercept(&self, interceptor: Rc<dyn Interceptor>) {
+		self.1.push(interceptor);
+	}
+
+	/// Executes a decoded request through the interceptors, encoding either its
+	/// result or an error envelope
+	async fn handle(&self, format: Format, req: Request) -> Result<Vec<u8>, Error> {
+		let response = self.1.run(req, |req| self.0.record(req)).await;
+		format.encode(response.unwrap_or_else(|envelope| envelope))
+	}
+}
+
 struct SurrealWasmConnection {
 	pub kvs: Arc<Datastore>,
 	pub live_queries: Arc<StdRwLock<HashMap<Uuid, Option<Uuid>>>>,
 	pub transactions: DashMap<Uuid, Arc<Transaction>>,
+	/// When each open transaction began, in milliseconds since the Unix epoch
+	pub transaction_started: DashMap<Uuid, f64>,
 	pub sessions: HashMap<Option<Uuid>, Arc<RwLock<Session>>>,
+	/// The channel used to share notifications with engines opening the same store
+	pub broadcast: Option<String>,
+	/// The slow query log, when enabled in the connection options
+	pub slow_log: Option<Arc<SlowLog>>,
+	/// The audit channel, when enabled in the connection options
+	pub audit: Option<Audit>,
+	/// The log of requests and their results, when enabled in the connection options
+	pub recorder: Option<Recorder<Vec<u8>>>,
+	/// The namespace and database every session is restricted to, when set
+	pub pin: Option<Pin>,
+	/// The restrictions of individual sessions, within the pin of the engine
+	pub pins: DashMap<Option<Uuid>, Pin>,
+	/// Whether every write is rejected
+	pub read_only: bool,
+	/// The endpoint the datastore was opened with
+	pub endpoint: String,
 }
 
 impl SurrealWasmConnection {
+	/// Returns the pin restricting a session, if any
+	fn pin(&self, session_id: Option<Uuid>) -> Option<Pin> {
+		self.pins.get(&session_id).map(|pin| pin.clone()).or_else(|| self.pin.clone())
+	}
+
+	/// Rejects an operation which writes when the engine is read-only
+	fn check_writable(&self, action: &str) -> Result<(), surrealdb_types::Error> {
+		match self.read_only {
+			true => Err(readonly::denied(action)),
+			false => Ok(()),
+		}
+	}
+
+	/// Executes a request without writing, rejecting the methods which write and
+	/// running queries outside of a transaction in a read transaction, which is
+	/// cancelled once they finish
+	async fn execute_read_only(
+		&self,
+		txn: Option<Uuid>,
+		session_id: Option<Uuid>,
+		req: Request,
+	) -> Result<DbResult, surrealdb_types::Error> {
+		let method = req.method.to_str();
+		if readonly::is_write(method) {
+			return Err(readonly::denied(&format!("run the {method} method")));
+		}
+		if txn.is_some() || !readonly::is_query(method) {
+			return self.execute_pinned(txn, session_id, req).await;
+		}
+
+		let tx = self
+			.kvs
+			.transaction(TransactionType::Read, LockType::Optimistic)
+			.await
+			.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
+		let tx = Arc::new(tx);
+		let id = Uuid::now_v7();
+		self.transactions.insert(id, tx.clone());
+		let res = self.execute_pinned(Some(id), session_id, req).await;
+		self.transactions.remove(&id);
+		let _ = tx.cancel().await;
+		res
+	}
+
+	/// Executes a request, keeping its session within its pin
+	async fn execute_pinned(
+		&self,
+		txn: Option<Uuid>,
+		session_id: Option<Uuid>,
+		req: Request,
+	) -> Result<DbResult, surrealdb_types::Error> {
+		let Some(pin) = self.pin(session_id) else {
+			return RpcProtocol::execute(self, txn, session_id, req.method, req.params).await;
+		};
+
+		pin.check(req.method.to_str(), &req.params)?;
+		let session = self.sessions.get(&session_id).map(|s| s.clone());
+		if let Some(session) = &session {
+			pin.select(&mut *session.write().await);
+		}
+
+		let res = RpcProtocol::execute(self, txn, session_id, req.method, req.params).await;
+
+		// Requests selecting anything outside the pin are rejected above, but a reset
+		// clears the selection and an authentication token may carry its own, so
+		// select the pin again
+		let session = session.or_else(|| self.sessions.get(&session_id).map(|s| s.clone()));
+		if let Some(session) = session {
+			pin.select(&mut *session.write().await);
+		}
+
+		res
+	}
+
+	/// Executes a decoded request against this connection
+	async fn run(&self, req: Request) -> Result<DbResult, surrealdb_types::Error> {
+		let method = req.method.to_str().to_string();
+		let session_id: Option<Uuid> = req.session_id.map(Into::into);
+		let txn: Option<Uuid> = req.txn.map(Into::into);
+		let captured = self.slow_log.as_ref().and_then(|log| log.capture(&req));
+		let start = inspect::now();
+		let res = match self.read_only {
+			true => self.execute_read_only(txn, session_id, req).await,
+			false => self.execute_pinned(txn, session_id, req).await,
+		};
+		let duration = (inspect::now() - start).max(0.0);
+
+		if let Some(audit) = &self.audit {
+			let error = res.as_ref().err().map(audit::error_kind);
+			let session = self.sessions.get(&session_id).map(|s| s.clone());
+			let session = match &session {
+				Some(session) => Some(session.read().await),
+				None => None,
+			};
+			audit.record(&method, session_id, session.as_deref(), txn, error, duration);
+		}
+
+		if let (Some(log), Some(captured)) = (&self.slow_log, captured) {
+			let elapsed = Duration::from_secs_f64(duration / 1000.0);
+			if log.is_slow(elapsed) {
+				// Record it in the background, as capturing the plan runs another query
+				let log = log.clone();
+				let kvs = self.kvs.clone();
+				let session = self.sessions.get(&session_id).map(|s| s.clone());
+				let failed = res.is_err();
+				spawn_local(async move {
+					if let Some(session) = session {
+						let session = session.read().await.clone();
+						log.record(&kvs, &session, captured, elapsed, failed).await;
+					}
+				});
+			}
+		}
+
+		res
+	}
+
+	/// Executes a decoded request, resolving to its result or to its error envelope
+	async fn respond(&self, req: Request) -> Result<Value, Value> {
+		self.run(req).await.map(Value::from_t).map_err(error_envelope)
+	}
+
+	/// Executes a decoded request, returning either its result or an error envelope
+	async fn envelope(&self, req: Request) -> Value {
+		self.respond(req).await.unwrap_or_else(|envelope| envelope)
+	}
+
+	/// Executes a decoded request, recording it along with its result when enabled
+	async fn record(&self, req: Request) -> Result<Value, Value> {
+		let entry = self.recorder.as_ref().map(|_| recording::entry(&req));
+		let response = self.respond(req).await;
+
+		if let (Some(recorder), Some(entry)) = (&self.recorder, entry) {
+			let (Ok(value) | Err(value)) = &response;
+			if let Err(err) = recorder.record(entry, value) {
+				tracing::warn!("Failed to record a request: {err}");
+			}
+		}
+
+		response
+	}
+
 	/// Runs a sync retain on live_queries, then deletes collected query ids on kvs.
 	/// Wrapped in AssertSend so the future satisfies the trait’s Send bound (WASM is single-threaded).
 	fn cleanup_live_queries_async<F>(&self, retain: F) -> impl Future<Output = ()> + Send
@@ -229,6 +1107,25 @@ impl SurrealWasmConnection {
 	}
 }
 
+/// Wraps an RPC error in the envelope returned to the SDK
+fn error_envelope(rpc_err: surrealdb_types::Error) -> Value {
+	let mut envelope = surrealdb_types::Object::default();
+	envelope.insert("error".to_string(), Value::from_t(rpc_err));
+	Value::Object(envelope)
+}
+
+/// Parses the id of a session, where `None` is the default session
+fn session_id(session: Option<String>) -> Result<Option<Uuid>, Error> {
+	Ok(session.map(|id| Uuid::parse_str(&id)).transpose().map_err(|e| e.to_string())?)
+}
+
+/// Calls a JS callback with one argument, awaiting the result when it returns a promise
+async fn call_async(callback: &Function, arg: &JsValue) -> Result<JsValue, String> {
+	let js_error = |err: JsValue| err.as_string().unwrap_or_else(|| format!("{err:?}"));
+	let value = callback.call1(&JsValue::NULL, arg).map_err(js_error)?;
+	JsFuture::from(Promise::resolve(&value)).await.map_err(js_error)
+}
+
 impl RpcProtocol for SurrealWasmConnection {
 	fn kvs(&self) -> &Datastore {
 		&self.kvs
@@ -325,16 +1222,21 @@ impl RpcProtocol for SurrealWasmConnection {
 		_txn: Option<Uuid>,
 		_session_id: Option<Uuid>,
 	) -> Result<DbResult, surrealdb_types::Error> {
-		// Create a new transaction
+		// Create a new transaction, which only reads on a read-only engine
+		let kind = match self.read_only {
+			true => TransactionType::Read,
+			false => TransactionType::Write,
+		};
 		let tx = self
 			.kvs()
-			.transaction(TransactionType::Write, LockType::Optimistic)
+			.transaction(kind, LockType::Optimistic)
 			.await
 			.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
 		// Generate a unique transaction ID
 		let id = Uuid::now_v7();
 		// Store the transaction in the map
 		self.transactions.insert(id, Arc::new(tx));
+		self.transaction_started.insert(id, inspect::now());
 		// Return the transaction ID to the client
 		Ok(DbResult::Other(Value::Uuid(surrealdb_types::Uuid::from(id))))
 	}
@@ -358,6 +1260,7 @@ impl RpcProtocol for SurrealWasmConnection {
 		let Some((_, tx)) = self.transactions.remove(&txn_id) else {
 			return Err(surrealdb_core::rpc::invalid_params("Transaction not found"));
 		};
+		self.transaction_started.remove(&txn_id);
 
 		// Commit the transaction
 		tx.commit().await.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
@@ -385,6 +1288,7 @@ impl RpcProtocol for SurrealWasmConnection {
 		let Some((_, tx)) = self.transactions.remove(&txn_id) else {
 			return Err(surrealdb_core::rpc::invalid_params("Transaction not found"));
 		};
+		self.transaction_started.remove(&txn_id);
 
 		// Cancel the transaction
 		tx.cancel().await.map_err(surrealdb_core::rpc::types_error_from_anyhow)?;
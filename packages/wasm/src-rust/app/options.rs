@@ -3,12 +3,29 @@ use serde::Deserialize;
 use std::collections::HashSet;
 use surrealdb_core::dbs::{capabilities, NewPlannerStrategy};
 
+use super::audit::AuditOptions;
+use super::pin::Pin;
+use super::slowlog::SlowQueryOptions;
+use super::storage::StorageOptions;
+
 #[derive(Deserialize)]
 pub struct Options {
 	pub query_timeout: Option<u8>,
 	pub transaction_timeout: Option<u8>,
+	/// Whether to reject every write, for inspecting a datastore safely
+	pub read_only: Option<bool>,
+	/// Options for the storage backend, merged with the query parameters of the endpoint
+	pub storage: Option<StorageOptions>,
 	pub capabilities: Option<CapabilitiesConfig>,
 	pub defaults: Option<DefaultsConfig>,
+	/// Tracing directives selecting the records sent to the log handler
+	pub log_filter: Option<String>,
+	pub slow_queries: Option<SlowQueryOptions>,
+	pub audit: Option<AuditOptions>,
+	/// The namespace and database every session is restricted to
+	pub pin: Option<Pin>,
+	/// Whether to keep a log of every request and its result in memory
+	pub record: Option<bool>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -95,6 +112,29 @@ pub enum PlannerStrategy {
 	AllReadOnly,
 }
 
+impl PlannerStrategy {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			PlannerStrategy::BestEffort => "best-effort",
+			PlannerStrategy::ComputeOnly => "compute-only",
+			PlannerStrategy::AllReadOnly => "all-read-only",
+		}
+	}
+}
+
+impl CapabilitiesConfig {
+	/// Returns the configured planner strategy, or the default one
+	pub fn planner_strategy(&self) -> PlannerStrategy {
+		match self {
+			CapabilitiesConfig::Capabilities {
+				planner_strategy: Some(strategy),
+				..
+			} => strategy.clone(),
+			_ => PlannerStrategy::default(),
+		}
+	}
+}
+
 impl From<PlannerStrategy> for NewPlannerStrategy {
 	fn from(strategy: PlannerStrategy) -> Self {
 		match strategy {
@@ -0,0 +1,201 @@
+use serde::Deserialize;
+use surrealdb_core::dbs::Session;
+use surrealdb_core::sql::statements::DefineStatement;
+use surrealdb_core::sql::statements::InfoStatement;
+use surrealdb_core::sql::statements::RemoveStatement;
+use surrealdb_core::sql::statements::UseStatement;
+use surrealdb_core::sql::Base;
+use surrealdb_core::sql::Expr;
+use surrealdb_core::sql::TopLevelExpr;
+use surrealdb_types::Array;
+use surrealdb_types::ToSql;
+use surrealdb_types::Value;
+
+/// Restricts a session to a single namespace, and optionally a single database
+#[derive(Deserialize, Clone)]
+pub struct Pin {
+	pub namespace: String,
+	/// The database, or `None` to allow every database in the namespace
+	pub database: Option<String>,
+}
+
+impl Pin {
+	/// Returns whether a selection is within the pin, where `None` selects nothing
+	pub fn allows(&self, ns: Option<&str>, db: Option<&str>) -> bool {
+		let ns = ns.is_none_or(|ns| ns == self.namespace);
+		let db = match &self.database {
+			Some(database) => db.is_none_or(|db| db == database),
+			None => true,
+		};
+		ns && db
+	}
+
+	/// Returns whether another pin only allows selections within this one
+	pub fn contains(&self, other: &Pin) -> bool {
+		self.allows(Some(&other.namespace), other.database.as_deref())
+			&& (self.database.is_none() || other.database.is_some())
+	}
+
+	/// Selects the pinned namespace and database in a session, returning whether
+	/// the session had selected anything outside of them
+	pub fn select(&self, session: &mut Session) -> bool {
+		let escaped = !self.allows(session.ns.as_deref(), session.db.as_deref());
+		session.ns = Some(self.namespace.clone());
+		if self.database.is_some() {
+			session.db = self.database.clone();
+		}
+		escaped
+	}
+
+	/// Rejects a request which would select a namespace or database outside the pin
+	pub fn check(&self, method: &str, params: &Array) -> Result<(), surrealdb_types::Error> {
+		let mut params = params.iter();
+		match method {
+			"use" => {
+				let ns = params.next().and_then(as_str);
+				let db = params.next().and_then(as_str);
+				self.check_selection(ns, db)
+			}
+			"query" => match params.next() {
+				Some(Value::String(sql)) => self.check_query(sql),
+				_ => Ok(()),
+			},
+			// The function is checked as the statement calling it, so a name which
+			// does not parse is rejected before it runs
+			"run" => match params.next() {
+				Some(Value::String(name)) => {
+					let args = match params.nth(1) {
+						Some(Value::Array(args)) => {
+							args.iter().map(|arg| arg.to_sql()).collect::<Vec<_>>().join(", ")
+						}
+						_ => String::new(),
+					};
+					self.check_query(&format!("{name}({args})"))
+				}
+				_ => Ok(()),
+			},
+			// A GraphQL query can only read the tables of the selected database, which
+			// is within the pin once `select` runs
+			"graphql" => Ok(()),
+			"signin" | "signup" => match params.next() {
+				Some(Value::Object(credentials)) => {
+					let field = |names: [&str; 3]| {
+						names.iter().find_map(|name| credentials.get(*name).and_then(as_str))
+					};
+					let ns = field(["ns", "NS", "namespace"]);
+					let db = field(["db", "DB", "database"]);
+					self.check_selection(ns, db)
+				}
+				_ => Ok(()),
+			},
+			_ => Ok(()),
+		}
+	}
+
+	/// Rejects a query with a `USE` statement selecting anything outside the pin,
+	/// or a `DEFINE`, `REMOVE` or `INFO` statement acting above the pinned level,
+	/// before any of its statements run.
+	///
+	/// Statements on the root, such as `INFO FOR ROOT`, `REMOVE NAMESPACE` or
+	/// `DEFINE USER ... ON ROOT`, are always rejected. Statements on the namespace,
+	/// such as `DEFINE DATABASE` or `INFO FOR NS`, are rejected when the pin names
+	/// a database. A query which cannot be parsed is rejected as well, as its
+	/// statements cannot be checked.
+	pub fn check_query(&self, sql: &str) -> Result<(), surrealdb_types::Error> {
+		let ast = surrealdb_core::syn::parse(sql).map_err(|err| {
+			surrealdb_core::rpc::types_error_from_anyhow(anyhow::anyhow!("{err}"))
+		})?;
+		for expr in &ast.expressions {
+			match expr {
+				TopLevelExpr::Use(UseStatement::Ns(ns)) => {
+					self.check_selection(Some(ns.as_str()), None)?
+				}
+				TopLevelExpr::Use(UseStatement::Db(db)) => {
+					self.check_selection(None, Some(db.as_str()))?
+				}
+				TopLevelExpr::Use(UseStatement::NsDb(ns, db)) => {
+					self.check_selection(Some(ns.as_str()), Some(db.as_str()))?
+				}
+				TopLevelExpr::Expr(expr) => {
+					if let Some(base) = level(expr) {
+						self.check_level(base)?
+					}
+				}
+				_ => {}
+			}
+		}
+		Ok(())
+	}
+
+	/// Rejects a statement acting on the root, or on the namespace when the pin
+	/// names a database
+	fn check_level(&self, base: Base) -> Result<(), surrealdb_types::Error> {
+		match base {
+			Base::Db => Ok(()),
+			Base::Ns if self.database.is_none() => Ok(()),
+			_ => Err(self.pinned()),
+		}
+	}
+
+	fn check_selection(
+		&self,
+		ns: Option<&str>,
+		db: Option<&str>,
+	) -> Result<(), surrealdb_types::Error> {
+		if self.allows(ns, db) {
+			return Ok(());
+		}
+
+		Err(self.pinned())
+	}
+
+	/// The error returned when a statement or selection leaves the pin
+	fn pinned(&self) -> surrealdb_types::Error {
+		denied(match &self.database {
+			Some(database) => format!(
+				"The session is pinned to namespace {} and database {database}",
+				self.namespace
+			),
+			None => format!("The session is pinned to namespace {}", self.namespace),
+		})
+	}
+}
+
+/// Returns the level a `DEFINE`, `REMOVE` or `INFO` statement acts on, when it
+/// is above a database
+fn level(expr: &Expr) -> Option<Base> {
+	match expr {
+		Expr::Define(define) => match define.as_ref() {
+			DefineStatement::Namespace(_) => Some(Base::Root),
+			DefineStatement::Database(_) => Some(Base::Ns),
+			DefineStatement::User(user) => Some(user.base.clone()),
+			DefineStatement::Access(access) => Some(access.base.clone()),
+			_ => None,
+		},
+		Expr::Remove(remove) => match remove.as_ref() {
+			RemoveStatement::Namespace(_) => Some(Base::Root),
+			RemoveStatement::Database(_) => Some(Base::Ns),
+			RemoveStatement::User(user) => Some(user.base.clone()),
+			RemoveStatement::Access(access) => Some(access.base.clone()),
+			_ => None,
+		},
+		Expr::Info(info) => match info.as_ref() {
+			InfoStatement::Root(..) => Some(Base::Root),
+			InfoStatement::Ns(..) => Some(Base::Ns),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// The error returned when a session attempts to leave its pin
+pub fn denied(message: String) -> surrealdb_types::Error {
+	surrealdb_types::Error::not_allowed(message, None)
+}
+
+fn as_str(value: &Value) -> Option<&str> {
+	match value {
+		Value::String(value) => Some(value.as_str()),
+		_ => None,
+	}
+}
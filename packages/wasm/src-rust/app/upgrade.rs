@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use surrealdb_core::kvs::version::MajorVersion;
+use surrealdb_core::kvs::Datastore;
+use surrealdb_core::kvs::LockType;
+use surrealdb_core::kvs::TransactionType;
+
+/// The number of keys copied in each transaction
+const BATCH_SIZE: u32 = 1000;
+
+/// The bounds of the whole keyspace
+const KEYSPACE_START: &[u8] = &[0x00];
+const KEYSPACE_END: &[u8] = &[0xff];
+
+#[derive(Serialize)]
+pub struct UpgradeProgress {
+	/// `copying`, `migrating` or `complete`
+	pub stage: &'static str,
+	/// The number of keys copied into the new location so far
+	pub copied: u64,
+	/// The storage version being migrated from
+	pub from: u16,
+	/// The storage version being migrated to
+	pub to: u16,
+}
+
+#[derive(Serialize)]
+pub struct UpgradeReport {
+	/// The storage version before the upgrade
+	pub from: u16,
+	/// The storage version after the upgrade
+	pub to: u16,
+	/// The number of keys copied into the new location
+	pub copied: u64,
+}
+
+/// Returns whether a datastore was written with an older storage format
+pub async fn needs_upgrade(kvs: &Datastore) -> Result<bool, String> {
+	let version = kvs.get_version().await.map_err(|e| e.to_string())?;
+	Ok(!version.is_latest())
+}
+
+/// Migrates the storage format of a datastore to the latest version.
+///
+/// When a `target` is given, every key is first copied into it and the copy is
+/// migrated instead, leaving the original datastore untouched. The target must
+/// be empty. `progress` is called after each batch of copied keys, before the
+/// migration and once it completes.
+pub async fn upgrade<F>(
+	source: &Datastore,
+	target: Option<&Datastore>,
+	mut progress: F,
+) -> Result<UpgradeReport, String>
+where
+	F: FnMut(UpgradeProgress) -> Result<(), String>,
+{
+	let from = source.get_version().await.map_err(|e| e.to_string())?;
+	let (from, to) = (u16::from(from), u16::from(MajorVersion::latest()));
+	let mut report = |stage, copied| {
+		progress(UpgradeProgress {
+			stage,
+			copied,
+			from,
+			to,
+		})
+	};
+
+	let (kvs, copied) = match target {
+		Some(target) => (target, copy(source, target, &mut report).await?),
+		None => (source, 0),
+	};
+
+	let version = kvs.get_version().await.map_err(|e| e.to_string())?;
+	if !version.is_latest() {
+		report("migrating", copied)?;
+		let tx = kvs
+			.transaction(TransactionType::Write, LockType::Optimistic)
+			.await
+			.map_err(|e| e.to_string())?;
+		let tx = Arc::new(tx);
+		if let Err(err) = version.fix(tx.clone()).await {
+			let _ = tx.cancel().await;
+			return Err(format!("Failed to migrate from storage version {from}: {err}"));
+		}
+		tx.commit().await.map_err(|e| e.to_string())?;
+	}
+
+	report("complete", copied)?;
+	Ok(UpgradeReport {
+		from,
+		to,
+		copied,
+	})
+}
+
+/// Copies every key of a datastore into an empty one, in batches
+async fn copy<F>(source: &Datastore, target: &Datastore, report: &mut F) -> Result<u64, String>
+where
+	F: FnMut(&'static str, u64) -> Result<(), String>,
+{
+	let tx = target
+		.transaction(TransactionType::Read, LockType::Optimistic)
+		.await
+		.map_err(|e| e.to_string())?;
+	let existing = tx.scan(KEYSPACE_START.to_vec()..KEYSPACE_END.to_vec(), 1, None).await;
+	let _ = tx.cancel().await;
+	if !existing.map_err(|e| e.to_string())?.is_empty() {
+		return Err("The datastore to upgrade into is not empty".to_string());
+	}
+
+	let mut copied = 0;
+	let mut start = KEYSPACE_START.to_vec();
+	loop {
+		let tx = source
+			.transaction(TransactionType::Read, LockType::Optimistic)
+			.await
+			.map_err(|e| e.to_string())?;
+		let batch = tx.scan(start.clone()..KEYSPACE_END.to_vec(), BATCH_SIZE, None).await;
+		let _ = tx.cancel().await;
+		let batch = batch.map_err(|e| e.to_string())?;
+		let Some((last, _)) = batch.last() else {
+			break;
+		};
+
+		// Continue from the key immediately after the last one copied
+		start = last.clone();
+		start.push(0x00);
+
+		let tx = target
+			.transaction(TransactionType::Write, LockType::Optimistic)
+			.await
+			.map_err(|e| e.to_string())?;
+		for (key, val) in &batch {
+			if let Err(err) = tx.set(key, val, None).await {
+				let _ = tx.cancel().await;
+				return Err(err.to_string());
+			}
+		}
+		tx.commit().await.map_err(|e| e.to_string())?;
+
+		copied += batch.len() as u64;
+		report("copying", copied)?;
+		if batch.len() < BATCH_SIZE as usize {
+			break;
+		}
+	}
+
+	Ok(copied)
+}
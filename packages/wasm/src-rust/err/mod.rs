@@ -9,6 +9,12 @@ impl From<Error> for JsValue {
 	}
 }
 
+impl From<JsValue> for Error {
+	fn from(v: JsValue) -> Self {
+		Self(v)
+	}
+}
+
 impl From<anyhow::Error> for Error {
 	fn from(v: anyhow::Error) -> Self {
 		Self(JsValue::from(v.to_string()))
@@ -3,6 +3,10 @@
 mod app;
 mod err;
 
+pub use app::intercept::{Before, Interceptor};
+pub use app::EncodedResponse;
+pub use app::SurrealWasmEngine;
+
 #[cfg(feature = "debug")]
 mod debug {
 	use wasm_bindgen::prelude::wasm_bindgen;
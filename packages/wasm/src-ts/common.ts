@@ -1,15 +1,93 @@
-import init, { type ConnectionOptions, type SurrealWasmEngine } from "../wasm/surrealdb";
+import type { Uuid } from "surrealdb";
+import init, {
+    type AuditRecord,
+    type ConnectionOptions,
+    type LogRecord,
+    type SessionPin,
+    type SlowQuery,
+    type SurrealWasmEngine,
+} from "../wasm/surrealdb";
+
+export interface WasmEngineOptions extends ConnectionOptions {
+    /** Receives the tracing records of the embedded engine which pass `log_filter` */
+    onLog?: (record: LogRecord) => void;
+    /** Receives each query exceeding the `slow_queries` threshold */
+    onSlowQuery?: (query: SlowQuery) => void;
+    /** Receives a record of every request, when `audit` is enabled */
+    onAudit?: (record: AuditRecord) => void;
+    /** Run around every request, in order. Only supported by the local engine broker */
+    interceptors?: EngineInterceptor[];
+}
+
+export interface InterceptedRequest {
+    method: string;
+    params: unknown[];
+    /** The session the request runs in, or `undefined` for the default session */
+    session?: Uuid;
+    txn?: Uuid;
+}
+
+export type InterceptorAction = { request: InterceptedRequest } | { response: unknown };
+
+/**
+ * Runs around every request executed by the embedded engine. The response is
+ * either the result of the request or an object holding its `error`.
+ */
+export interface EngineInterceptor {
+    /**
+     * Runs before the request executes, in the order the interceptors are listed.
+     * Return `{ request }` to replace the request, or `{ response }` to respond
+     * without running the remaining interceptors or the request itself.
+     */
+    before?(
+        request: InterceptedRequest,
+    ): InterceptorAction | undefined | Promise<InterceptorAction | undefined>;
+    /**
+     * Runs after the request, in the reverse order, for every interceptor whose
+     * `before` ran. Return `{ response }` to replace the response.
+     */
+    after?(
+        request: InterceptedRequest,
+        response: unknown,
+    ): { response: unknown } | undefined | Promise<{ response: unknown } | undefined>;
+}
+
+/** An interceptor exchanging CBOR encoded values with the engine */
+export interface EncodedInterceptor {
+    before?: (request: Uint8Array) => Promise<Uint8Array | null>;
+    after?: (exchange: Uint8Array) => Promise<Uint8Array | null>;
+}
 
 export interface EngineBroker {
     isConnected: boolean;
+    /**
+     * The session requests without one run in, in place of the default session,
+     * when the engine is shared with other tabs
+     */
+    session?: string;
+    /**
+     * Opens the engine. When the engine is shared with other tabs and another
+     * engine takes over, the requests returned by `restore` run first, to select
+     * the namespace and database and authenticate the sessions of this tab again.
+     */
     connect(
         url: string,
-        options: ConnectionOptions | undefined,
+        options: WasmEngineOptions | undefined,
         onNotification: (data: Uint8Array) => void,
+        restore?: () => Uint8Array[],
     ): Promise<void>;
-    execute(payload: Uint8Array): Promise<Uint8Array>;
+    /**
+     * Executes a request, passing the response to `decode` while it can be read in
+     * place. The response may be reused once `decode` returns, so it must not be kept.
+     */
+    execute<T>(payload: Uint8Array, decode: (response: Uint8Array) => T): Promise<T>;
     importSql(data: string): Promise<void>;
     exportSql(options: Uint8Array): Promise<string>;
+    setLogFilter(directives: string): Promise<void>;
+    slowQueries(): Promise<SlowQuery[]>;
+    recording(): Promise<Uint8Array>;
+    pinSession(session: string | undefined, pin: SessionPin): Promise<void>;
+    intercept(interceptors: EncodedInterceptor[]): Promise<void>;
     close(): Promise<void>;
 }
 
@@ -63,6 +141,37 @@ export function readNotifications(
     return cancel;
 }
 
+/**
+ * Calls `handle` with each value read from a stream of the engine, until the signal is aborted
+ */
+export function readRecords<T>(
+    stream: ReadableStream<T>,
+    handle: (value: T) => void,
+    signal: AbortSignal,
+): void {
+    const reader = stream.getReader();
+
+    signal.addEventListener("abort", () => {
+        reader.cancel().catch(() => {});
+    });
+
+    (async () => {
+        try {
+            while (!signal.aborted) {
+                const { done, value } = await reader.read();
+
+                if (done || signal.aborted) {
+                    break;
+                }
+
+                handle(value);
+            }
+        } catch {
+            // The stream is cancelled when the engine is closed
+        }
+    })();
+}
+
 let initPromise: ReturnType<typeof init> | undefined;
 
 /**
@@ -15,10 +15,25 @@ import {
     type SqlExportOptions,
     type SurrealEngine,
     UnexpectedConnectionError,
-    type Uuid,
+    Uuid,
 } from "surrealdb";
-import type { ConnectionOptions } from "../wasm/surrealdb";
-import type { EngineBroker } from "./common";
+import {
+    type ConnectionOptions,
+    type ReplayReport,
+    type SessionPin,
+    type SlowQuery,
+    SurrealWasmEngine,
+    type UpgradeProgress,
+    type UpgradeReport,
+} from "../wasm/surrealdb";
+import {
+    type EncodedInterceptor,
+    type EngineBroker,
+    type EngineInterceptor,
+    type InterceptedRequest,
+    initializeLibrary,
+    type WasmEngineOptions,
+} from "./common";
 import { wrapSqonError } from "./wrap-sqon-error";
 
 type LiveChannels = Record<string, [LiveMessage]>;
@@ -30,6 +45,9 @@ interface LivePayload {
     record: RecordId;
 }
 
+/** The engines which are currently open, closed when their datastore is destroyed */
+const openEngines = new Set<WebAssemblyEngine>();
+
 /**
  * The engine implementation responsible for communicating with an embedded
  * WebAssembly build of SurrealDB.
@@ -39,12 +57,15 @@ export class WebAssemblyEngine extends RpcEngine implements SurrealEngine {
     #publisher = new Publisher<EngineEvents>();
     #subscriptions = new Publisher<LiveChannels>();
     #abort: AbortController | undefined;
-    #options: ConnectionOptions | undefined;
+    #options: WasmEngineOptions | undefined;
+    #interceptors: EngineInterceptor[];
 
-    constructor(broker: EngineBroker, context: DriverContext, options?: ConnectionOptions) {
+    constructor(broker: EngineBroker, context: DriverContext, options?: WasmEngineOptions) {
         super(context);
+        const { interceptors, ...rest } = options ?? {};
         this.#broker = broker;
-        this.#options = options;
+        this.#options = options ? rest : undefined;
+        this.#interceptors = interceptors ?? [];
     }
 
     features = new Set([
@@ -59,10 +80,12 @@ export class WebAssemblyEngine extends RpcEngine implements SurrealEngine {
         this.#abort?.abort();
         this.#abort = new AbortController();
         this._state = state;
+        openEngines.add(this);
         this.#initialize(state, this.#abort.signal);
     }
 
     async close(): Promise<void> {
+        openEngines.delete(this);
         this._state = undefined;
         this.#abort?.abort();
         this.#abort = undefined;
@@ -105,11 +128,15 @@ export class WebAssemblyEngine extends RpcEngine implements SurrealEngine {
         }
 
         const id = this._context.uniqueId();
-        const payload = wrapSqonError(() => this._context.codecs.cbor.encode({ id, ...request }));
+        const { session: shared } = this.#broker;
+        const session = request.session ?? (shared === undefined ? undefined : new Uuid(shared));
+        const payload = wrapSqonError(() =>
+            this._context.codecs.cbor.encode({ id, ...request, session }),
+        );
 
-        const response = await this.#broker.execute(payload);
-        const decoded = wrapSqonError(() =>
-            this._context.codecs.cbor.decode<Record<string, unknown>>(response),
+        const { cbor } = this._context.codecs;
+        const decoded = await this.#broker.execute(payload, (response) =>
+            wrapSqonError(() => cbor.decode<Record<string, unknown>>(response)),
         );
 
         if (decoded && typeof decoded === "object" && "error" in decoded) {
@@ -153,6 +180,109 @@ export class WebAssemblyEngine extends RpcEngine implements SurrealEngine {
         return this.#broker.importSql(data);
     }
 
+    /**
+     * Returns the log of every request and its result, when the `record` option is set
+     */
+    async recording(): Promise<Uint8Array> {
+        return this.#broker.recording();
+    }
+
+    /**
+     * Re-executes a log returned by `recording()` against a fresh engine,
+     * reporting the requests whose result differs from the recorded one
+     *
+     * @param log The contents of the log
+     * @param endpoint The endpoint of the fresh engine, defaulting to `mem://`
+     * @param options The connection options of the fresh engine
+     */
+    static async replay(
+        log: Uint8Array,
+        endpoint?: string,
+        options?: ConnectionOptions,
+    ): Promise<ReplayReport> {
+        await initializeLibrary();
+
+        return SurrealWasmEngine.replay(log, endpoint, options);
+    }
+
+    /**
+     * Returns whether the datastore at an endpoint was written with an older
+     * storage format, and must be upgraded before it can be connected to
+     *
+     * @param endpoint The endpoint of the datastore, such as `indxdb://data`
+     */
+    static async needsUpgrade(endpoint: string): Promise<boolean> {
+        await initializeLibrary();
+
+        return SurrealWasmEngine.needs_upgrade(endpoint);
+    }
+
+    /**
+     * Migrates the storage format of a datastore to the latest version. The
+     * datastore must not be open in any engine, in this or another tab, while it
+     * is upgraded.
+     *
+     * @param endpoint The endpoint of the datastore, such as `indxdb://data`
+     * @param options.target An empty datastore to upgrade a copy into, leaving the original untouched
+     * @param options.onProgress Receives each stage of the upgrade
+     */
+    static async upgrade(
+        endpoint: string,
+        options?: { target?: string; onProgress?: (progress: UpgradeProgress) => void },
+    ): Promise<UpgradeReport> {
+        await initializeLibrary();
+
+        return SurrealWasmEngine.upgrade(endpoint, options?.target, options?.onProgress);
+    }
+
+    /**
+     * Closes every engine of this context connected to a datastore and deletes
+     * its IndexedDB database. Deleting fails while the database is still open in
+     * another tab or worker, though the deletion stays queued and goes ahead once
+     * it is closed there, as IndexedDB cannot cancel it.
+     *
+     * @param endpoint The endpoint of the datastore, such as `indxdb://data`
+     */
+    static async destroy(endpoint: string): Promise<void> {
+        const url = new URL(endpoint).toString();
+        const engines = [...openEngines].filter((engine) => engine._state?.url.toString() === url);
+        await Promise.all(engines.map((engine) => engine.close()));
+        await initializeLibrary();
+
+        return SurrealWasmEngine.destroy(endpoint);
+    }
+
+    /**
+     * Restricts a session to a namespace, and optionally a database. Requests,
+     * `USE` statements and imports selecting anything outside the pin fail with
+     * a permission error. A pin cannot be removed once set, and must be within
+     * the `pin` connection option when one is set.
+     *
+     * @param session The session to restrict, or `undefined` for the default session
+     * @param pin The namespace and database the session is restricted to
+     */
+    async pinSession(session: Uuid | undefined, pin: SessionPin): Promise<void> {
+        return this.#broker.pinSession(session?.toString(), pin);
+    }
+
+    /**
+     * Returns the queries which exceeded the `slow_queries` threshold, from the oldest.
+     * Only the most recent `capacity` queries are kept.
+     */
+    async slowQueries(): Promise<SlowQuery[]> {
+        return this.#broker.slowQueries();
+    }
+
+    /**
+     * Replaces the log filter of the embedded engine with directives such as
+     * `surrealdb_core::dbs=debug`
+     *
+     * @param directives The filter directives, in the `RUST_LOG` format
+     */
+    async setLogFilter(directives: string): Promise<void> {
+        return this.#broker.setLogFilter(directives);
+    }
+
     override async exportSql(options: Partial<SqlExportOptions>): Promise<Response> {
         const payload = wrapSqonError(() => this._context.codecs.cbor.encode(options));
         const sql = await this.#broker.exportSql(payload);
@@ -160,27 +290,101 @@ export class WebAssemblyEngine extends RpcEngine implements SurrealEngine {
         return new Response(sql);
     }
 
+    #encode({ before, after }: EngineInterceptor): EncodedInterceptor {
+        const { cbor } = this._context.codecs;
+
+        return {
+            before:
+                before &&
+                (async (request) => {
+                    const action = await before(cbor.decode<InterceptedRequest>(request));
+                    return action === undefined ? null : cbor.encode(action);
+                }),
+            after:
+                after &&
+                (async (exchange) => {
+                    const { request, response } = cbor.decode<{
+                        request: InterceptedRequest;
+                        response: unknown;
+                    }>(exchange);
+                    const action = await after(request, response);
+                    return action === undefined ? null : cbor.encode(action);
+                }),
+        };
+    }
+
+    /**
+     * Encodes the requests which attach each session and restore its namespace,
+     * database, variables and authentication, as the connection state holds them
+     */
+    #restoreRequests(): Uint8Array[] {
+        if (!this._state) {
+            return [];
+        }
+
+        const { cbor } = this._context.codecs;
+        const { session: shared } = this.#broker;
+        const { rootSession, sessions } = this._state;
+        const requests: Uint8Array[] = [];
+
+        const encode = (method: string, params: unknown[] | undefined, session?: Uuid) => {
+            const id = this._context.uniqueId();
+            requests.push(wrapSqonError(() => cbor.encode({ id, method, params, session })));
+        };
+
+        for (const state of [rootSession, ...sessions.values()]) {
+            const session = state.id ?? (shared === undefined ? undefined : new Uuid(shared));
+
+            if (state.id) {
+                encode("attach", undefined, session);
+            }
+
+            if (state.namespace || state.database) {
+                encode("use", [state.namespace, state.database], session);
+            }
+
+            for (const [name, value] of Object.entries(state.variables)) {
+                encode("let", [name, value], session);
+            }
+
+            if (state.accessToken) {
+                encode("authenticate", [state.accessToken], session);
+            }
+        }
+
+        return requests;
+    }
+
     async #initialize(state: ConnectionState, signal: AbortSignal) {
         try {
-            await this.#broker.connect(state.url.toString(), this.#options, (data) => {
-                const payload = wrapSqonError(() =>
-                    this._context.codecs.cbor.decode<LivePayload>(data),
-                );
-
-                if (payload.id) {
-                    this.#subscriptions.publish(payload.id.toString(), {
-                        queryId: payload.id,
-                        action: payload.action,
-                        recordId: payload.record,
-                        value: payload.result,
-                    });
-                }
-            });
+            await this.#broker.connect(
+                state.url.toString(),
+                this.#options,
+                (data) => {
+                    const payload = wrapSqonError(() =>
+                        this._context.codecs.cbor.decode<LivePayload>(data),
+                    );
+
+                    if (payload.id) {
+                        this.#subscriptions.publish(payload.id.toString(), {
+                            queryId: payload.id,
+                            action: payload.action,
+                            recordId: payload.record,
+                            value: payload.result,
+                        });
+                    }
+                },
+                () => this.#restoreRequests(),
+            );
 
             if (signal.aborted) {
                 return;
             }
 
+            if (this.#interceptors.length > 0) {
+                await this.#broker.intercept(this.#interceptors.map((i) => this.#encode(i)));
+            }
+
             this.#publisher.publish("connected");
         } catch (err) {
             this.#publisher.publish("error", new UnexpectedConnectionError(err));
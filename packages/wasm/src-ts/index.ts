@@ -1,12 +1,15 @@
 import type { DriverContext, Engines } from "surrealdb";
-import type { ConnectionOptions } from "../wasm/surrealdb";
 import { WebAssemblyEngine } from "./engine";
+import { LeaderEngineBroker, type WasmLeaderOptions } from "./leader/leader-broker";
 import { LocalEngineBroker } from "./local/local-broker";
 import { type WasmWorkerOptions, WorkerEngineBroker } from "./worker/worker-broker";
 
 /**
  * Configure the `mem` and `indxdb` WebAssembly engines for the JavaScript SDK.
  *
+ * When the `leader` option is set, tabs opening the same `indxdb` database elect
+ * a single tab to open it, and every other tab forwards its requests to that tab.
+ *
  * @param options Optional connection options to configure the WebAssembly engines.
  * @example
  * ```ts
@@ -21,13 +24,20 @@ import { type WasmWorkerOptions, WorkerEngineBroker } from "./worker/worker-brok
  * });
  * ```
  */
-export const createWasmEngines = (options?: ConnectionOptions): Engines => {
+export const createWasmEngines = (options?: WasmLeaderOptions): Engines => {
     const createEngine = (ctx: DriverContext) =>
         new WebAssemblyEngine(new LocalEngineBroker(), ctx, options);
 
+    const createSharedEngine = (ctx: DriverContext) =>
+        new WebAssemblyEngine(
+            options?.leader ? new LeaderEngineBroker() : new LocalEngineBroker(),
+            ctx,
+            options,
+        );
+
     return {
         mem: createEngine,
-        indxdb: createEngine,
+        indxdb: createSharedEngine,
     };
 };
 
@@ -0,0 +1,389 @@
+import { ConnectionUnavailableError } from "surrealdb";
+import type { SessionPin, SlowQuery } from "../../wasm/surrealdb";
+import type { EngineBroker, WasmEngineOptions } from "../common";
+import { LocalEngineBroker } from "../local/local-broker";
+import {
+    type ChannelMessage,
+    LeaderMessageType,
+    type LeaderRequest,
+    type RequestMessage,
+} from "./leader-contract";
+
+export interface WasmLeaderOptions extends WasmEngineOptions {
+    /**
+     * Elect a single tab to open each IndexedDB database, with every other tab
+     * forwarding its requests to it.
+     */
+    leader?: boolean;
+}
+
+type PromiseResolver<T> = {
+    resolve: (value: T) => void;
+    reject: (error: Error) => void;
+};
+
+type PendingRequest = PromiseResolver<unknown> & {
+    message: RequestMessage;
+    /** Whether the leader has started running the request */
+    accepted: boolean;
+    /** Whether the request restores a session in a new leader */
+    restoring: boolean;
+};
+
+/**
+ * Coordinates the tabs and workers opening an IndexedDB database, so that only
+ * one engine reads and writes the underlying store.
+ *
+ * The tab holding the Web Lock for the database is the leader, and opens the
+ * engine locally. Every other tab is a follower, forwarding its requests to the
+ * leader over a `BroadcastChannel` and receiving every live query notification.
+ * When the leader tab closes its lock is released, and the next tab waiting for
+ * it opens the engine and takes over.
+ *
+ * Requests are held until a leader is elected. When the leader changes, the
+ * requests the previous leader had not started are sent to the new one, while
+ * those it had started are rejected, as they may or may not have completed.
+ *
+ * Each tab runs its requests in a session of its own, in place of the default
+ * session of the leader engine. Sessions live in the leader engine, so when the
+ * leader changes each tab first restores its sessions in the new one, before
+ * any other request is sent to it.
+ */
+export class LeaderEngineBroker implements EngineBroker {
+    #id = crypto.randomUUID();
+    #session = crypto.randomUUID();
+    #local = new LocalEngineBroker();
+    #channel: BroadcastChannel | undefined;
+    #abort: AbortController | undefined;
+    #release: (() => void) | undefined;
+    #isLeader = false;
+    #leader: string | undefined;
+    #elected: Promise<void> = Promise.resolve();
+    #restored: Promise<void> = Promise.resolve();
+    #restore: (() => Uint8Array[]) | undefined;
+    #markElected: PromiseResolver<void> | undefined;
+    #nextId = 0;
+    #pending = new Map<number, PendingRequest>();
+    #handleNotification: ((data: Uint8Array) => void) | undefined;
+
+    get isConnected() {
+        return !!this.#channel;
+    }
+
+    get session() {
+        return this.#session;
+    }
+
+    async connect(
+        url: string,
+        options: WasmLeaderOptions | undefined,
+        onNotification: (data: Uint8Array) => void,
+        restore?: () => Uint8Array[],
+    ) {
+        await this.close();
+
+        const name = `surrealdb:leader:${url}`;
+        const abort = new AbortController();
+
+        this.#abort = abort;
+        this.#handleNotification = onNotification;
+        this.#restore = restore;
+        this.#elected = new Promise<void>((resolve, reject) => {
+            this.#markElected = { resolve, reject };
+        });
+
+        this.#channel = new BroadcastChannel(name);
+        this.#channel.addEventListener("message", (event) => {
+            this.#handleMessage(event.data as ChannelMessage);
+        });
+
+        // Wait in line for the lock, holding it until the broker is closed
+        navigator.locks
+            .request(name, { signal: abort.signal }, async () => {
+                await this.#local.connect(url, options, (data) => {
+                    onNotification(data);
+                    this.#post({ type: LeaderMessageType.NOTIFICATION, data });
+                });
+
+                this.#isLeader = true;
+                this.#setLeader(this.#id);
+                this.#post({ type: LeaderMessageType.LEADER, leader: this.#id });
+
+                await new Promise<void>((resolve) => {
+                    this.#release = resolve;
+                });
+            })
+            .catch((error) => {
+                if (!abort.signal.aborted) {
+                    this.#markElected?.reject(error);
+                }
+            });
+
+        // Ask an existing leader to announce itself
+        this.#post({ type: LeaderMessageType.HELLO });
+
+        await this.#elected;
+    }
+
+    async execute<T>(payload: Uint8Array, decode: (response: Uint8Array) => T): Promise<T> {
+        return decode(await this.#forward<Uint8Array>({ method: "execute", args: [payload] }));
+    }
+
+    async importSql(data: string): Promise<void> {
+        return this.#forward({ method: "importSql", args: [data] });
+    }
+
+    async exportSql(options: Uint8Array): Promise<string> {
+        return this.#forward({ method: "exportSql", args: [options] });
+    }
+
+    async slowQueries(): Promise<SlowQuery[]> {
+        return this.#forward({ method: "slowQueries", args: [] });
+    }
+
+    async recording(): Promise<Uint8Array> {
+        return this.#forward({ method: "recording", args: [] });
+    }
+
+    async setLogFilter(directives: string): Promise<void> {
+        return this.#forward({ method: "setLogFilter", args: [directives] });
+    }
+
+    async pinSession(session: string | undefined, pin: SessionPin): Promise<void> {
+        return this.#forward({ method: "pinSession", args: [session, pin] });
+    }
+
+    async intercept(): Promise<void> {
+        // Requests forwarded by follower tabs could not run the interceptors of this tab
+        throw new Error("Interceptors are not supported with the leader option");
+    }
+
+    async close() {
+        const wasLeader = this.#isLeader;
+
+        // Requests arriving from now on are left for the next leader
+        this.#isLeader = false;
+        this.#abort?.abort();
+        this.#abort = undefined;
+
+        // Close the engine before another tab can take over the lock
+        if (wasLeader) {
+            await this.#local.close();
+        }
+
+        this.#release?.();
+        this.#release = undefined;
+        this.#channel?.close();
+        this.#channel = undefined;
+        this.#leader = undefined;
+        this.#markElected = undefined;
+        this.#handleNotification = undefined;
+        this.#restore = undefined;
+        this.#rejectPending();
+    }
+
+    async #forward<T>(request: LeaderRequest): Promise<T> {
+        if (!this.#channel) {
+            throw new ConnectionUnavailableError();
+        }
+
+        await this.#elected;
+        await this.#restored;
+
+        return this.#send(request);
+    }
+
+    /** Runs a request in the leader, which may be this tab */
+    async #send<T>(request: LeaderRequest, restoring = false): Promise<T> {
+        if (this.#isLeader) {
+            return this.#run(request, this.#session) as Promise<T>;
+        }
+
+        const message: RequestMessage = {
+            type: LeaderMessageType.REQUEST,
+            id: this.#nextId++,
+            from: this.#id,
+            session: this.#session,
+            request,
+        };
+
+        return new Promise<T>((resolve, reject) => {
+            this.#pending.set(message.id, {
+                resolve: (value) => resolve(value as T),
+                reject,
+                message,
+                accepted: false,
+                restoring,
+            });
+
+            this.#post(message);
+        });
+    }
+
+    async #run(request: LeaderRequest, session: string): Promise<unknown> {
+        await this.#local.attachSession(session);
+
+        switch (request.method) {
+            case "execute":
+                // Copied, as the response may be posted to a follower
+                return this.#local.execute(...request.args, (response) => response.slice());
+            case "importSql":
+                return this.#local.importSql(...request.args, session);
+            case "exportSql":
+                return this.#local.exportSql(...request.args, session);
+            case "slowQueries":
+                return this.#local.slowQueries();
+            case "recording":
+                return this.#local.recording();
+            case "pinSession": {
+                const [pinned, pin] = request.args;
+                return this.#local.pinSession(pinned ?? session, pin);
+            }
+            case "setLogFilter":
+                return this.#local.setLogFilter(...request.args);
+        }
+    }
+
+    async #handleRequest(message: RequestMessage) {
+        this.#post({ type: LeaderMessageType.ACCEPTED, id: message.id, to: message.from });
+
+        try {
+            const data = await this.#run(message.request, message.session);
+
+            this.#post({
+                type: LeaderMessageType.RESPONSE,
+                id: message.id,
+                to: message.from,
+                data,
+            });
+        } catch (error) {
+            this.#post({
+                type: LeaderMessageType.ERROR,
+                id: message.id,
+                to: message.from,
+                message: error instanceof Error ? error.message : String(error),
+            });
+        }
+    }
+
+    #handleMessage(message: ChannelMessage) {
+        switch (message.type) {
+            case LeaderMessageType.HELLO: {
+                if (this.#isLeader) {
+                    this.#post({ type: LeaderMessageType.LEADER, leader: this.#id });
+                }
+                break;
+            }
+
+            case LeaderMessageType.LEADER: {
+                this.#setLeader(message.leader);
+                break;
+            }
+
+            case LeaderMessageType.REQUEST: {
+                if (this.#isLeader) {
+                    this.#handleRequest(message);
+                }
+                break;
+            }
+
+            case LeaderMessageType.ACCEPTED: {
+                const pending = this.#pending.get(message.id);
+                if (message.to === this.#id && pending) {
+                    pending.accepted = true;
+                }
+                break;
+            }
+
+            case LeaderMessageType.RESPONSE: {
+                const pending = this.#pending.get(message.id);
+                if (message.to === this.#id && pending) {
+                    this.#pending.delete(message.id);
+                    pending.resolve(message.data);
+                }
+                break;
+            }
+
+            case LeaderMessageType.ERROR: {
+                const pending = this.#pending.get(message.id);
+                if (message.to === this.#id && pending) {
+                    this.#pending.delete(message.id);
+                    pending.reject(new Error(message.message));
+                }
+                break;
+            }
+
+            case LeaderMessageType.NOTIFICATION: {
+                this.#handleNotification?.(message.data);
+                break;
+            }
+        }
+    }
+
+    #setLeader(leader: string) {
+        const changed = this.#leader !== undefined && this.#leader !== leader;
+
+        this.#leader = leader;
+        this.#markElected?.resolve();
+
+        if (changed) {
+            // A restore still waiting on the previous leader moves on to this one
+            this.#redirectPending(true);
+
+            const restored = this.#restored.then(() => this.#restoreSessions());
+            this.#restored = restored.finally(() => this.#redirectPending());
+        }
+    }
+
+    /**
+     * Restores the sessions of this tab in the new leader. A session which fails
+     * to be restored is left as it is, failing the requests which rely on it.
+     */
+    async #restoreSessions() {
+        for (const payload of this.#restore?.() ?? []) {
+            await this.#send({ method: "execute", args: [payload] }, true).catch(() => undefined);
+        }
+    }
+
+    /**
+     * Sends the requests the previous leader had not started to the new leader,
+     * which may be this tab, and rejects the others
+     *
+     * @param restoring Whether to only redirect the requests restoring a session
+     */
+    #redirectPending(restoring = false) {
+        for (const [id, pending] of this.#pending) {
+            if (restoring && !pending.restoring) {
+                continue;
+            }
+
+            if (!pending.accepted && !this.#isLeader) {
+                this.#post(pending.message);
+                continue;
+            }
+
+            this.#pending.delete(id);
+
+            if (pending.accepted) {
+                pending.reject(new ConnectionUnavailableError());
+            } else {
+                this.#run(pending.message.request, this.#session).then(
+                    pending.resolve,
+                    pending.reject,
+                );
+            }
+        }
+    }
+
+    #rejectPending() {
+        for (const pending of this.#pending.values()) {
+            pending.reject(new ConnectionUnavailableError());
+        }
+
+        this.#pending.clear();
+    }
+
+    #post(message: ChannelMessage) {
+        this.#channel?.postMessage(message);
+    }
+}
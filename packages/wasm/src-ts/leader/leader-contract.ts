@@ -0,0 +1,78 @@
+import type { SessionPin } from "../../wasm/surrealdb";
+
+export const LeaderMessageType = {
+    HELLO: "hello",
+    LEADER: "leader",
+    REQUEST: "request",
+    ACCEPTED: "accepted",
+    RESPONSE: "response",
+    ERROR: "error",
+    NOTIFICATION: "notification",
+} as const;
+
+export type LeaderMessageType = (typeof LeaderMessageType)[keyof typeof LeaderMessageType];
+
+export type LeaderRequest =
+    | { method: "execute"; args: [payload: Uint8Array] }
+    | { method: "importSql"; args: [data: string] }
+    | { method: "exportSql"; args: [options: Uint8Array] }
+    | { method: "setLogFilter"; args: [directives: string] }
+    | { method: "slowQueries"; args: [] }
+    | { method: "recording"; args: [] }
+    | { method: "pinSession"; args: [session: string | undefined, pin: SessionPin] };
+
+/** Sent by a follower when it joins, asking the leader to announce itself */
+export interface HelloMessage {
+    type: typeof LeaderMessageType.HELLO;
+}
+
+/** Sent by the leader when elected, and in reply to a hello */
+export interface LeaderMessage {
+    type: typeof LeaderMessageType.LEADER;
+    leader: string;
+}
+
+export interface RequestMessage {
+    type: typeof LeaderMessageType.REQUEST;
+    id: number;
+    from: string;
+    /** The session of the requesting tab, used in place of the default session */
+    session: string;
+    request: LeaderRequest;
+}
+
+/** Sent by the leader when it starts running a request, which is then never sent again */
+export interface AcceptedMessage {
+    type: typeof LeaderMessageType.ACCEPTED;
+    id: number;
+    to: string;
+}
+
+export interface ResponseMessage {
+    type: typeof LeaderMessageType.RESPONSE;
+    id: number;
+    to: string;
+    data: unknown;
+}
+
+export interface ErrorMessage {
+    type: typeof LeaderMessageType.ERROR;
+    id: number;
+    to: string;
+    message: string;
+}
+
+/** Live query notifications are sent to every tab, which ignore unknown live queries */
+export interface NotificationMessage {
+    type: typeof LeaderMessageType.NOTIFICATION;
+    data: Uint8Array;
+}
+
+export type ChannelMessage =
+    | HelloMessage
+    | LeaderMessage
+    | RequestMessage
+    | AcceptedMessage
+    | ResponseMessage
+    | ErrorMessage
+    | NotificationMessage;
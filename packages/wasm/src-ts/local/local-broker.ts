@@ -1,6 +1,13 @@
 import { ConnectionUnavailableError } from "surrealdb";
-import { type ConnectionOptions, SurrealWasmEngine } from "../../wasm/surrealdb";
-import { type EngineBroker, initializeLibrary, readNotifications } from "../common";
+import { type SessionPin, type SlowQuery, SurrealWasmEngine } from "../../wasm/surrealdb";
+import {
+    type EncodedInterceptor,
+    type EngineBroker,
+    initializeLibrary,
+    readNotifications,
+    readRecords,
+    type WasmEngineOptions,
+} from "../common";
 
 export class LocalEngineBroker implements EngineBroker {
     #engine: SurrealWasmEngine | undefined;
@@ -14,7 +21,7 @@ export class LocalEngineBroker implements EngineBroker {
 
     async connect(
         url: string,
-        options: ConnectionOptions | undefined,
+        options: WasmEngineOptions | undefined,
         onNotification: (data: Uint8Array) => void,
     ) {
         this.#cancelNotifications?.();
@@ -23,7 +30,12 @@ export class LocalEngineBroker implements EngineBroker {
 
         await initializeLibrary();
 
-        this.#engine = await SurrealWasmEngine.connect(url.toString(), options);
+        const { onLog, onSlowQuery, onAudit, ...rest } = options ?? {};
+        if (onLog) {
+            SurrealWasmEngine.set_log_handler(onLog);
+        }
+
+        this.#engine = await SurrealWasmEngine.connect(url.toString(), options ? rest : undefined);
         this.#active = true;
 
         this.#abortController = new AbortController();
@@ -32,30 +44,98 @@ export class LocalEngineBroker implements EngineBroker {
             onNotification,
             this.#abortController.signal,
         );
+
+        if (onSlowQuery) {
+            readRecords(
+                this.#engine.slow_query_stream(),
+                onSlowQuery,
+                this.#abortController.signal,
+            );
+        }
+
+        if (onAudit) {
+            readRecords(this.#engine.audit_stream(), onAudit, this.#abortController.signal);
+        }
+    }
+
+    async execute<T>(payload: Uint8Array, decode: (response: Uint8Array) => T): Promise<T> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        const response = await this.#engine.execute(payload);
+
+        // Decode straight from the memory of the module, before anything else runs in it
+        try {
+            return decode(response.view());
+        } finally {
+            response.free();
+        }
     }
 
-    execute(payload: Uint8Array) {
+    async importSql(data: string, session?: string): Promise<void> {
         if (!this.#active || !this.#engine) {
             throw new ConnectionUnavailableError();
         }
 
-        return this.#engine.execute(payload);
+        return this.#engine.import(data, session);
     }
 
-    async importSql(data: string): Promise<void> {
+    async exportSql(options: Uint8Array, session?: string): Promise<string> {
         if (!this.#active || !this.#engine) {
             throw new ConnectionUnavailableError();
         }
 
-        return this.#engine.import(data);
+        return this.#engine.export(options, session);
     }
 
-    async exportSql(options: Uint8Array): Promise<string> {
+    /** Creates a session with the given id, unless it exists already */
+    async attachSession(session: string): Promise<void> {
         if (!this.#active || !this.#engine) {
             throw new ConnectionUnavailableError();
         }
 
-        return this.#engine.export(options);
+        return this.#engine.attach_session(session);
+    }
+
+    async slowQueries(): Promise<SlowQuery[]> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.slow_queries();
+    }
+
+    async recording(): Promise<Uint8Array> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.recording();
+    }
+
+    async pinSession(session: string | undefined, pin: SessionPin): Promise<void> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#engine.pin_session(session, pin);
+    }
+
+    async intercept(interceptors: EncodedInterceptor[]): Promise<void> {
+        if (!this.#active || !this.#engine) {
+            throw new ConnectionUnavailableError();
+        }
+
+        for (const { before, after } of interceptors) {
+            this.#engine.add_interceptor(before, after);
+        }
+    }
+
+    async setLogFilter(directives: string): Promise<void> {
+        await initializeLibrary();
+
+        SurrealWasmEngine.set_log_filter(directives);
     }
 
     async close() {
@@ -1,13 +1,20 @@
 import { ConnectionUnavailableError } from "surrealdb";
-import { SurrealWasmEngine } from "../../wasm/surrealdb";
-import { initializeLibrary, readNotifications } from "../common";
+import {
+    type AuditRecord,
+    type LogRecord,
+    type SlowQuery,
+    SurrealWasmEngine,
+} from "../../wasm/surrealdb";
+import { initializeLibrary, readNotifications, readRecords } from "../common";
 import {
     type ConnectRequest,
     type ExecuteRequest,
     type ExportSqlRequest,
     type ImportSqlRequest,
+    type PinSessionRequest,
     type RequestMessage,
     RequestType,
+    type SetLogFilterRequest,
     ResponseType,
 } from "./worker-contract";
 
@@ -22,6 +29,12 @@ async function handleConnect(request: ConnectRequest): Promise<void> {
 
     await initializeLibrary();
 
+    SurrealWasmEngine.set_log_handler(
+        request.log
+            ? (record: LogRecord) => self.postMessage({ type: ResponseType.LOG, record })
+            : null,
+    );
+
     instance = await SurrealWasmEngine.connect(request.url, request.options);
 
     abortController = new AbortController();
@@ -35,6 +48,22 @@ async function handleConnect(request: ConnectRequest): Promise<void> {
         },
         abortController.signal,
     );
+
+    if (request.slowQueries) {
+        readRecords<SlowQuery>(
+            instance.slow_query_stream(),
+            (query) => self.postMessage({ type: ResponseType.SLOW_QUERY, query }),
+            abortController.signal,
+        );
+    }
+
+    if (request.audit) {
+        readRecords<AuditRecord>(
+            instance.audit_stream(),
+            (record) => self.postMessage({ type: ResponseType.AUDIT, record }),
+            abortController.signal,
+        );
+    }
 }
 
 async function handleExecute(request: ExecuteRequest): Promise<Uint8Array> {
@@ -42,7 +71,14 @@ async function handleExecute(request: ExecuteRequest): Promise<Uint8Array> {
         throw new ConnectionUnavailableError();
     }
 
-    return instance.execute(request.payload);
+    const response = await instance.execute(request.payload);
+
+    // The memory of the module cannot be transferred, so the response is copied once
+    try {
+        return response.view().slice();
+    } finally {
+        response.free();
+    }
 }
 
 async function handleImportSql(request: ImportSqlRequest): Promise<void> {
@@ -61,6 +97,36 @@ async function handleExportSql(request: ExportSqlRequest): Promise<string> {
     return instance.export(request.options);
 }
 
+async function handleSetLogFilter(request: SetLogFilterRequest): Promise<void> {
+    await initializeLibrary();
+
+    SurrealWasmEngine.set_log_filter(request.directives);
+}
+
+async function handleSlowQueries(): Promise<SlowQuery[]> {
+    if (!instance) {
+        throw new ConnectionUnavailableError();
+    }
+
+    return instance.slow_queries();
+}
+
+async function handleRecording(): Promise<Uint8Array> {
+    if (!instance) {
+        throw new ConnectionUnavailableError();
+    }
+
+    return instance.recording();
+}
+
+async function handlePinSession(request: PinSessionRequest): Promise<void> {
+    if (!instance) {
+        throw new ConnectionUnavailableError();
+    }
+
+    return instance.pin_session(request.session, request.pin);
+}
+
 async function handleClose(): Promise<void> {
     abortController?.abort();
     await cancelNotifications?.();
@@ -98,6 +164,26 @@ self.addEventListener("message", async (event) => {
                 break;
             }
 
+            case RequestType.SET_LOG_FILTER: {
+                result = await handleSetLogFilter(message.data);
+                break;
+            }
+
+            case RequestType.SLOW_QUERIES: {
+                result = await handleSlowQueries();
+                break;
+            }
+
+            case RequestType.RECORDING: {
+                result = await handleRecording();
+                break;
+            }
+
+            case RequestType.PIN_SESSION: {
+                result = await handlePinSession(message.data);
+                break;
+            }
+
             case RequestType.CLOSE: {
                 await handleClose();
                 result = undefined;
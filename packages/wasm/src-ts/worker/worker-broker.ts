@@ -1,10 +1,10 @@
 import { ConnectionUnavailableError } from "surrealdb";
 import { getIncrementalID } from "../../../sdk/src/internal/get-incremental-id";
-import type { ConnectionOptions } from "../../wasm/surrealdb";
-import type { EngineBroker } from "../common";
+import type { AuditRecord, LogRecord, SessionPin, SlowQuery } from "../../wasm/surrealdb";
+import type { EngineBroker, WasmEngineOptions } from "../common";
 import { RequestType, ResponseType, type WorkerMessage } from "./worker-contract";
 
-export interface WasmWorkerOptions extends ConnectionOptions {
+export interface WasmWorkerOptions extends WasmEngineOptions {
     createWorker?: () => Worker;
 }
 
@@ -19,6 +19,9 @@ export class WorkerEngineBroker implements EngineBroker {
     #markReady: (() => void) | undefined;
     #promiseResolvers = new Map<string, PromiseResolver<unknown>>();
     #handleNotification: ((data: Uint8Array) => void) | undefined;
+    #handleLog: ((record: LogRecord) => void) | undefined;
+    #handleSlowQuery: ((query: SlowQuery) => void) | undefined;
+    #handleAudit: ((record: AuditRecord) => void) | undefined;
 
     get isConnected() {
         return !!this.#worker;
@@ -30,6 +33,9 @@ export class WorkerEngineBroker implements EngineBroker {
         onNotification: (data: Uint8Array) => void,
     ) {
         this.#handleNotification = onNotification;
+        this.#handleLog = options?.onLog;
+        this.#handleSlowQuery = options?.onSlowQuery;
+        this.#handleAudit = options?.onAudit;
         this.#worker =
             options?.createWorker?.() ??
             new Worker(new URL(/* @vite-ignore */ "./worker-agent.mjs", import.meta.url), {
@@ -50,23 +56,31 @@ export class WorkerEngineBroker implements EngineBroker {
                 options: {
                     ...options,
                     createWorker: undefined,
+                    onLog: undefined,
+                    onSlowQuery: undefined,
+                    onAudit: undefined,
                 },
+                log: !!options?.onLog,
+                slowQueries: !!options?.onSlowQuery,
+                audit: !!options?.onAudit,
             },
         });
     }
 
-    execute(payload: Uint8Array): Promise<Uint8Array> {
+    async execute<T>(payload: Uint8Array, decode: (response: Uint8Array) => T): Promise<T> {
         if (!this.#worker) {
             throw new ConnectionUnavailableError();
         }
 
-        return this.#send<Uint8Array>(
+        const response = await this.#send<Uint8Array>(
             {
                 type: RequestType.EXECUTE,
                 data: { payload },
             },
             [payload.buffer as ArrayBuffer],
         );
+
+        return decode(response);
     }
 
     async importSql(data: string): Promise<void> {
@@ -87,6 +101,43 @@ export class WorkerEngineBroker implements EngineBroker {
         ]);
     }
 
+    async slowQueries(): Promise<SlowQuery[]> {
+        if (!this.#worker) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#send<SlowQuery[]>({ type: RequestType.SLOW_QUERIES, data: undefined });
+    }
+
+    async recording(): Promise<Uint8Array> {
+        if (!this.#worker) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#send<Uint8Array>({ type: RequestType.RECORDING, data: undefined });
+    }
+
+    async setLogFilter(directives: string): Promise<void> {
+        if (!this.#worker) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#send<void>({ type: RequestType.SET_LOG_FILTER, data: { directives } });
+    }
+
+    async pinSession(session: string | undefined, pin: SessionPin): Promise<void> {
+        if (!this.#worker) {
+            throw new ConnectionUnavailableError();
+        }
+
+        return this.#send<void>({ type: RequestType.PIN_SESSION, data: { session, pin } });
+    }
+
+    async intercept(): Promise<void> {
+        // Functions cannot be sent to the worker running the engine
+        throw new Error("Interceptors are not supported by the worker engine broker");
+    }
+
     async close() {
         if (this.#worker) {
             await this.#send<void>({
@@ -99,6 +150,9 @@ export class WorkerEngineBroker implements EngineBroker {
         this.#worker = undefined;
         this.#promiseResolvers.clear();
         this.#handleNotification = undefined;
+        this.#handleLog = undefined;
+        this.#handleSlowQuery = undefined;
+        this.#handleAudit = undefined;
         this.#ready = Promise.resolve();
         this.#markReady = undefined;
     }
@@ -132,6 +186,21 @@ export class WorkerEngineBroker implements EngineBroker {
                 this.#handleNotification?.(message.data);
                 break;
             }
+
+            case ResponseType.LOG: {
+                this.#handleLog?.(message.record);
+                break;
+            }
+
+            case ResponseType.SLOW_QUERY: {
+                this.#handleSlowQuery?.(message.query);
+                break;
+            }
+
+            case ResponseType.AUDIT: {
+                this.#handleAudit?.(message.record);
+                break;
+            }
         }
     }
 
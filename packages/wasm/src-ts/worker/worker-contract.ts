@@ -1,10 +1,20 @@
-import type { ConnectionOptions } from "../../wasm/surrealdb";
+import type {
+    AuditRecord,
+    ConnectionOptions,
+    LogRecord,
+    SessionPin,
+    SlowQuery,
+} from "../../wasm/surrealdb";
 
 export const RequestType = {
     CONNECT: "connect",
     EXECUTE: "execute",
     IMPORT_SQL: "importSql",
     EXPORT_SQL: "exportSql",
+    SET_LOG_FILTER: "setLogFilter",
+    SLOW_QUERIES: "slowQueries",
+    RECORDING: "recording",
+    PIN_SESSION: "pinSession",
     CLOSE: "close",
 } as const;
 
@@ -14,6 +24,9 @@ export const ResponseType = {
     READY: "ready",
     RESPONSE: "response",
     NOTIFICATION: "notification",
+    LOG: "log",
+    SLOW_QUERY: "slowQuery",
+    AUDIT: "audit",
     ERROR: "error",
 } as const;
 
@@ -22,6 +35,12 @@ export type ResponseType = (typeof ResponseType)[keyof typeof ResponseType];
 export interface ConnectRequest {
     url: string;
     options: ConnectionOptions | undefined;
+    /** Whether to forward the tracing records of the engine */
+    log: boolean;
+    /** Whether to forward each slow query as it is recorded */
+    slowQueries: boolean;
+    /** Whether to forward the audit records */
+    audit: boolean;
 }
 
 export interface ExecuteRequest {
@@ -36,11 +55,24 @@ export interface ExportSqlRequest {
     options: Uint8Array;
 }
 
+export interface SetLogFilterRequest {
+    directives: string;
+}
+
+export interface PinSessionRequest {
+    session: string | undefined;
+    pin: SessionPin;
+}
+
 export type RequestData =
     | { type: typeof RequestType.CONNECT; data: ConnectRequest }
     | { type: typeof RequestType.EXECUTE; data: ExecuteRequest }
     | { type: typeof RequestType.IMPORT_SQL; data: ImportSqlRequest }
     | { type: typeof RequestType.EXPORT_SQL; data: ExportSqlRequest }
+    | { type: typeof RequestType.SET_LOG_FILTER; data: SetLogFilterRequest }
+    | { type: typeof RequestType.SLOW_QUERIES; data: undefined }
+    | { type: typeof RequestType.RECORDING; data: undefined }
+    | { type: typeof RequestType.PIN_SESSION; data: PinSessionRequest }
     | { type: typeof RequestType.CLOSE; data: undefined };
 
 export type RequestMessage = RequestData & {
@@ -64,8 +96,30 @@ export interface NotificationMessage {
     data: Uint8Array;
 }
 
+export interface LogMessage {
+    type: typeof ResponseType.LOG;
+    record: LogRecord;
+}
+
+export interface SlowQueryMessage {
+    type: typeof ResponseType.SLOW_QUERY;
+    query: SlowQuery;
+}
+
+export interface AuditMessage {
+    type: typeof ResponseType.AUDIT;
+    record: AuditRecord;
+}
+
 export interface ReadyMessage {
     type: typeof ResponseType.READY;
 }
 
-export type WorkerMessage = ResponseMessage | ErrorMessage | NotificationMessage | ReadyMessage;
+export type WorkerMessage =
+    | ResponseMessage
+    | ErrorMessage
+    | NotificationMessage
+    | LogMessage
+    | SlowQueryMessage
+    | AuditMessage
+    | ReadyMessage;